@@ -0,0 +1,9 @@
+// SpriteStudio のプロジェクトをランタイム用データに変換するコマンドの例
+// このコマンドはスプラッシュ用のキー定義(SplashTranslation)でしか変換できない
+// ゲームのデータはゲーム側のクレートに自身の AnimationFile を指定して
+// importer::converter_main を呼び出す bin を用意して変換する(importer::converter_main を参照)
+use amethyst_sprite_studio::{importer, splash::SplashTranslation};
+
+fn main() {
+    importer::converter_main::<SplashTranslation>();
+}
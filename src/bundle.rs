@@ -1,6 +1,10 @@
 use crate::{
-    resource::data::AnimationData,
-    system::{AnimationTimeIncrementSystem, AnimationTransitionSystem, RootTranslateSystem},
+    resource::{data::AnimationData, state_machine::StateMachine},
+    system::{
+        AnimationBlendSystem, AnimationLayerSystem, AnimationNodeSystem,
+        AnimationTimeIncrementSystem, AnimationTransitionSystem, AttachmentSystem, HitboxSystem,
+        IndependentEffectSystem, IndependentInstanceSystem, PartTintSystem, RootTranslateSystem,
+    },
     traits::translate_animation::TranslateAnimation,
 };
 
@@ -13,6 +17,14 @@ use amethyst::{
 };
 use std::marker::PhantomData;
 
+// アニメーションの再生，ノード作成，当たり判定と追従のシステム一式
+// ノードは Parent をたどってローカルの Transform から作るので TransformSystem の実行順に依存しない
+// Attachment を付けたエンティティの Transform は "attachment" が書き換えるので，
+// 同じフレームの描画に反映させるには TransformBundle をこのバンドルの後に追加して依存させる
+//
+// game_data
+//     .with_bundle(SpriteStudioBundle::<MyAnimationFile>::new())?
+//     .with_bundle(TransformBundle::new().with_dep(&["attachment"]))?
 pub struct SpriteStudioBundle<T> {
     _marker: PhantomData<T>,
 }
@@ -40,12 +52,24 @@ where
             &[],
         );
 
+        builder.add(
+            Processor::<StateMachine<T>>::new(),
+            "animation_state_machine_processor",
+            &[],
+        );
+
         builder.add(
             AnimationTimeIncrementSystem::new(),
             "animation_time_increment",
             &[],
         );
 
+        builder.add(AnimationBlendSystem::<T>::new(), "animation_blend", &[]);
+
+        builder.add(AnimationLayerSystem::<T>::new(), "animation_layer", &[]);
+
+        builder.add(PartTintSystem::new(), "part_tint", &[]);
+
         builder.add_barrier();
 
         builder.add(
@@ -58,6 +82,36 @@ where
 
         builder.add(RootTranslateSystem::<T>::new(), "root_translate", &[]);
 
+        builder.add(
+            IndependentInstanceSystem::<T>::new(),
+            "independent_instance",
+            &["root_translate"],
+        );
+
+        builder.add(
+            IndependentEffectSystem::<T>::new(),
+            "independent_effect",
+            &[],
+        );
+
+        builder.add(
+            AnimationNodeSystem::<T>::new(),
+            "animation_nodes",
+            &[
+                "root_translate",
+                "independent_instance",
+                "independent_effect",
+            ],
+        );
+
+        builder.add(HitboxSystem::<T>::new(), "hitbox", &["animation_nodes"]);
+
+        builder.add(
+            AttachmentSystem::<T>::new(),
+            "attachment",
+            &["animation_nodes"],
+        );
+
         Ok(())
     }
 }
@@ -1,7 +1,29 @@
+mod animation_blend;
+mod animation_layers;
 mod animation_nodes;
+mod animation_queue;
+mod animation_state_machine;
 mod animation_time;
+mod attachment;
+mod cell_override;
+mod hitboxes;
+mod independent_effects;
+mod independent_instance;
+mod part_tints;
 mod play_animation_key;
 
-pub use animation_nodes::{AnimationNodes, BuildRequireData, Node};
-pub use animation_time::AnimationTime;
+pub use animation_blend::AnimationBlend;
+pub use animation_layers::{AnimationLayer, AnimationLayers};
+pub use animation_nodes::{
+    AnimationNodes, BuildRequireData, MeshNode, Node, NodeOverrideData, NodeOverrides,
+};
+pub use animation_queue::AnimationQueue;
+pub use animation_state_machine::AnimationStateMachine;
+pub use animation_time::{AnimationTime, LoopMode};
+pub use attachment::Attachment;
+pub use cell_override::CellOverride;
+pub use hitboxes::{CollisionShape, Hitboxes};
+pub use independent_effects::IndependentEffects;
+pub use independent_instance::IndependentInstance;
+pub use part_tints::PartTints;
 pub use play_animation_key::PlayAnimationKey;
@@ -0,0 +1,74 @@
+use crate::{components::AnimationTime, traits::animation_file::AnimationFile};
+use amethyst::ecs::{Component, DenseVecStorage};
+
+// アニメーション遷移時のクロスフェード
+// 遷移元のアニメーションのキーと再生時間をフェードが終わるまで保持する
+// 遷移元が同じパックの場合だけパーツごとに補間する(パーツの構成が違うと対応が取れない)
+pub struct AnimationBlend<T>
+where
+    T: AnimationFile,
+{
+    pack: T::PackKey,
+    animation: T::AnimationKey,
+    time: AnimationTime, // 遷移元の再生時間
+    duration: f32,       // フェードにかける秒数
+    elapsed: f32,        // フェード開始からの秒数
+}
+
+impl<T> AnimationBlend<T>
+where
+    T: AnimationFile,
+{
+    pub fn new(
+        pack: T::PackKey,
+        animation: T::AnimationKey,
+        time: AnimationTime,
+        duration: f32,
+    ) -> Self {
+        AnimationBlend {
+            pack,
+            animation,
+            time,
+            duration,
+            elapsed: 0.,
+        }
+    }
+
+    pub fn pack(&self) -> &T::PackKey {
+        &self.pack
+    }
+
+    pub fn animation(&self) -> &T::AnimationKey {
+        &self.animation
+    }
+
+    pub fn time(&self) -> &AnimationTime {
+        &self.time
+    }
+
+    // 遷移先の割合(0.0 で遷移元，1.0 で遷移先)
+    pub fn rate(&self) -> f32 {
+        if self.duration <= 0. {
+            1.
+        } else {
+            (self.elapsed / self.duration).min(1.)
+        }
+    }
+
+    pub fn is_end(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    // 遷移元の再生時間も進める
+    pub(crate) fn add_time(&mut self, delta: f32) {
+        self.time.add_time(delta);
+        self.elapsed += delta;
+    }
+}
+
+impl<T> Component for AnimationBlend<T>
+where
+    T: AnimationFile,
+{
+    type Storage = DenseVecStorage<Self>;
+}
@@ -0,0 +1,204 @@
+use crate::{
+    components::{AnimationTime, LoopMode},
+    traits::animation_file::AnimationFile,
+};
+use amethyst::ecs::{Component, DenseVecStorage};
+use std::collections::BTreeSet;
+
+// PlayAnimationKey のアニメーションに重ねて再生するアニメーション
+// PlayAnimationKey と同じパックのアニメーションだけを合成する(パーツの構成が違うと対応が取れない)
+pub struct AnimationLayer<T>
+where
+    T: AnimationFile,
+{
+    pack: T::PackKey,
+    animation: T::AnimationKey,
+    time: AnimationTime,
+    weight: f32,            // 0.0 ~ 1.0 の合成の重み
+    mask: BTreeSet<String>, // 合成するパーツ名(空の場合は全パーツ)
+}
+
+impl<T> AnimationLayer<T>
+where
+    T: AnimationFile,
+{
+    // 終了後は最初から再生する．time_mut().set_loop_mode で変更できる
+    // Once と Clamp は終端のフレームで停止する
+    pub fn new(pack: T::PackKey, animation: T::AnimationKey) -> Self {
+        let mut time = AnimationTime::new();
+        time.set_loop_mode(LoopMode::Loop(None));
+        time.play(None);
+        AnimationLayer {
+            pack,
+            animation,
+            time,
+            weight: 1.,
+            mask: BTreeSet::new(),
+        }
+    }
+
+    pub fn pack(&self) -> &T::PackKey {
+        &self.pack
+    }
+
+    pub fn animation(&self) -> &T::AnimationKey {
+        &self.animation
+    }
+
+    // アニメーションを切り替えて最初から再生する
+    pub fn set_animation(&mut self, pack: T::PackKey, animation: T::AnimationKey) {
+        self.pack = pack;
+        self.animation = animation;
+        self.time.set_play_time(0.);
+        self.time.reset_loop_count();
+        self.time.play(None);
+    }
+
+    pub fn time(&self) -> &AnimationTime {
+        &self.time
+    }
+
+    pub fn time_mut(&mut self) -> &mut AnimationTime {
+        &mut self.time
+    }
+
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
+
+    pub fn set_weight(&mut self, weight: f32) {
+        self.weight = weight.max(0.).min(1.);
+    }
+
+    pub fn set_mask<I, S>(&mut self, part_names: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.mask = part_names.into_iter().map(Into::into).collect();
+    }
+
+    pub fn mask(&self) -> impl Iterator<Item = &str> {
+        self.mask.iter().map(|name| name.as_str())
+    }
+
+    pub fn contains_part(&self, part_name: &str) -> bool {
+        self.mask.is_empty() || self.mask.contains(part_name)
+    }
+
+    // 総フレーム数に収めた再生フレーム
+    // 終端での巻き戻しは AnimationLayerSystem で行う
+    pub fn play_frame(&self, fps: f32, total_frame: usize) -> usize {
+        self.time
+            .current_frame(fps, total_frame)
+            .min(total_frame.saturating_sub(1))
+    }
+}
+
+// アニメーションのレイヤー
+// 後に追加したレイヤーほど上に重なる
+pub struct AnimationLayers<T>
+where
+    T: AnimationFile,
+{
+    layers: Vec<(String, AnimationLayer<T>)>,
+}
+
+impl<T> AnimationLayers<T>
+where
+    T: AnimationFile,
+{
+    pub fn new() -> Self {
+        AnimationLayers { layers: vec![] }
+    }
+
+    // 同じ名前のレイヤーがあれば同じ順番のまま置き換える
+    pub fn insert<S: Into<String>>(&mut self, name: S, layer: AnimationLayer<T>) {
+        let name = name.into();
+        match self.layers.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, l)) => *l = layer,
+            None => self.layers.push((name, layer)),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<AnimationLayer<T>> {
+        let index = self.layers.iter().position(|(n, _)| n == name)?;
+        Some(self.layers.remove(index).1)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AnimationLayer<T>> {
+        self.layers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, layer)| layer)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut AnimationLayer<T>> {
+        self.layers
+            .iter_mut()
+            .find(|(n, _)| n == name)
+            .map(|(_, layer)| layer)
+    }
+
+    // 下のレイヤーから順に返す
+    pub fn layers(&self) -> impl Iterator<Item = (&str, &AnimationLayer<T>)> {
+        self.layers
+            .iter()
+            .map(|(name, layer)| (name.as_str(), layer))
+    }
+
+    pub(crate) fn layers_mut(&mut self) -> impl Iterator<Item = &mut AnimationLayer<T>> {
+        self.layers.iter_mut().map(|(_, layer)| layer)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+}
+
+impl<T> Default for AnimationLayers<T>
+where
+    T: AnimationFile,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Component for AnimationLayers<T>
+where
+    T: AnimationFile,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TestFile;
+
+    const FPS: f32 = 10.;
+    const TOTAL_FRAME: usize = 10;
+
+    #[test]
+    fn loop_by_default() {
+        let mut layer = AnimationLayer::<TestFile>::new(0, 0);
+        assert_eq!(layer.time().loop_mode(), LoopMode::Loop(None));
+
+        layer.time_mut().add_time(1.25);
+        assert_eq!(layer.time_mut().wrap(FPS, TOTAL_FRAME), Some(1));
+        assert_eq!(layer.play_frame(FPS, TOTAL_FRAME), 2);
+    }
+
+    #[test]
+    fn reverse_play_frame() {
+        // 逆再生は終端の時間から最終フレームを再生する
+        let mut layer = AnimationLayer::<TestFile>::new(0, 0);
+        layer.time_mut().set_play_speed(-1.);
+        layer.time_mut().set_play_time(1.);
+        assert_eq!(layer.play_frame(FPS, TOTAL_FRAME), TOTAL_FRAME - 1);
+
+        layer.time_mut().add_time(0.25);
+        assert_eq!(layer.play_frame(FPS, TOTAL_FRAME), 7);
+    }
+}
@@ -1,31 +1,54 @@
 use crate::{
-    components::{AnimationTime, PlayAnimationKey},
-    resource::{
-        animation::Animation, data::AnimationData, name::AnimationName, pack::Pack, AnimationStore,
+    components::{
+        AnimationBlend, AnimationLayer, AnimationLayers, AnimationTime, CellOverride,
+        IndependentEffects, PartTints, PlayAnimationKey,
+    },
+    resource::{animation::Animation, data::AnimationData, mesh::Mesh, pack::Pack, AnimationStore},
+    traits::{animation_file::AnimationFile, interpolate::lerp, AnimationKey, AnimationUser},
+    types::{
+        AlphaBlend, DeformKey, EffectKey, InstanceKey, LinearColor, PartType, SortMode, TextAlign,
+        TextKey, VertexKey,
     },
-    traits::animation_file::AnimationFile,
-    types::InstanceKey,
 };
 use amethyst::{
     assets::AssetStorage,
-    core::{math::Matrix4, Transform},
-    ecs::{Read, ReadStorage},
+    core::{
+        math::{Matrix4, Vector2, Vector3, Vector4},
+        Transform,
+    },
+    ecs::{Component, DenseVecStorage, Entity, Read, ReadStorage, SystemData},
     renderer::resources::Tint,
     renderer::sprite::SpriteSheetHandle,
 };
 use smallvec::SmallVec;
+use std::{cmp::Ordering, sync::Arc};
 
+// AnimationNodeSystem が毎フレーム作成する
+// 描画，当たり判定，追従はこのコンポーネントを読む
 pub struct AnimationNodes<T> {
     play_frame: usize,
+    sort_mode: SortMode,
     nodes: SmallVec<[Node<T>; 32]>,
     instance_nodes: Vec<AnimationNodes<T>>,
 }
 
+impl<T> Component for AnimationNodes<T>
+where
+    T: AnimationUser,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
 impl<T> AnimationNodes<T> {
     pub fn node(&self, part_id: usize) -> Option<&Node<T>> {
         self.nodes.get(part_id)
     }
 
+    // Z ソート後でもパーツ番号からノードを取得する
+    pub fn part_node(&self, part_id: usize) -> Option<&Node<T>> {
+        self.nodes.iter().find(|node| node.part_id == part_id)
+    }
+
     pub fn nodes(&self) -> impl Iterator<Item = &Node<T>> {
         self.nodes.iter()
     }
@@ -41,24 +64,31 @@ impl<T> AnimationNodes<T> {
         let nodes = SmallVec::new();
         AnimationNodes {
             play_frame,
+            sort_mode: SortMode::default(),
             nodes,
             instance_nodes: Vec::with_capacity(4),
         }
     }
 
-    pub(crate) fn sort_by_z(&mut self) {
-        self.nodes.sort_by(|node1, node2| {
-            let z1 = node1.transform.translation().z;
-            let z2 = node2.transform.translation().z;
-            z1.partial_cmp(&z2).unwrap()
+    // アニメーションの設定に合わせて描画順に並べる
+    // NaN は比較できないので同じ値として扱う
+    pub(crate) fn sort(&mut self) {
+        let compare = |v1: f32, v2: f32| v1.partial_cmp(&v2).unwrap_or(Ordering::Equal);
+        let z = |node: &Node<T>| node.transform.translation().z;
+        let sort_mode = self.sort_mode;
+        self.nodes.sort_by(|node1, node2| match sort_mode {
+            SortMode::Priority => {
+                compare(node1.priority, node2.priority).then(node1.part_id.cmp(&node2.part_id))
+            }
+            SortMode::Z => {
+                compare(z(node1), z(node2)).then(compare(node1.priority, node2.priority))
+            }
         });
         for instance in self.instance_nodes.iter_mut() {
-            instance.sort_by_z();
+            instance.sort();
         }
         self.instance_nodes.sort_by(|instance1, instance2| {
-            let z1 = instance1.nodes[0].transform.translation().z;
-            let z2 = instance2.nodes[0].transform.translation().z;
-            z1.partial_cmp(&z2).unwrap()
+            compare(z(&instance1.nodes[0]), z(&instance2.nodes[0]))
         });
     }
 
@@ -66,9 +96,82 @@ impl<T> AnimationNodes<T> {
         self.nodes.push(node);
     }
 
+    // ボーンの影響を受けるメッシュの頂点をスキニングする
+    // ボーンの行列が揃っている必要があるので全パーツのノード作成後，Z ソート前に呼ぶ
+    fn skin_meshes<P, A>(&mut self, pack: &Pack<T, P, A>)
+    where
+        P: AnimationKey,
+        A: AnimationKey,
+    {
+        // バインドポーズはボーンの影響を受けるメッシュがあるパックにだけある
+        let (bind_matrices, inverse_bind_matrices) =
+            match (pack.bind_matrices(), pack.inverse_bind_matrices()) {
+                (Some(bind_matrices), Some(inverse_bind_matrices)) => {
+                    (bind_matrices, inverse_bind_matrices)
+                }
+                _ => return,
+            };
+
+        // バインドポーズから今のボーンの位置へ移動させる行列
+        let skin_matrices = self
+            .nodes
+            .iter()
+            .zip(inverse_bind_matrices)
+            .map(|(node, inverse_bind)| node.global_matrix * inverse_bind)
+            .collect::<Vec<_>>();
+
+        for (part_id, part) in pack.parts().enumerate() {
+            let weights = part.mesh_weights();
+            if weights.is_empty() {
+                continue;
+            }
+            let node = &mut self.nodes[part_id];
+            // 描画時にパーツの行列をかけるので，スキニング後の座標はパーツのローカル座標に戻す
+            let inverse_matrix = match node.global_matrix.try_inverse() {
+                Some(inverse_matrix) => inverse_matrix,
+                None => continue,
+            };
+            let mesh = match node.mesh.as_mut() {
+                Some(mesh) => mesh,
+                None => continue,
+            };
+
+            let mesh_bind = bind_matrices[part_id];
+            for (vertex, weights) in Arc::make_mut(&mut mesh.vertices).iter_mut().zip(weights) {
+                // ウェイトの合計で正規化する
+                let total = weights.iter().map(|w| w.weight()).sum::<f32>();
+                if total <= 0. {
+                    continue;
+                }
+                let bind_position = mesh_bind * Vector4::new(vertex[0], vertex[1], 0., 1.);
+                let position = weights
+                    .iter()
+                    .filter_map(|w| {
+                        let skin_matrix = skin_matrices.get(w.bone_id())?;
+                        Some(skin_matrix * bind_position * (w.weight() / total))
+                    })
+                    .fold(Vector4::zeros(), |sum, position| sum + position);
+                let local = inverse_matrix * position;
+                *vertex = [local.x, local.y];
+            }
+        }
+    }
+
     fn add_instance(&mut self, instance: Self) {
         self.instance_nodes.push(instance);
     }
+
+    // クロスフェード．遷移元の同じパーツ番号のノードから色や変形を補間する
+    // 座標はノード作成時にローカル座標で補間済み
+    // インスタンスなどの子ノードは遷移先のものだけを使う
+    fn blend(&mut self, from: &Self, rate: f32) {
+        for node in self.nodes.iter_mut() {
+            if let Some(from_node) = from.part_node(node.part_id) {
+                node.blend_from(from_node, rate);
+            }
+        }
+        self.sort();
+    }
 }
 
 pub type BuildRequireData<'s, T> = (
@@ -80,11 +183,78 @@ pub type BuildRequireData<'s, T> = (
     Read<'s, AnimationStore<T>>,
 );
 
+// ノード作成時に実行時の設定で上書きするもの
+// 上書きしないものは None のままでよい
+pub struct NodeOverrides<'a, T>
+where
+    T: AnimationFile,
+{
+    pub cell_override: Option<&'a CellOverride>,
+    pub part_tints: Option<&'a PartTints>,
+    pub blend: Option<&'a AnimationBlend<T>>,
+    pub layers: Option<&'a AnimationLayers<T>>,
+    pub independent_effects: Option<&'a IndependentEffects>,
+}
+
+impl<'a, T> Default for NodeOverrides<'a, T>
+where
+    T: AnimationFile,
+{
+    fn default() -> Self {
+        NodeOverrides {
+            cell_override: None,
+            part_tints: None,
+            blend: None,
+            layers: None,
+            independent_effects: None,
+        }
+    }
+}
+
+// エンティティごとの NodeOverrides を取得するためのストレージ
+#[derive(SystemData)]
+pub struct NodeOverrideData<'s, T>
+where
+    T: AnimationFile,
+{
+    cell_overrides: ReadStorage<'s, CellOverride>,
+    part_tints: ReadStorage<'s, PartTints>,
+    blends: ReadStorage<'s, AnimationBlend<T>>,
+    layers: ReadStorage<'s, AnimationLayers<T>>,
+    independent_effects: ReadStorage<'s, IndependentEffects>,
+}
+
+impl<'s, T> NodeOverrideData<'s, T>
+where
+    T: AnimationFile,
+{
+    pub fn get(&self, entity: Entity) -> NodeOverrides<T> {
+        NodeOverrides {
+            cell_override: self.cell_overrides.get(entity),
+            part_tints: self.part_tints.get(entity),
+            blend: self.blends.get(entity),
+            layers: self.layers.get(entity),
+            independent_effects: self.independent_effects.get(entity),
+        }
+    }
+}
+
+// 合成するレイヤーのアニメーションと再生フレーム
+struct LayerFrame<'a, T>
+where
+    T: AnimationFile,
+{
+    animation: &'a Animation<T::UserData>,
+    frame: usize,
+    layer: &'a AnimationLayer<T>,
+}
+
 impl<'s, U> AnimationNodes<U> {
     // 実時間でノードを作成
     pub fn make_node<T>(
         time: &AnimationTime,
         tint: Option<&Tint>,
+        overrides: &NodeOverrides<T>,
         key: Option<(&T::FileId, &T::PackKey, &T::AnimationKey)>,
         root_transform: &Transform,
         root_matrix: &Matrix4<f32>,
@@ -107,19 +277,89 @@ impl<'s, U> AnimationNodes<U> {
         let pack = animation_storage.get(handle)?.pack(pack_id)?;
         let animation = pack.animation(animation_id)?;
 
-        let current_frame = time.play_frame(animation.fps() as f32);
+        let current_frame = time.current_frame(animation.fps() as f32, animation.total_frame());
 
-        Self::make_animation_nodes(
+        let NodeOverrides {
+            cell_override,
+            part_tints,
+            blend,
+            layers,
+            independent_effects,
+        } = *overrides;
+
+        // 同じパックのレイヤーだけを下から順に合成する
+        let layer_frames = layers
+            .into_iter()
+            .flat_map(|layers| layers.layers())
+            .filter(|(_, layer)| layer.weight() > 0. && layer.pack() == pack_id)
+            .filter_map(|(_, layer)| {
+                let animation = pack.animation(layer.animation())?;
+                let frame = layer.play_frame(animation.fps() as f32, animation.total_frame());
+                Some(LayerFrame {
+                    animation,
+                    frame,
+                    layer,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // クロスフェード中は遷移元のアニメーションのノードと補間する
+        // 遷移元が先に終わった場合は最終フレームのままにする
+        let blend = blend.filter(|blend| blend.pack() == pack_id);
+        let from_nodes = blend.and_then(|blend| {
+            pack.animation(blend.animation())
+                .and_then(|from_animation| {
+                    let from_frame = blend
+                        .time()
+                        .play_frame(from_animation.fps() as f32)
+                        .min(from_animation.total_frame().saturating_sub(1));
+                    Self::make_animation_nodes(
+                        from_frame,
+                        root_transform,
+                        root_matrix,
+                        &root_color,
+                        &[0.; 4],
+                        (false, false),
+                        pack,
+                        from_animation,
+                        (id, pack_id, blend.animation()),
+                        cell_override,
+                        part_tints,
+                        None, // 独立動作のエフェクトは遷移先のキーの経過時間なので使わない
+                        &layer_frames,
+                        None,
+                        store,
+                        animation_storage,
+                    )
+                })
+                .map(|from_nodes| (from_nodes, blend.rate()))
+        });
+        let blend_from = from_nodes
+            .as_ref()
+            .map(|(from_nodes, rate)| (from_nodes, *rate));
+
+        let mut nodes = Self::make_animation_nodes(
             current_frame,
             root_transform,
             root_matrix,
             &root_color,
+            &[0.; 4],
+            (false, false),
             pack,
             animation,
-            id,
+            (id, pack_id, animation_id),
+            cell_override,
+            part_tints,
+            independent_effects,
+            &layer_frames,
+            blend_from,
             store,
             animation_storage,
-        )
+        )?;
+        if let Some((from_nodes, rate)) = blend_from {
+            nodes.blend(from_nodes, rate);
+        }
+        Some(nodes)
     }
 
     // インスタンスパーツのノード作成
@@ -131,6 +371,8 @@ impl<'s, U> AnimationNodes<U> {
         root_transform: &Transform,
         root_matrix: &Matrix4<f32>,
         root_color: &[f32; 4],
+        root_color_offset: &[f32; 4],
+        root_flip: (bool, bool),
         store: &AnimationStore<T>,
         animation_storage: &AssetStorage<AnimationData<T>>,
     ) -> Option<AnimationNodes<T::UserData>>
@@ -143,53 +385,214 @@ impl<'s, U> AnimationNodes<U> {
         let pack = animation_storage.get(handle)?.pack(pack_id)?;
         let animation = pack.animation(animation_id)?;
 
-        // アニメーション情報からインスタンスの再生フレームを算出
-        // インスタンスキーには再生開始位置，終了位置が載っている
         // 開始位置と実際の再生フレートの差がインスタンスパーツ上の再生位置
         // この時点で再生速度を考慮する(f32 -> usize キャストは 0 方向へ丸められる)
-        let end_frame = animation.total_frame() - instance.end_offset();
-        let play_frame =
+        let elapsed_frame =
             (((current_frame - key_set_frame) as f32) * instance.speed_rate()) as usize;
-        // 1回の再生時間算出
-        let once_play_time = end_frame - instance.start_offset() + 1;
-        // 再生回数と，その再生フレーム値を算出
-        let played_loop_num = play_frame / once_play_time;
-        let mut current_play_frame = play_frame % once_play_time;
-        if let Some(loop_num) = instance.loop_num() {
-            if loop_num <= played_loop_num {
-                // 再生回数が指定回数を超えてるので終了
-                return None;
-            }
-        }
-
-        // 逆再生の場合か，pingpong再生の奇数ループ目は再生フレーム値が逆になる
-        if instance.reverse() == true || (played_loop_num % 2 == 1 && instance.pingpong() == true) {
-            current_play_frame = once_play_time - current_play_frame;
-        }
+        let play_frame = instance.play_frame(elapsed_frame, animation.total_frame())?;
 
         Self::make_animation_nodes(
-            current_play_frame + instance.start_offset(),
+            play_frame,
             root_transform,
             root_matrix,
             root_color,
+            root_color_offset,
+            root_flip,
             pack,
             animation,
-            id,
+            (id, pack_id, animation_id),
+            None, // パーツ番号が違うので差し替えとレイヤーは参照先には適用しない
+            None,
+            None,
+            &[],
+            None,
             store,
             animation_storage,
         )
     }
 
+    // エフェクトパーツのパーティクルのノード作成
+    fn make_effect_nodes<T>(
+        key_set_frame: usize,
+        current_frame: usize,
+        independent_frame: Option<f32>,
+        effect_key: &EffectKey,
+        (id, part_id, effect_index): (&T::FileId, usize, usize),
+        root_transform: &Transform,
+        root_matrix: &Matrix4<f32>,
+        root_color: &[f32; 4],
+        root_color_offset: &[f32; 4],
+        root_flip: (bool, bool),
+        store: &AnimationStore<T>,
+        animation_storage: &AssetStorage<AnimationData<T>>,
+    ) -> Option<AnimationNodes<T::UserData>>
+    where
+        T: AnimationFile,
+    {
+        let handle = store.get_animation_handle(id)?;
+        let effect = animation_storage.get(handle)?.effect(effect_index)?;
+
+        // キーがセットされてからの経過を再生速度で伸縮し，開始フレームを足したものがエフェクト上の時間
+        // 独立動作のキーは親のフレームではなく実時間の経過フレームを使うので，親のループで巻き戻らない
+        let elapsed_frame = match independent_frame {
+            Some(elapsed_frame) if effect_key.independent() => elapsed_frame,
+            _ => (current_frame - key_set_frame) as f32,
+        };
+        let effect_frame = elapsed_frame * effect_key.speed() + effect_key.start_time() as f32;
+
+        // シードが固定されていなければパーツ番号をシードにして毎回同じ結果にする
+        let particles = effect.particles(effect_frame, part_id as u32);
+        log::trace!(
+            "make effect: {} F, {} particles",
+            effect_frame,
+            particles.len()
+        );
+
+        let mut nodes = AnimationNodes::new(current_frame);
+        for particle in particles {
+            let emitter = match effect.emitter(particle.emitter_id) {
+                Some(emitter) => emitter,
+                None => continue,
+            };
+            let cell = match emitter.cell() {
+                Some(cell) => cell,
+                None => continue,
+            };
+            let handle = match store.get_sprite_handle(id, cell.map_id()) {
+                Some(handle) => handle.clone(),
+                None => continue,
+            };
+
+            let mut local_transform = Transform::default();
+            local_transform.set_translation_xyz(particle.position.0, particle.position.1, 0.);
+            local_transform.set_rotation_2d(particle.rotation);
+            local_transform.set_scale(Vector3::new(particle.scale.0, particle.scale.1, 1.));
+
+            let mut transform = root_transform.clone();
+            transform.concat(&local_transform);
+            transform.translation_mut().z = root_transform.translation().z;
+            let global_matrix = root_matrix * local_transform.matrix();
+
+            let mut color = particle.color;
+            for (i, c) in root_color.iter().enumerate() {
+                color[i] *= c;
+            }
+
+            let mut node = Node::new(part_id, transform, global_matrix, color, false);
+            node.set_flip(root_flip.0, root_flip.1);
+            node.set_blend(*root_color_offset, emitter.alpha_blend());
+            node.set_sprite_info(handle, cell.cell_id());
+            nodes.push(node);
+        }
+
+        // 空のノードは Z ソートできないので返さない
+        if nodes.nodes.is_empty() {
+            None
+        } else {
+            Some(nodes)
+        }
+    }
+
+    // テキストパーツの文字ごとのノード作成
+    // 文字はセル中心をピボットにしたビットマップフォントのセルで描画する
+    fn make_text_nodes<T>(
+        current_frame: usize,
+        text_key: &TextKey,
+        (id, part_id, alpha_blend): (&T::FileId, usize, AlphaBlend),
+        root_transform: &Transform,
+        root_matrix: &Matrix4<f32>,
+        root_color: &[f32; 4],
+        root_color_offset: &[f32; 4],
+        root_flip: (bool, bool),
+        store: &AnimationStore<T>,
+        animation_storage: &AssetStorage<AnimationData<T>>,
+    ) -> Option<AnimationNodes<T::UserData>>
+    where
+        T: AnimationFile,
+    {
+        let handle = store.get_animation_handle(id)?;
+        let font = animation_storage.get(handle)?.font(text_key.font())?;
+        let sprite_sheet = store.get_sprite_handle(id, font.map_id())?;
+
+        let line_height = font.line_height();
+        let scale = if text_key.size() > 0. && line_height > 0. {
+            text_key.size() / line_height
+        } else {
+            1.
+        };
+        // フォントにない文字(空白など)は高さの半分だけ送る
+        let advance = |c: char| {
+            font.glyph(c)
+                .map(|g| g.width())
+                .unwrap_or(line_height * 0.5)
+        };
+
+        // 複数行は行の中心がパーツの原点を中心に並ぶようにする
+        let lines = text_key.text().lines().collect::<Vec<_>>();
+        let top = (lines.len() as f32 - 1.) * 0.5 * line_height;
+
+        let mut nodes = AnimationNodes::new(current_frame);
+        for (line_no, line) in lines.iter().enumerate() {
+            let line_width = line.chars().map(advance).sum::<f32>();
+            let mut pen_x = match text_key.align() {
+                TextAlign::Left => 0.,
+                TextAlign::Center => -line_width * 0.5,
+                TextAlign::Right => -line_width,
+            };
+            let pen_y = top - line_no as f32 * line_height;
+
+            for c in line.chars() {
+                let width = advance(c);
+                if let Some(glyph) = font.glyph(c) {
+                    let mut local_transform = Transform::default();
+                    local_transform.set_translation_xyz(
+                        (pen_x + width * 0.5) * scale,
+                        pen_y * scale,
+                        0.,
+                    );
+                    local_transform.set_scale(Vector3::new(scale, scale, 1.));
+
+                    let mut transform = root_transform.clone();
+                    transform.concat(&local_transform);
+                    transform.translation_mut().z = root_transform.translation().z;
+                    let global_matrix = root_matrix * local_transform.matrix();
+
+                    let mut node = Node::new(part_id, transform, global_matrix, *root_color, false);
+                    node.set_flip(root_flip.0, root_flip.1);
+                    node.set_blend(*root_color_offset, alpha_blend);
+                    node.set_sprite_info(sprite_sheet.clone(), glyph.cell_id());
+                    nodes.push(node);
+                }
+                pen_x += width;
+            }
+        }
+
+        // 空のノードは Z ソートできないので返さない
+        if nodes.nodes.is_empty() {
+            None
+        } else {
+            Some(nodes)
+        }
+    }
+
     // アニメーション，パックデータからノード作成
     fn make_animation_nodes<T>(
         current_frame: usize,
         root_transform: &Transform,
         root_matrix: &Matrix4<f32>,
         root_color: &[f32; 4],
+        root_color_offset: &[f32; 4],
+        root_flip: (bool, bool),
         pack: &Pack<T::UserData, T::PackKey, T::AnimationKey>,
         animation: &Animation<T::UserData>,
-        // インスタンスノードに必要な情報
-        id: &T::FileId,
+        // インスタンスノードに必要な情報(再生中のファイル，パック，アニメーションのキー)
+        key: (&T::FileId, &T::PackKey, &T::AnimationKey),
+        cell_override: Option<&CellOverride>,
+        part_tints: Option<&PartTints>,
+        independent_effects: Option<&IndependentEffects>,
+        layers: &[LayerFrame<T>],
+        // クロスフェードの遷移元のノードと遷移先の割合
+        blend_from: Option<(&AnimationNodes<T::UserData>, f32)>,
         store: &AnimationStore<T>,
         animation_storage: &AssetStorage<AnimationData<T>>,
     ) -> Option<AnimationNodes<T::UserData>>
@@ -203,32 +606,92 @@ impl<'s, U> AnimationNodes<U> {
 
         log::trace!("make node: {} F", current_frame);
 
+        let (id, _, _) = key;
+        // 参照名の解決に再生中のファイルのデータを使う
+        let data = animation_storage.get(store.get_animation_handle(id)?)?;
         let mut nodes = AnimationNodes::new(current_frame);
+        nodes.sort_mode = animation.sort_mode();
         for (part_id, part) in pack.parts().enumerate() {
             log::trace!("\tmake node: part = {}", part_id);
+
+            // レイヤーで上書きするパーツは座標，色と頂点変形を重みで合成する
+            // 切り替わる値(セル，非表示，反転など)は重みが 0.5 以上の一番上のレイヤーのものを使う
+            let part_layers = layers
+                .iter()
+                .filter(|layer| layer.layer.contains_part(part.name()))
+                .collect::<SmallVec<[_; 4]>>();
+            let base = (animation, current_frame);
+            let (animation, current_frame) = part_layers
+                .iter()
+                .rev()
+                .find(|layer| layer.layer.weight() >= 0.5)
+                .map(|layer| (layer.animation, layer.frame))
+                .unwrap_or(base);
+
             // 親ノードの情報を取得,なければ Entity の情報
-            let (parent_transform, parent_hide, parent_matrix) = part
-                .parent_id()
-                .and_then(|p| nodes.node(p as usize))
-                .map(
-                    |Node {
-                         transform,
-                         hide,
-                         global_matrix,
-                         ..
-                     }| (transform.clone(), *hide, *global_matrix),
-                )
-                .unwrap_or((root_transform.clone(), false, *root_matrix));
+            let (mut parent_transform, parent_hide, mut parent_matrix, parent_flip, parent_alpha) =
+                part.parent_id()
+                    .and_then(|p| nodes.node(p as usize))
+                    .map(
+                        |Node {
+                             transform,
+                             hide,
+                             global_matrix,
+                             flip_h,
+                             flip_v,
+                             color,
+                             ..
+                         }| {
+                            (
+                                transform.clone(),
+                                *hide,
+                                *global_matrix,
+                                (*flip_h, *flip_v),
+                                color[3],
+                            )
+                        },
+                    )
+                    .unwrap_or((
+                        root_transform.clone(),
+                        false,
+                        *root_matrix,
+                        root_flip,
+                        root_color[3],
+                    ));
+
+            // スケールを継承しない場合は親のスケールを Entity のスケールに戻す
+            let inheritance = part.inheritance();
+            if inheritance.scale() == false {
+                let (parent_scale_x, parent_scale_y) = matrix_scale(&parent_matrix);
+                let (root_scale_x, root_scale_y) = matrix_scale(root_matrix);
+                // 親のスケールが 0 の場合は戻せないのでそのまま
+                let rate = |root: f32, parent: f32| if parent == 0. { 1. } else { root / parent };
+                parent_matrix *= Matrix4::new_nonuniform_scaling(&Vector3::new(
+                    rate(root_scale_x, parent_scale_x),
+                    rate(root_scale_y, parent_scale_y),
+                    1.,
+                ));
+                parent_transform.set_scale(*root_transform.scale());
+            }
 
             // パーツ座標のグローバル化
             let mut part_transform = parent_transform.clone();
-            let mut local_transform = animation.local_transform(part_id, current_frame);
+            let mut local_transform = layered_transform(base, part_id, &part_layers);
 
             // ルートの移動値は後で足すのでここではゼロとする
             if part_id == crate::constant::ROOT_PART_ID {
                 local_transform.set_translation_xyz(0., 0., 0.);
             }
 
+            // クロスフェード中はローカル座標で補間してから親の行列をかける
+            // 子パーツやインスタンスは補間後の親を基準にするので親子関係が崩れない
+            if let Some((from_node, rate)) =
+                blend_from.and_then(|(from, rate)| Some((from.part_node(part_id)?, rate)))
+            {
+                local_transform =
+                    blend_transform(&from_node.local_transform, &local_transform, rate);
+            }
+
             part_transform.concat(&local_transform);
             part_transform.translation_mut().z =
                 local_transform.translation().z + root_transform.translation().z;
@@ -237,37 +700,88 @@ impl<'s, U> AnimationNodes<U> {
             let global_matrix = parent_matrix * local_transform.matrix();
 
             // パーツカラーのグローバル化
-            let (r, g, b, a) = animation
-                .local_color(part_id, current_frame)
-                .0
-                .into_components();
-            let mut part_color = [r, g, b, a];
+            // 不透明度は親パーツから引き継ぐ(Entity の不透明度は親パーツに含まれている)
+            let mut root_color = *root_color;
+            if inheritance.alpha() {
+                root_color[3] = parent_alpha;
+            }
+            let root_color = &root_color;
+            let mut part_color = layered_color(base, part_id, &part_layers);
             for (i, c) in root_color.iter().enumerate() {
                 part_color[i] *= c;
             }
 
+            // カラーブレンドの加算成分は親の乗算成分をかけて親の加算成分を足す
+            let LinearColor(r, g, b, _) = layered_color_offset(base, part_id, &part_layers);
+            let mut part_color_offset = [r, g, b, 0.];
+            for (i, (c, o)) in root_color.iter().zip(root_color_offset).enumerate() {
+                part_color_offset[i] = part_color_offset[i] * c + o;
+            }
+
+            // 頂点ごとの色も同様に親の色を合成して描画順(右上，左上，右下，左下)に並べる
+            // パーツ全体の色は子ノードに引き継ぐために四隅の平均とする
+            let compose = |corner| compose_root_color(corner, root_color, root_color_offset);
+            let mut vertex_colors = animation
+                .local_vertex_colors(part_id, current_frame)
+                .map(|[lt, rt, lb, rb]| [compose(rt), compose(lt), compose(rb), compose(lb)]);
+            if let Some(corners) = vertex_colors.as_ref() {
+                let channels = part_color.iter_mut().zip(part_color_offset.iter_mut());
+                for (i, (color, offset)) in channels.enumerate() {
+                    *color = corners.iter().map(|(c, _)| c[i]).sum::<f32>() / 4.;
+                    *offset = corners.iter().map(|(_, o)| o[i]).sum::<f32>() / 4.;
+                }
+            }
+
+            // 実行時の色の上書き(ダメージ時の点滅など)
+            let part_tint = part_tints.and_then(|part_tints| part_tints.tint(part.name()));
+            if let Some(part_tint) = part_tint.as_ref() {
+                part_tint.apply(&mut part_color, &mut part_color_offset);
+                for (color, offset) in vertex_colors.iter_mut().flatten() {
+                    part_tint.apply(color, offset);
+                }
+            }
+
+            // 反転は親の反転と打ち消し合う
+            let (local_flip_h, local_flip_v) = animation.local_flip(part_id, current_frame);
+            let part_flip = (
+                (parent_flip.0 && inheritance.flip_h()) ^ local_flip_h,
+                (parent_flip.1 && inheritance.flip_v()) ^ local_flip_v,
+            );
+
+            // ローカルスケールとローカル不透明度はこのパーツの描画にのみ使い，子パーツには継承しない
+            // インスタンスなどの子ノードはこのパーツの描画として扱う
+            let (local_scale_x, local_scale_y) = animation.local_scale(part_id, current_frame);
+            let local_alpha = animation.local_alpha(part_id, current_frame);
+            let draw_matrix = global_matrix
+                * Matrix4::new_nonuniform_scaling(&Vector3::new(local_scale_x, local_scale_y, 1.));
+            let mut draw_color = part_color;
+            draw_color[3] *= local_alpha;
+
             // 独立再生じゃないインスタンスパーツだった場合，このパーツの下にノードを追加する
+            // 独立再生の場合は IndependentInstanceSystem がエンティティを生成する
+            // 参照名で省略されているキーは再生中のものを引き継ぐ
             let instance_node = match (
-                part.refference_animation_name(),
+                part.refference_animation_name()
+                    .and_then(|name| data.resolve_animation_name(name, key)),
                 animation.instance(part_id, current_frame),
             ) {
-                (
-                    Some(AnimationName::FullName { pack, animation }),
-                    Some((instance_frame, instance_key)),
-                ) => {
+                (Some((ref_id, ref_pack, ref_animation)), Some((instance_frame, instance_key))) => {
                     if instance_key.independent() == false {
                         let root_transform = &part_transform;
-                        let root_color = &part_color;
-                        let root_matrix = &global_matrix;
+                        let root_color = &draw_color;
+                        let root_color_offset = &part_color_offset;
+                        let root_matrix = &draw_matrix;
 
                         Self::make_instance_nodes(
                             instance_frame, // キーフレームがセットされたフレーム
                             current_frame,  // 親アニメーションの今のフレーム
                             instance_key,   // インスタンスキー情報
-                            Some((id, pack, animation)),
+                            Some((&ref_id, &ref_pack, &ref_animation)),
                             root_transform,
                             root_matrix,
                             root_color,
+                            root_color_offset,
+                            part_flip,
                             store,
                             animation_storage,
                         )
@@ -278,13 +792,92 @@ impl<'s, U> AnimationNodes<U> {
                 _ => None,
             };
 
-            // 親の非表示情報を引き継いでノード作成
+            // 親の非表示情報を引き継ぐ
+            // 実行時に表示が上書きされていればそちらを優先する
+            let part_hide = match part_tint.and_then(|part_tint| part_tint.visible()) {
+                Some(visible) => visible == false,
+                None => {
+                    (parent_hide && inheritance.hide()) || animation.hide(part_id, current_frame)
+                }
+            };
+
+            // エフェクトパーツのパーティクルもインスタンスと同じく子ノードとして描画する
+            let effect_node = match (
+                part.refference_effect_index(),
+                animation.effect(part_id, current_frame),
+            ) {
+                (Some(effect_index), Some((effect_frame, effect_key))) if part_hide == false => {
+                    // 独立動作のキーの実時間での経過フレーム(IndependentEffects がなければ親のフレームで再生)
+                    let independent_frame = independent_effects
+                        .and_then(|effects| effects.elapsed(part_id, effect_frame))
+                        .map(|elapsed| elapsed * animation.fps() as f32);
+                    Self::make_effect_nodes(
+                        effect_frame,  // キーフレームがセットされたフレーム
+                        current_frame, // 親アニメーションの今のフレーム
+                        independent_frame,
+                        effect_key,
+                        (id, part_id, effect_index),
+                        &part_transform,
+                        &draw_matrix,
+                        &draw_color,
+                        &part_color_offset,
+                        part_flip,
+                        store,
+                        animation_storage,
+                    )
+                }
+                _ => None,
+            };
+
+            // テキストパーツの文字も子ノードとして描画する
+            let text_node = match animation.text(part_id, current_frame) {
+                Some(text_key) if part.part_type() == PartType::Text && part_hide == false => {
+                    Self::make_text_nodes(
+                        current_frame,
+                        text_key,
+                        (id, part_id, part.alpha_blend()),
+                        &part_transform,
+                        &draw_matrix,
+                        &draw_color,
+                        &part_color_offset,
+                        part_flip,
+                        store,
+                        animation_storage,
+                    )
+                }
+                _ => None,
+            };
+
             let mut node = Node::new(
+                part_id,
                 part_transform,
                 global_matrix,
                 part_color,
-                parent_hide || animation.hide(part_id, current_frame),
+                part_hide,
             );
+            node.set_local_transform(local_transform);
+            node.set_flip(part_flip.0, part_flip.1);
+            node.set_blend(part_color_offset, part.alpha_blend());
+            if let Some(corners) = vertex_colors {
+                node.set_vertex_colors(corners);
+            }
+            node.set_local_alpha(local_alpha);
+            node.set_local_scale(local_scale_x, local_scale_y);
+            node.set_priority(animation.priority(part_id, current_frame));
+            // 当たり判定の半径はセットアップ上にあるかもしれない
+            let bounding_radius = animation.bounding_radius(part_id, current_frame).or(pack
+                .setup_info()
+                .and_then(|setup| setup.bounding_radius(part_id, 0)));
+            node.set_bounding_radius(bounding_radius);
+
+            // セルの描画サイズ，原点，UV の変形
+            let (size_x, size_y) = animation.local_size(part_id, current_frame);
+            let (pivot_x, pivot_y) = animation.local_pivot_offset(part_id, current_frame);
+            node.set_cell_transform([size_x, size_y], [pivot_x, pivot_y]);
+            let ((uv_x, uv_y), uv_rotated, (uv_scale_x, uv_scale_y)) =
+                animation.local_uv_transform(part_id, current_frame);
+            node.set_uv_transform([uv_x, uv_y], uv_rotated, [uv_scale_x, uv_scale_y]);
+            node.set_mask(part.part_type() == PartType::Mask, part.mask_influence());
 
             //-------------------------------------
             // ユーザーデータとはスプライトシートのハンドルをここでセット
@@ -294,21 +887,41 @@ impl<'s, U> AnimationNodes<U> {
             }
 
             // スプライトシート
-            if let Some((handle, sprite_no)) = animation
-                .cell(part_id, current_frame)
-                .or(pack.setup_info().and_then(|setup| setup.cell(part_id, 0))) // セルがセットアップ上にあるかもしれない
-                .and_then(|cell| {
-                    let map_id = cell.map_id();
-                    let cell_id = cell.cell_id();
-                    let handle = store.get_sprite_handle(id, map_id)?;
-                    Some((handle.clone(), cell_id))
-                })
-            {
+            // 差し替えが指定されていればそのセル(セルマップ番号，セル番号)を使う
+            let cell = cell_override
+                .and_then(|cell_override| cell_override.cell(part_id, part.name()))
+                .or_else(|| {
+                    animation
+                        .cell(part_id, current_frame)
+                        .or(pack.setup_info().and_then(|setup| setup.cell(part_id, 0))) // セルがセットアップ上にあるかもしれない
+                        .map(|cell| (cell.map_id(), cell.cell_id()))
+                });
+            let sprite_sheet_set =
+                cell_override.and_then(|cell_override| cell_override.sprite_sheet_set());
+            if let Some((handle, sprite_no)) = cell.and_then(|(map_id, cell_id)| {
+                // セットに同じセルマップのスプライトシートがなければ元のものを使う
+                let handle = sprite_sheet_set
+                    .and_then(|set| store.get_sprite_set_handle(id, set, map_id))
+                    .or_else(|| store.get_sprite_handle(id, map_id))?;
+                Some((handle.clone(), cell_id))
+            }) {
                 log::trace!("\tmake sprite: {:?}, {}", handle, sprite_no);
                 node.set_sprite_info(handle, sprite_no);
             }
 
-            if let Some(deforms) = animation.vertex(part_id, current_frame) {
+            // メッシュパーツはセルのメッシュに頂点変形を適用する
+            if part.part_type() == PartType::Mesh {
+                if let Some(mesh) = cell.and_then(|(map_id, cell_id)| {
+                    animation_storage
+                        .get(store.get_animation_handle(id)?)?
+                        .mesh(map_id, cell_id)
+                }) {
+                    let deform = animation.deform(part_id, current_frame);
+                    node.set_mesh(MeshNode::new(mesh, deform.as_ref()));
+                }
+            }
+
+            if let Some(deforms) = layered_vertex(base, part_id, &part_layers) {
                 node.set_deform(
                     [deforms.lt().0, deforms.lt().1],
                     [deforms.lb().0, deforms.lb().1],
@@ -321,17 +934,133 @@ impl<'s, U> AnimationNodes<U> {
             if let Some(instance) = instance_node {
                 nodes.add_instance(instance);
             }
+            if let Some(effect) = effect_node {
+                nodes.add_instance(effect);
+            }
+            if let Some(text) = text_node {
+                nodes.add_instance(text);
+            }
 
             log::trace!("\tmake end: part = {}", part_id);
             nodes.push(node);
         }
-        nodes.sort_by_z();
+        nodes.skin_meshes(pack);
+        nodes.sort();
         Some(nodes)
     }
 }
 
+// レイヤーを下から順に重ねたローカル座標
+fn layered_transform<T>(
+    (animation, frame): (&Animation<T::UserData>, usize),
+    part_id: usize,
+    layers: &[&LayerFrame<T>],
+) -> Transform
+where
+    T: AnimationFile,
+{
+    layers.iter().fold(
+        animation.local_transform(part_id, frame),
+        |transform, layer| {
+            let layer_transform = layer.animation.local_transform(part_id, layer.frame);
+            blend_transform(&transform, &layer_transform, layer.layer.weight())
+        },
+    )
+}
+
+fn layered_color<T>(
+    (animation, frame): (&Animation<T::UserData>, usize),
+    part_id: usize,
+    layers: &[&LayerFrame<T>],
+) -> [f32; 4]
+where
+    T: AnimationFile,
+{
+    let color = |animation: &Animation<T::UserData>, frame| {
+        let (r, g, b, a) = animation.local_color(part_id, frame).0.into_components();
+        Vector4::new(r, g, b, a)
+    };
+    layers
+        .iter()
+        .fold(color(animation, frame), |c, layer| {
+            lerp(c, color(layer.animation, layer.frame), layer.layer.weight())
+        })
+        .into()
+}
+
+fn layered_color_offset<T>(
+    (animation, frame): (&Animation<T::UserData>, usize),
+    part_id: usize,
+    layers: &[&LayerFrame<T>],
+) -> LinearColor
+where
+    T: AnimationFile,
+{
+    layers.iter().fold(
+        animation.local_color_offset(part_id, frame),
+        |offset, layer| {
+            let layer_offset = layer.animation.local_color_offset(part_id, layer.frame);
+            lerp(offset, layer_offset, layer.layer.weight())
+        },
+    )
+}
+
+// 頂点変形は両方にキーがあれば補間し，片方にしかなければ切り替わる値と同じく扱う
+fn layered_vertex<T>(
+    (animation, frame): (&Animation<T::UserData>, usize),
+    part_id: usize,
+    layers: &[&LayerFrame<T>],
+) -> Option<VertexKey>
+where
+    T: AnimationFile,
+{
+    layers
+        .iter()
+        .fold(animation.vertex(part_id, frame), |vertex, layer| {
+            let weight = layer.layer.weight();
+            match (vertex, layer.animation.vertex(part_id, layer.frame)) {
+                (Some(vertex), Some(layer_vertex)) => Some(lerp(vertex, layer_vertex, weight)),
+                (_, layer_vertex) if weight >= 0.5 => layer_vertex,
+                (vertex, _) => vertex,
+            }
+        })
+}
+
+// 移動とスケールは線形補間し，回転は球面線形補間する
+fn blend_transform(from: &Transform, to: &Transform, rate: f32) -> Transform {
+    let mut transform = to.clone();
+    transform.set_translation(lerp(*from.translation(), *to.translation(), rate));
+    transform.set_scale(lerp(*from.scale(), *to.scale(), rate));
+    transform.set_rotation(from.rotation().slerp(to.rotation(), rate));
+    transform
+}
+
+// マトリクスの X 軸と Y 軸の長さ
+fn matrix_scale(matrix: &Matrix4<f32>) -> (f32, f32) {
+    let scale_x = Vector3::new(matrix[(0, 0)], matrix[(1, 0)], matrix[(2, 0)]).norm();
+    let scale_y = Vector3::new(matrix[(0, 1)], matrix[(1, 1)], matrix[(2, 1)]).norm();
+    (scale_x, scale_y)
+}
+
+// 乗算成分と加算成分の組に親の色を合成する
+fn compose_root_color(
+    (LinearColor(r, g, b, a), LinearColor(or, og, ob, _)): (LinearColor, LinearColor),
+    root_color: &[f32; 4],
+    root_color_offset: &[f32; 4],
+) -> ([f32; 4], [f32; 4]) {
+    let mut color = [r, g, b, a];
+    let mut offset = [or, og, ob, 0.];
+    for (i, (c, o)) in root_color.iter().zip(root_color_offset).enumerate() {
+        color[i] *= c;
+        offset[i] = offset[i] * c + o;
+    }
+    (color, offset)
+}
+
 pub struct Node<T> {
+    pub part_id: usize,
     pub transform: Transform,
+    pub local_transform: Transform, // 親パーツからの相対座標
     pub global_matrix: Matrix4<f32>,
     pub hide: bool,
     pub user: Option<T>,
@@ -339,17 +1068,71 @@ pub struct Node<T> {
     pub sprite_no: Option<usize>,
     pub color: [f32; 4],
     pub deform_offsets: [[f32; 2]; 4],
+    pub flip_h: bool,            // 親の反転を含めた左右反転
+    pub flip_v: bool,            // 親の反転を含めた上下反転
+    pub mask: bool,              // マスクパーツ(ステンシルにのみ書き込む)
+    pub mask_influence: bool,    // マスクパーツの影響を受ける
+    pub mesh: Option<MeshNode>,  // メッシュパーツの頂点
+    pub color_offset: [f32; 4],  // カラーブレンドでテクスチャの色に足す値
+    pub alpha_blend: AlphaBlend, // 描画先との合成方法
+    // 頂点ごとの色(右上，左上，右下，左下)．頂点ごとの指定がなければパーツ全体の色
+    pub vertex_colors: [[f32; 4]; 4],
+    pub vertex_color_offsets: [[f32; 4]; 4],
+    pub priority: f32,          // 描画優先度
+    pub local_scale: [f32; 2],  // 描画時のみかけるスケール
+    pub size: [Option<f32>; 2], // 描画サイズ．なければセルのサイズ
+    pub pivot_offset: [f32; 2], // セルのサイズに対する原点の移動量(上方向が正)
+    pub uv_offset: [f32; 2],    // テクスチャ座標の移動量
+    pub uv_rotation: f32,       // セル中心を基準にした UV の回転(度)
+    pub uv_scale: [f32; 2],     // セル中心を基準にした UV のスケール
+    // 当たり判定の円の半径．なければセルの対角線の半分
+    pub bounding_radius: Option<f32>,
+}
+
+// メッシュパーツの描画用頂点
+// 頂点座標は変形を適用したパーツのローカル座標
+// 形状はメッシュのデータと共有し，頂点を変形するときだけ複製する
+pub struct MeshNode {
+    pub vertices: Arc<Vec<[f32; 2]>>,
+    pub tex_coords: Arc<Vec<[f32; 2]>>, // セルの左上を 0.0, 右下を 1.0 とした位置
+    pub indices: Arc<Vec<u32>>,
+}
+
+impl MeshNode {
+    fn new(mesh: &Mesh, deform: Option<&DeformKey>) -> Self {
+        let vertices = match deform {
+            Some(deform) => Arc::new(
+                mesh.vertices()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &[x, y])| {
+                        let (dx, dy) = deform.offset(i);
+                        [x + dx, y + dy]
+                    })
+                    .collect(),
+            ),
+            None => Arc::clone(mesh.vertices()),
+        };
+        MeshNode {
+            vertices,
+            tex_coords: Arc::clone(mesh.tex_coords()),
+            indices: Arc::clone(mesh.indices()),
+        }
+    }
 }
 
 impl<T> Node<T> {
     pub(crate) fn new(
+        part_id: usize,
         transform: Transform,
         global_matrix: Matrix4<f32>,
         color: [f32; 4],
         hide: bool,
     ) -> Self {
         Node {
+            part_id,
             transform,
+            local_transform: Transform::default(),
             global_matrix,
             hide,
             user: None,
@@ -357,9 +1140,30 @@ impl<T> Node<T> {
             sprite_no: None,
             color,
             deform_offsets: [[0.; 2]; 4],
+            flip_h: false,
+            flip_v: false,
+            mask: false,
+            mask_influence: true,
+            mesh: None,
+            color_offset: [0.; 4],
+            alpha_blend: AlphaBlend::Mix,
+            vertex_colors: [color; 4],
+            vertex_color_offsets: [[0.; 4]; 4],
+            priority: 0.,
+            local_scale: [1.; 2],
+            size: [None; 2],
+            pivot_offset: [0.; 2],
+            uv_offset: [0.; 2],
+            uv_rotation: 0.,
+            uv_scale: [1.; 2],
+            bounding_radius: None,
         }
     }
 
+    pub(crate) fn set_local_transform(&mut self, local_transform: Transform) {
+        self.local_transform = local_transform;
+    }
+
     pub(crate) fn set_user(&mut self, user: T) {
         self.user = user.into();
     }
@@ -369,7 +1173,92 @@ impl<T> Node<T> {
         self.sprite_no = sprite_no.into();
     }
 
+    pub(crate) fn set_flip(&mut self, flip_h: bool, flip_v: bool) {
+        self.flip_h = flip_h;
+        self.flip_v = flip_v;
+    }
+
+    pub(crate) fn set_blend(&mut self, color_offset: [f32; 4], alpha_blend: AlphaBlend) {
+        self.color_offset = color_offset;
+        self.alpha_blend = alpha_blend;
+        self.vertex_color_offsets = [color_offset; 4];
+    }
+
+    pub(crate) fn set_vertex_colors(&mut self, corners: [([f32; 4], [f32; 4]); 4]) {
+        for (i, (color, offset)) in corners.iter().enumerate() {
+            self.vertex_colors[i] = *color;
+            self.vertex_color_offsets[i] = *offset;
+        }
+    }
+
+    pub(crate) fn set_local_alpha(&mut self, local_alpha: f32) {
+        for color in self.vertex_colors.iter_mut() {
+            color[3] *= local_alpha;
+        }
+    }
+
+    pub(crate) fn set_local_scale(&mut self, scale_x: f32, scale_y: f32) {
+        self.local_scale = [scale_x, scale_y];
+    }
+
+    pub(crate) fn set_priority(&mut self, priority: f32) {
+        self.priority = priority;
+    }
+
+    pub(crate) fn set_bounding_radius(&mut self, radius: Option<f32>) {
+        self.bounding_radius = radius;
+    }
+
+    pub(crate) fn set_cell_transform(&mut self, size: [Option<f32>; 2], pivot_offset: [f32; 2]) {
+        self.size = size;
+        self.pivot_offset = pivot_offset;
+    }
+
+    pub(crate) fn set_uv_transform(&mut self, offset: [f32; 2], rotation: f32, scale: [f32; 2]) {
+        self.uv_offset = offset;
+        self.uv_rotation = rotation;
+        self.uv_scale = scale;
+    }
+
+    pub(crate) fn set_mask(&mut self, mask: bool, mask_influence: bool) {
+        self.mask = mask;
+        self.mask_influence = mask_influence;
+    }
+
+    pub(crate) fn set_mesh(&mut self, mesh: MeshNode) {
+        self.mesh = mesh.into();
+    }
+
     pub(crate) fn set_deform(&mut self, lt: [f32; 2], lb: [f32; 2], rt: [f32; 2], rb: [f32; 2]) {
         self.deform_offsets = [rt, lt, rb, lb];
     }
+
+    // 遷移元のノードから rate の割合で補間する(1.0 でこのノードの値)
+    // 座標と行列は親子関係を保つためにノード作成時にローカル座標で補間するのでここでは扱わない
+    pub(crate) fn blend_from(&mut self, from: &Node<T>, rate: f32) {
+        let lerp4 = |from: [f32; 4], to: [f32; 4]| -> [f32; 4] {
+            lerp(Vector4::from(from), Vector4::from(to), rate).into()
+        };
+        let lerp2 = |from: [f32; 2], to: [f32; 2]| -> [f32; 2] {
+            lerp(Vector2::from(from), Vector2::from(to), rate).into()
+        };
+        self.color = lerp4(from.color, self.color);
+        self.color_offset = lerp4(from.color_offset, self.color_offset);
+        for i in 0..4 {
+            self.vertex_colors[i] = lerp4(from.vertex_colors[i], self.vertex_colors[i]);
+            self.vertex_color_offsets[i] =
+                lerp4(from.vertex_color_offsets[i], self.vertex_color_offsets[i]);
+            self.deform_offsets[i] = lerp2(from.deform_offsets[i], self.deform_offsets[i]);
+        }
+
+        // メッシュの変形は頂点数が同じ(同じメッシュ)場合だけ補間する
+        if let (Some(mesh), Some(from_mesh)) = (self.mesh.as_mut(), from.mesh.as_ref()) {
+            if mesh.vertices.len() == from_mesh.vertices.len() {
+                let vertices = Arc::make_mut(&mut mesh.vertices);
+                for (vertex, from_vertex) in vertices.iter_mut().zip(from_mesh.vertices.iter()) {
+                    *vertex = lerp2(*from_vertex, *vertex);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,259 @@
+use crate::traits::animation_file::AnimationFile;
+use amethyst::ecs::{Component, DenseVecStorage};
+use std::collections::VecDeque;
+
+enum QueueEntry<T>
+where
+    T: AnimationFile,
+{
+    Play {
+        pack: T::PackKey,
+        animation: T::AnimationKey,
+        times: Option<usize>, // 再生回数(None の場合は無限ループ)
+    },
+    Return, // キューを始める前に再生していたアニメーションに戻る
+}
+
+// 再生するアニメーションの予約
+// AnimationTransitionSystem がステートマシンや TranslateAnimation より先に消費する
+// キューを消費し終わったら通常の遷移に戻る
+//
+// queue.play_once(Pack, Attack).then(Pack, Recover).then_return();
+pub struct AnimationQueue<T>
+where
+    T: AnimationFile,
+{
+    entries: VecDeque<QueueEntry<T>>,
+    current: Option<(T::PackKey, T::AnimationKey, Option<usize>)>, // 再生中のキーと残りの再生回数
+    return_key: Option<(T::PackKey, T::AnimationKey)>, // キューを始める前に再生していたキー
+}
+
+impl<T> AnimationQueue<T>
+where
+    T: AnimationFile,
+{
+    pub fn new() -> Self {
+        AnimationQueue {
+            entries: VecDeque::new(),
+            current: None,
+            return_key: None,
+        }
+    }
+
+    // 予約を破棄して次の更新ですぐに再生する
+    // 戻り先はキューを始める前のキーのまま変わらない
+    pub fn play_once(&mut self, pack: T::PackKey, animation: T::AnimationKey) -> &mut Self {
+        self.play_times(pack, animation, 1)
+    }
+
+    pub fn play_times(
+        &mut self,
+        pack: T::PackKey,
+        animation: T::AnimationKey,
+        times: usize,
+    ) -> &mut Self {
+        self.entries.clear();
+        self.current = None;
+        self.then_times(pack, animation, times)
+    }
+
+    // 直前のアニメーションが終わったら再生する
+    pub fn then(&mut self, pack: T::PackKey, animation: T::AnimationKey) -> &mut Self {
+        self.then_times(pack, animation, 1)
+    }
+
+    pub fn then_times(
+        &mut self,
+        pack: T::PackKey,
+        animation: T::AnimationKey,
+        times: usize,
+    ) -> &mut Self {
+        if times > 0 {
+            self.entries.push_back(QueueEntry::Play {
+                pack,
+                animation,
+                times: Some(times),
+            });
+        }
+        self
+    }
+
+    // 止めるかキューを破棄するまでループする
+    pub fn then_loop(&mut self, pack: T::PackKey, animation: T::AnimationKey) -> &mut Self {
+        self.entries.push_back(QueueEntry::Play {
+            pack,
+            animation,
+            times: None,
+        });
+        self
+    }
+
+    // キューを始める前に再生していたアニメーションに戻ってキューを終える
+    pub fn then_return(&mut self) {
+        self.entries.push_back(QueueEntry::Return);
+    }
+
+    // 予約を破棄する．再生中のアニメーションはそのまま通常の遷移に戻る
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.current = None;
+        self.return_key = None;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.current.is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.current.is_none() && self.entries.is_empty()
+    }
+
+    // 再生中でなければ予約の先頭から再生を始める
+    // 始めた場合は再生するキーを返す
+    pub(crate) fn start(
+        &mut self,
+        current_key: Option<(T::PackKey, T::AnimationKey)>,
+    ) -> Option<(T::PackKey, T::AnimationKey)> {
+        if self.current.is_some() || self.entries.is_empty() {
+            return None;
+        }
+        if self.return_key.is_none() {
+            self.return_key = current_key;
+        }
+        self.pop()
+    }
+
+    // 再生中のアニメーションが終わったときの次のキー
+    // 再生回数が残っていれば同じキーを返す．None の場合はキューを消費し終わっている
+    pub(crate) fn next(&mut self) -> Option<(T::PackKey, T::AnimationKey)> {
+        if let Some((pack, animation, times)) = self.current.as_mut() {
+            match times {
+                None => return Some((*pack, *animation)),
+                Some(times) if *times > 1 => {
+                    *times -= 1;
+                    return Some((*pack, *animation));
+                }
+                _ => {}
+            }
+        }
+        self.current = None;
+        self.pop()
+    }
+
+    fn pop(&mut self) -> Option<(T::PackKey, T::AnimationKey)> {
+        match self.entries.pop_front() {
+            Some(QueueEntry::Play {
+                pack,
+                animation,
+                times,
+            }) => {
+                self.current = Some((pack, animation, times));
+                Some((pack, animation))
+            }
+            Some(QueueEntry::Return) => {
+                self.entries.clear();
+                self.return_key.take()
+            }
+            None => {
+                self.return_key = None;
+                None
+            }
+        }
+    }
+}
+
+impl<T> Default for AnimationQueue<T>
+where
+    T: AnimationFile,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Component for AnimationQueue<T>
+where
+    T: AnimationFile,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TestFile;
+
+    const IDLE: (u32, u32) = (0, 0);
+
+    #[test]
+    fn empty_queue_does_not_start() {
+        let mut queue = AnimationQueue::<TestFile>::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.start(Some(IDLE)), None);
+        assert!(queue.is_playing() == false);
+    }
+
+    #[test]
+    fn plays_in_order_then_returns() {
+        let mut queue = AnimationQueue::<TestFile>::new();
+        queue.play_once(0, 1).then_times(0, 2, 2).then_return();
+
+        assert_eq!(queue.start(Some(IDLE)), Some((0, 1)));
+        assert!(queue.is_playing());
+        // 再生中は次の予約を始めない
+        assert_eq!(queue.start(Some((0, 1))), None);
+
+        assert_eq!(queue.next(), Some((0, 2)));
+        assert_eq!(queue.next(), Some((0, 2)));
+        assert_eq!(queue.next(), Some(IDLE));
+        assert!(queue.is_playing() == false);
+        assert!(queue.is_empty());
+        assert_eq!(queue.next(), None);
+    }
+
+    #[test]
+    fn finishes_without_return() {
+        let mut queue = AnimationQueue::<TestFile>::new();
+        queue.play_once(0, 1).then(0, 2);
+
+        assert_eq!(queue.start(Some(IDLE)), Some((0, 1)));
+        assert_eq!(queue.next(), Some((0, 2)));
+        // 消費し終わったら通常の遷移に戻る
+        assert_eq!(queue.next(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn loop_repeats_until_cleared() {
+        let mut queue = AnimationQueue::<TestFile>::new();
+        queue.play_once(0, 1).then_loop(0, 2);
+
+        assert_eq!(queue.start(Some(IDLE)), Some((0, 1)));
+        for _ in 0..3 {
+            assert_eq!(queue.next(), Some((0, 2)));
+        }
+        queue.clear();
+        assert!(queue.is_empty());
+        assert_eq!(queue.next(), None);
+    }
+
+    #[test]
+    fn play_once_replaces_queue_and_keeps_return_key() {
+        let mut queue = AnimationQueue::<TestFile>::new();
+        queue.play_once(0, 1).then(0, 2).then_return();
+        assert_eq!(queue.start(Some(IDLE)), Some((0, 1)));
+
+        // 割り込んでも戻り先はキューを始める前のキーのまま
+        queue.play_once(0, 3).then_return();
+        assert!(queue.is_playing() == false);
+        assert_eq!(queue.start(Some((0, 1))), Some((0, 3)));
+        assert_eq!(queue.next(), Some(IDLE));
+    }
+
+    #[test]
+    fn zero_times_is_skipped() {
+        let mut queue = AnimationQueue::<TestFile>::new();
+        queue.play_times(0, 1, 0).then(0, 2);
+        assert_eq!(queue.start(Some(IDLE)), Some((0, 2)));
+    }
+}
@@ -0,0 +1,314 @@
+use crate::{
+    resource::state_machine::{Condition, ParamValue, StateMachine},
+    traits::animation_file::AnimationFile,
+};
+use amethyst::{
+    assets::Handle,
+    ecs::{Component, DenseVecStorage},
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+// ステートマシンでアニメーションを遷移させる
+// このコンポーネントを持つエンティティは TranslateAnimation::translate_animation の代わりに
+// ステートマシンの遷移条件で次のアニメーションを決める
+pub struct AnimationStateMachine<T>
+where
+    T: AnimationFile,
+{
+    state_machine: Handle<StateMachine<T>>,
+    state: Option<String>, // 現在のステート(None の場合は開始ステートから始める)
+    params: BTreeMap<String, ParamValue>,
+    triggers: BTreeSet<String>,
+    user_eq: fn(&T::UserData, &T::UserData) -> bool, // 遷移条件 User の比較
+}
+
+impl<T> AnimationStateMachine<T>
+where
+    T: AnimationFile,
+{
+    // ユーザーデータの比較は遷移条件 User でだけ使うので比較できる場合だけここで要求する
+    pub fn new(state_machine: Handle<StateMachine<T>>) -> Self
+    where
+        T::UserData: PartialEq,
+    {
+        AnimationStateMachine {
+            state_machine,
+            state: None,
+            params: BTreeMap::new(),
+            triggers: BTreeSet::new(),
+            user_eq: PartialEq::eq,
+        }
+    }
+
+    pub fn state_machine(&self) -> &Handle<StateMachine<T>> {
+        &self.state_machine
+    }
+
+    pub fn state(&self) -> Option<&str> {
+        self.state.as_ref().map(|name| name.as_str())
+    }
+
+    // 開始ステートからやり直す
+    pub fn restart(&mut self) {
+        self.state = None;
+    }
+
+    pub fn set_bool<S: Into<String>>(&mut self, name: S, value: bool) {
+        self.params.insert(name.into(), ParamValue::Bool(value));
+    }
+
+    pub fn set_int<S: Into<String>>(&mut self, name: S, value: i32) {
+        self.params.insert(name.into(), ParamValue::Int(value));
+    }
+
+    pub fn set_float<S: Into<String>>(&mut self, name: S, value: f32) {
+        self.params.insert(name.into(), ParamValue::Float(value));
+    }
+
+    pub fn param(&self, name: &str) -> Option<ParamValue> {
+        self.params.get(name).copied()
+    }
+
+    pub fn remove_param(&mut self, name: &str) {
+        self.params.remove(name);
+    }
+
+    // トリガーは遷移条件で使われるまで残る
+    pub fn set_trigger<S: Into<String>>(&mut self, name: S) {
+        self.triggers.insert(name.into());
+    }
+
+    pub fn reset_trigger(&mut self, name: &str) {
+        self.triggers.remove(name);
+    }
+
+    pub fn is_triggered(&self, name: &str) -> bool {
+        self.triggers.contains(name)
+    }
+
+    // ステートが決まっていなければ開始ステートに入る
+    // 入った場合は再生するアニメーションのキーを返す
+    pub(crate) fn enter_initial(
+        &mut self,
+        state_machine: &StateMachine<T>,
+    ) -> Option<(T::PackKey, T::AnimationKey)> {
+        if self
+            .state
+            .as_ref()
+            .and_then(|name| state_machine.state(name))
+            .is_some()
+        {
+            return None;
+        }
+        let initial = state_machine.state(state_machine.initial())?;
+        self.state = Some(state_machine.initial().into());
+        Some((*initial.pack(), *initial.animation()))
+    }
+
+    // 現在のステートの遷移条件を評価して次のアニメーションのキー，開始フレームとクロスフェードの秒数を返す
+    // どの条件も満たさずに再生が終了していたら現在のステートを最初から再生する
+    pub(crate) fn translate(
+        &mut self,
+        state_machine: &StateMachine<T>,
+        rest_time: Option<usize>,
+        user: Option<&T::UserData>,
+    ) -> Option<(T::PackKey, T::AnimationKey, usize, Option<f32>)> {
+        let current = state_machine.state(self.state.as_ref()?)?;
+        let next = current.transitions().find_map(|transition| {
+            let triggers = self.satisfied_triggers(transition.condition(), rest_time, user)?;
+            let next = state_machine.state(transition.to())?;
+            Some((transition, next, triggers))
+        });
+
+        match next {
+            Some((transition, next, triggers)) => {
+                log::debug!("state transition: {:?} -> {}", self.state, transition.to());
+                for trigger in triggers {
+                    self.triggers.remove(trigger);
+                }
+                self.state = Some(transition.to().into());
+                let blend = Some(transition.blend()).filter(|&blend| blend > 0.);
+                Some((
+                    *next.pack(),
+                    *next.animation(),
+                    transition.start_frame(),
+                    blend,
+                ))
+            }
+            None if rest_time.is_none() => Some((*current.pack(), *current.animation(), 0, None)),
+            None => None,
+        }
+    }
+
+    // 条件を満たしていれば条件を満たすのに使ったトリガー名を返す
+    // Not の中のトリガーと満たさなかった Any の枝のトリガーは消費しない
+    fn satisfied_triggers<'c>(
+        &self,
+        condition: &'c Condition<T::UserData>,
+        rest_time: Option<usize>,
+        user: Option<&T::UserData>,
+    ) -> Option<Vec<&'c str>> {
+        let satisfied = |value: bool| Some(vec![]).filter(|_| value);
+        match condition {
+            Condition::End => satisfied(rest_time.is_none()),
+            Condition::User(value) => satisfied(
+                user.map(|user| (self.user_eq)(user, value))
+                    .unwrap_or(false),
+            ),
+            Condition::Param(name, compare) => satisfied(
+                self.param(name)
+                    .map(|value| compare.test(value))
+                    .unwrap_or(false),
+            ),
+            Condition::Trigger(name) => {
+                Some(vec![name.as_str()]).filter(|_| self.is_triggered(name))
+            }
+            Condition::Not(condition) => satisfied(
+                self.satisfied_triggers(condition, rest_time, user)
+                    .is_none(),
+            ),
+            Condition::All(conditions) => {
+                let mut triggers = vec![];
+                for condition in conditions {
+                    triggers.extend(self.satisfied_triggers(condition, rest_time, user)?);
+                }
+                Some(triggers)
+            }
+            Condition::Any(conditions) => {
+                let mut satisfied_any = false;
+                let mut triggers = vec![];
+                for condition in conditions {
+                    if let Some(condition_triggers) =
+                        self.satisfied_triggers(condition, rest_time, user)
+                    {
+                        satisfied_any = true;
+                        triggers.extend(condition_triggers);
+                    }
+                }
+                Some(triggers).filter(|_| satisfied_any)
+            }
+        }
+    }
+}
+
+impl<T> Component for AnimationStateMachine<T>
+where
+    T: AnimationFile,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TestFile;
+    use amethyst::assets::AssetStorage;
+
+    // idle(0) から condition を満たしたら run(1) に遷移するステートマシン
+    fn state_machine(condition: &str) -> StateMachine<TestFile> {
+        ron::de::from_str(&format!(
+            r#"(
+                initial: "idle",
+                states: {{
+                    "idle": (pack: 0, animation: 0, transitions: [
+                        (to: "run", condition: {}),
+                    ]),
+                    "run": (pack: 0, animation: 1),
+                }},
+            )"#,
+            condition
+        ))
+        .unwrap()
+    }
+
+    fn machine(state_machine: &StateMachine<TestFile>) -> AnimationStateMachine<TestFile> {
+        let storage = AssetStorage::<StateMachine<TestFile>>::new();
+        let mut machine = AnimationStateMachine::new(storage.allocate());
+        assert_eq!(machine.enter_initial(state_machine), Some((0, 0)));
+        machine
+    }
+
+    #[test]
+    fn trigger_is_consumed_by_transition() {
+        let state_machine = state_machine(r#"Trigger("jump")"#);
+        let mut machine = machine(&state_machine);
+        assert_eq!(machine.translate(&state_machine, Some(1), None), None);
+
+        machine.set_trigger("jump");
+        machine.set_trigger("other");
+        assert_eq!(
+            machine.translate(&state_machine, Some(1), None),
+            Some((0, 1, 0, None))
+        );
+        assert_eq!(machine.state(), Some("run"));
+        assert!(machine.is_triggered("jump") == false);
+        assert!(machine.is_triggered("other"));
+    }
+
+    #[test]
+    fn trigger_is_kept_without_transition() {
+        let state_machine =
+            state_machine(r#"All([Trigger("jump"), Param("ground", Equal(Bool(true)))])"#);
+        let mut machine = machine(&state_machine);
+        machine.set_trigger("jump");
+        machine.set_bool("ground", false);
+        assert_eq!(machine.translate(&state_machine, Some(1), None), None);
+        assert!(machine.is_triggered("jump"));
+
+        machine.set_bool("ground", true);
+        assert!(machine.translate(&state_machine, Some(1), None).is_some());
+        assert!(machine.is_triggered("jump") == false);
+    }
+
+    #[test]
+    fn trigger_in_unsatisfied_branch_is_kept() {
+        let state_machine = state_machine(
+            r#"Any([
+                All([Trigger("jump"), Param("ground", Equal(Bool(true)))]),
+                Param("speed", Greater(0.5)),
+            ])"#,
+        );
+        let mut machine = machine(&state_machine);
+        machine.set_trigger("jump");
+        machine.set_bool("ground", false);
+        machine.set_float("speed", 1.);
+        assert!(machine.translate(&state_machine, Some(1), None).is_some());
+        assert!(machine.is_triggered("jump"));
+    }
+
+    #[test]
+    fn trigger_in_satisfied_branches_is_consumed() {
+        let state_machine = state_machine(r#"Any([Trigger("jump"), Trigger("dash")])"#);
+        let mut machine = machine(&state_machine);
+        machine.set_trigger("jump");
+        machine.set_trigger("dash");
+        assert!(machine.translate(&state_machine, Some(1), None).is_some());
+        assert!(machine.is_triggered("jump") == false);
+        assert!(machine.is_triggered("dash") == false);
+    }
+
+    #[test]
+    fn trigger_under_not_is_kept() {
+        let state_machine = state_machine(r#"All([Not(Trigger("stop")), User(3)])"#);
+        let mut machine = machine(&state_machine);
+        machine.set_trigger("stop");
+        assert_eq!(machine.translate(&state_machine, Some(1), Some(&3)), None);
+
+        machine.reset_trigger("stop");
+        assert_eq!(machine.translate(&state_machine, Some(1), Some(&2)), None);
+        assert!(machine
+            .translate(&state_machine, Some(1), Some(&3))
+            .is_some());
+    }
+
+    #[test]
+    fn end_without_transition_restarts_current_state() {
+        let state_machine = state_machine(r#"Trigger("jump")"#);
+        let mut machine = machine(&state_machine);
+        assert_eq!(
+            machine.translate(&state_machine, None, None),
+            Some((0, 0, 0, None))
+        );
+        assert_eq!(machine.state(), Some("idle"));
+    }
+}
@@ -1,16 +1,39 @@
 use amethyst::ecs::{Component, DenseVecStorage};
+use std::ops::Range;
 
+// 再生が終了したときの動作
+// 回数は往復再生の往路と復路をそれぞれ 1 回と数える
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoopMode {
+    Once,                    // TranslateAnimation などで遷移する
+    Loop(Option<usize>),     // 指定回数(None は無限)再生してから遷移する
+    PingPong(Option<usize>), // 再生方向を反転させながら指定回数再生してから遷移する
+    Clamp,                   // 終端のフレームで停止する
+}
+
+impl Default for LoopMode {
+    fn default() -> Self {
+        LoopMode::Once
+    }
+}
+
+// 再生速度が負の場合は逆再生
+// 逆再生は再生時間が 0 未満になったら終了とする
 #[derive(Debug, Clone)]
 pub enum AnimationTime {
     Play {
         current_time: f32,
         prev_time: Option<f32>,
         play_speed: f32,
+        loop_mode: LoopMode,
+        loop_count: usize, // ループで再生し終えた回数
     },
     Stop {
         stopped_time: f32,      // 停止時点のでアニメーション再生時間
         stop_time: Option<f32>, // 停止を行う時間
         play_speed: f32,        // 再生時の再生速度を一応覚えておく
+        loop_mode: LoopMode,
+        loop_count: usize,
     },
 }
 
@@ -20,6 +43,8 @@ impl AnimationTime {
             stopped_time: 0.,
             stop_time: None,
             play_speed: 1.,
+            loop_mode: LoopMode::Once,
+            loop_count: 0,
         }
     }
 
@@ -54,6 +79,8 @@ impl AnimationTime {
             current_time,
             prev_time: None,
             play_speed: speed.into().unwrap_or(play_speed),
+            loop_mode: self.loop_mode(),
+            loop_count: self.loop_count(),
         }
     }
 
@@ -80,6 +107,8 @@ impl AnimationTime {
             stopped_time,
             stop_time,
             play_speed,
+            loop_mode: self.loop_mode(),
+            loop_count: self.loop_count(),
         }
     }
 
@@ -111,6 +140,161 @@ impl AnimationTime {
         Some(float_frame.floor() as usize)
     }
 
+    // 総フレーム数に収めた再生フレーム
+    // 逆再生は終端の時間から始まる(巻き戻しでも終端の時間になりうる)ので最終フレームに収める
+    pub(crate) fn current_frame(&self, fps: f32, total_frame: usize) -> usize {
+        let frame = self.play_frame(fps);
+        if self.is_reverse() {
+            frame.min(total_frame.saturating_sub(1))
+        } else {
+            frame
+        }
+    }
+
+    // 前回から今回の更新までに通過したフレーム
+    // フレームの開始時間が前回の時間以上，今回の時間未満のものを通過したとする
+    // 逆再生ではフレームの終了時間が今回の時間より後，前回の時間以下のものを通過したとする
+    pub fn passed_frames(&self, fps: f32) -> Option<Range<usize>> {
+        let prev_time = self.prev_time()?;
+        let play_time = self.play_time();
+        if play_time > prev_time {
+            Some((prev_time * fps).ceil() as usize..(play_time * fps).ceil() as usize)
+        } else if play_time < prev_time {
+            Some((play_time * fps).floor().max(0.) as usize..(prev_time * fps).floor() as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn play_speed(&self) -> f32 {
+        match self {
+            &AnimationTime::Play { play_speed, .. } => play_speed,
+            &AnimationTime::Stop { play_speed, .. } => play_speed,
+        }
+    }
+
+    pub fn is_reverse(&self) -> bool {
+        self.play_speed() < 0.
+    }
+
+    pub fn loop_mode(&self) -> LoopMode {
+        match self {
+            &AnimationTime::Play { loop_mode, .. } => loop_mode,
+            &AnimationTime::Stop { loop_mode, .. } => loop_mode,
+        }
+    }
+
+    // 再生回数も数え直す
+    pub fn set_loop_mode(&mut self, mode: LoopMode) {
+        match self {
+            AnimationTime::Play {
+                loop_mode,
+                loop_count,
+                ..
+            }
+            | AnimationTime::Stop {
+                loop_mode,
+                loop_count,
+                ..
+            } => {
+                *loop_mode = mode;
+                *loop_count = 0;
+            }
+        }
+    }
+
+    pub fn loop_count(&self) -> usize {
+        match self {
+            &AnimationTime::Play { loop_count, .. } => loop_count,
+            &AnimationTime::Stop { loop_count, .. } => loop_count,
+        }
+    }
+
+    // 別のアニメーションに遷移したときは再生回数を数え直す
+    pub(crate) fn reset_loop_count(&mut self) {
+        match self {
+            AnimationTime::Play { loop_count, .. } | AnimationTime::Stop { loop_count, .. } => {
+                *loop_count = 0;
+            }
+        }
+    }
+
+    // 再生が終了したときにループの設定に合わせて再生時間を巻き戻す
+    // 巻き戻した場合は再生し終えた回数を返す．None の場合は設定の回数を再生し終えている
+    // 終端を超えた時間は次の再生に持ち越す
+    pub(crate) fn wrap(&mut self, fps: f32, total_frame: usize) -> Option<usize> {
+        let duration = total_frame as f32 / fps;
+        if let AnimationTime::Play {
+            current_time,
+            prev_time,
+            play_speed,
+            loop_mode,
+            loop_count,
+        } = self
+        {
+            let (max_count, pingpong) = match *loop_mode {
+                LoopMode::Loop(max_count) => (max_count, false),
+                LoopMode::PingPong(max_count) => (max_count, true),
+                LoopMode::Once | LoopMode::Clamp => return None,
+            };
+            if duration <= 0. || max_count.map(|max| *loop_count + 1 >= max).unwrap_or(false) {
+                return None;
+            }
+            let backward = *play_speed < 0.;
+            let over = if backward {
+                -*current_time
+            } else {
+                *current_time - duration
+            };
+            let over = over % duration;
+
+            // 往復再生は方向を反転して終端から折り返す
+            *current_time = match (backward, pingpong) {
+                (false, false) => over,
+                (true, false) => duration - over,
+                (false, true) => duration - over,
+                (true, true) => over,
+            };
+            if pingpong {
+                *play_speed = -*play_speed;
+            }
+            *prev_time = None;
+            *loop_count += 1;
+            Some(*loop_count)
+        } else {
+            None
+        }
+    }
+
+    // 終端のフレームで停止する
+    pub(crate) fn clamp_end(&mut self, fps: f32, total_frame: usize) {
+        let end_time = if self.is_reverse() {
+            0.
+        } else {
+            total_frame.saturating_sub(1) as f32 / fps
+        };
+        self.set_play_time(end_time);
+        self.stop(None);
+    }
+
+    // 前回の時間も指定して再生時間を設定する
+    // 外から再生フレームを決めるときに passed_frames で通過したフレームを判定するために使う
+    pub(crate) fn seek(&mut self, from: f32, to: f32) {
+        match self {
+            AnimationTime::Play {
+                current_time,
+                prev_time,
+                ..
+            } => {
+                *prev_time = Some(from);
+                *current_time = to;
+            }
+            AnimationTime::Stop { stopped_time, .. } => {
+                *stopped_time = to;
+            }
+        }
+    }
+
     pub fn set_play_speed(&mut self, speed: f32) {
         if let AnimationTime::Play { play_speed, .. } = self {
             *play_speed = speed;
@@ -153,6 +337,7 @@ impl AnimationTime {
                 stop_time: Some(time),
                 play_speed,
                 stopped_time,
+                ..
             } => {
                 if *time > delta {
                     *time -= delta;
@@ -172,6 +357,8 @@ impl AnimationTime {
                 current_time,
                 prev_time: Some(current_time),
                 play_speed,
+                loop_mode: self.loop_mode(),
+                loop_count: self.loop_count(),
             };
         }
     }
@@ -202,3 +389,155 @@ impl AnimationTime {
 impl Component for AnimationTime {
     type Storage = DenseVecStorage<Self>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 10 fps で 10 フレーム(1 秒)のアニメーションとして扱う
+    const FPS: f32 = 10.;
+    const TOTAL_FRAME: usize = 10;
+
+    fn playing(speed: f32, loop_mode: LoopMode, play_time: f32) -> AnimationTime {
+        let mut time = AnimationTime::new();
+        time.set_loop_mode(loop_mode);
+        time.play(speed);
+        time.set_play_time(play_time);
+        time
+    }
+
+    #[test]
+    fn passed_frames_forward() {
+        let mut time = playing(1., LoopMode::Once, 0.);
+        assert_eq!(time.passed_frames(FPS), None);
+
+        time.add_time(0.25);
+        assert_eq!(time.passed_frames(FPS), Some(0..3));
+        time.add_time(0.25);
+        assert_eq!(time.passed_frames(FPS), Some(3..5));
+    }
+
+    #[test]
+    fn passed_frames_reverse() {
+        let mut time = playing(-1., LoopMode::Once, 1.);
+        time.add_time(0.25);
+        assert_eq!(time.passed_frames(FPS), Some(7..10));
+        time.add_time(0.25);
+        assert_eq!(time.passed_frames(FPS), Some(5..7));
+    }
+
+    #[test]
+    fn passed_frames_stopped() {
+        let mut time = playing(1., LoopMode::Once, 0.5);
+        time.stop(None);
+        time.add_time(0.25);
+        assert_eq!(time.passed_frames(FPS), None);
+        assert_eq!(time.play_time(), 0.5);
+    }
+
+    #[test]
+    fn current_frame_reverse_start() {
+        // 逆再生の開始時間は終端の時間なので最終フレームを再生する
+        let time = playing(-1., LoopMode::Once, 1.);
+        assert_eq!(time.play_frame(FPS), TOTAL_FRAME);
+        assert_eq!(time.current_frame(FPS, TOTAL_FRAME), TOTAL_FRAME - 1);
+
+        // 順再生で終端を超えた場合はそのまま返して終了を判定させる
+        let time = playing(1., LoopMode::Once, 1.);
+        assert_eq!(time.current_frame(FPS, TOTAL_FRAME), TOTAL_FRAME);
+    }
+
+    #[test]
+    fn current_frame_wrap_to_end() {
+        // 巻き戻した時間がちょうど終端になっても最終フレームを再生する
+        let mut time = playing(-1., LoopMode::Loop(None), 0.);
+        time.add_time(1.);
+        assert_eq!(time.wrap(FPS, TOTAL_FRAME), Some(1));
+        assert_eq!(time.play_time(), 1.);
+        assert_eq!(time.current_frame(FPS, TOTAL_FRAME), TOTAL_FRAME - 1);
+
+        let mut time = playing(1., LoopMode::PingPong(None), 0.);
+        time.add_time(1.);
+        assert_eq!(time.wrap(FPS, TOTAL_FRAME), Some(1));
+        assert!(time.is_reverse());
+        assert_eq!(time.current_frame(FPS, TOTAL_FRAME), TOTAL_FRAME - 1);
+    }
+
+    #[test]
+    fn wrap_once_and_clamp() {
+        let mut time = playing(1., LoopMode::Once, 1.25);
+        assert_eq!(time.wrap(FPS, TOTAL_FRAME), None);
+        assert_eq!(time.play_time(), 1.25);
+
+        let mut time = playing(1., LoopMode::Clamp, 1.25);
+        assert_eq!(time.wrap(FPS, TOTAL_FRAME), None);
+        time.clamp_end(FPS, TOTAL_FRAME);
+        assert!(time.is_stop());
+        assert_eq!(time.play_frame(FPS), TOTAL_FRAME - 1);
+    }
+
+    #[test]
+    fn wrap_forward() {
+        let mut time = playing(1., LoopMode::Loop(None), 0.);
+        time.add_time(1.25);
+        assert_eq!(time.wrap(FPS, TOTAL_FRAME), Some(1));
+        assert_eq!(time.play_time(), 0.25);
+        assert_eq!(time.passed_frames(FPS), None);
+        assert!(time.is_reverse() == false);
+
+        time.add_time(1.);
+        assert_eq!(time.wrap(FPS, TOTAL_FRAME), Some(2));
+        assert_eq!(time.play_time(), 0.25);
+    }
+
+    #[test]
+    fn wrap_forward_count() {
+        let mut time = playing(1., LoopMode::Loop(Some(2)), 1.25);
+        assert_eq!(time.wrap(FPS, TOTAL_FRAME), Some(1));
+        assert_eq!(time.loop_count(), 1);
+
+        // 2 回目の再生を終えたら巻き戻さずに遷移させる
+        time.add_time(1.);
+        assert_eq!(time.wrap(FPS, TOTAL_FRAME), None);
+        assert_eq!(time.play_time(), 1.25);
+    }
+
+    #[test]
+    fn wrap_reverse() {
+        let mut time = playing(-1., LoopMode::Loop(None), 1.);
+        time.add_time(1.25);
+        assert_eq!(time.play_time(), -0.25);
+        assert_eq!(time.wrap(FPS, TOTAL_FRAME), Some(1));
+        assert_eq!(time.play_time(), 0.75);
+        assert!(time.is_reverse());
+    }
+
+    #[test]
+    fn wrap_ping_pong() {
+        let mut time = playing(1., LoopMode::PingPong(None), 0.);
+        time.add_time(1.25);
+        assert_eq!(time.wrap(FPS, TOTAL_FRAME), Some(1));
+        assert_eq!(time.play_time(), 0.75);
+        assert!(time.is_reverse());
+
+        // 復路は逆再生として通過したフレームを判定する
+        time.add_time(0.25);
+        assert_eq!(time.passed_frames(FPS), Some(5..7));
+
+        time.add_time(0.75);
+        assert_eq!(time.play_time(), -0.25);
+        assert_eq!(time.wrap(FPS, TOTAL_FRAME), Some(2));
+        assert_eq!(time.play_time(), 0.25);
+        assert!(time.is_reverse() == false);
+    }
+
+    #[test]
+    fn wrap_ping_pong_count() {
+        // 往路と復路をそれぞれ 1 回と数える
+        let mut time = playing(1., LoopMode::PingPong(Some(2)), 1.25);
+        assert_eq!(time.wrap(FPS, TOTAL_FRAME), Some(1));
+        time.add_time(1.);
+        assert_eq!(time.wrap(FPS, TOTAL_FRAME), None);
+        assert!(time.is_reverse());
+    }
+}
@@ -0,0 +1,31 @@
+use amethyst::ecs::{Component, DenseVecStorage, Entity};
+
+// アニメーションしているエンティティのパーツに追従させる
+// AttachmentSystem が毎フレームパーツのワールド座標の行列を Transform にコピーする
+// Transform の親子関係は使わないので Parent を持たないエンティティに付けること
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    target: Entity,    // 追従先のアニメーションのエンティティ
+    part_name: String, // 追従先のパーツ名
+}
+
+impl Attachment {
+    pub fn new<S: Into<String>>(target: Entity, part_name: S) -> Self {
+        Attachment {
+            target,
+            part_name: part_name.into(),
+        }
+    }
+
+    pub fn target(&self) -> Entity {
+        self.target
+    }
+
+    pub fn part_name(&self) -> &str {
+        &self.part_name
+    }
+}
+
+impl Component for Attachment {
+    type Storage = DenseVecStorage<Self>;
+}
@@ -0,0 +1,64 @@
+use amethyst::ecs::{Component, DenseVecStorage};
+use std::collections::BTreeMap;
+
+// 再生中のアニメーションのセルを差し替える(着せ替え，装備の変更など)
+// パーツ番号の指定はパーツ名の指定より優先する
+// インスタンスパーツの参照先のアニメーションには適用しない
+#[derive(Debug, Default, Clone)]
+pub struct CellOverride {
+    sprite_sheet_set: Option<String>, // AnimationStore に登録したスプライトシートのセット名
+    part_ids: BTreeMap<usize, (usize, usize)>, // パーツ番号とセル(セルマップ番号，セル番号)
+    part_names: BTreeMap<String, (usize, usize)>, // パーツ名とセル(セルマップ番号，セル番号)
+}
+
+impl CellOverride {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    // セルマップ番号はそのままでスプライトシートだけを別のセットに差し替える
+    pub fn set_sprite_sheet_set<S: Into<String>>(&mut self, name: S) {
+        self.sprite_sheet_set = Some(name.into());
+    }
+
+    pub fn clear_sprite_sheet_set(&mut self) {
+        self.sprite_sheet_set = None;
+    }
+
+    pub fn sprite_sheet_set(&self) -> Option<&str> {
+        self.sprite_sheet_set.as_ref().map(|name| name.as_str())
+    }
+
+    pub fn set_part_cell(&mut self, part_id: usize, map_id: usize, cell_id: usize) {
+        self.part_ids.insert(part_id, (map_id, cell_id));
+    }
+
+    pub fn remove_part_cell(&mut self, part_id: usize) {
+        self.part_ids.remove(&part_id);
+    }
+
+    pub fn set_named_part_cell<S: Into<String>>(
+        &mut self,
+        part_name: S,
+        map_id: usize,
+        cell_id: usize,
+    ) {
+        self.part_names.insert(part_name.into(), (map_id, cell_id));
+    }
+
+    pub fn remove_named_part_cell(&mut self, part_name: &str) {
+        self.part_names.remove(part_name);
+    }
+
+    // パーツの差し替え先のセル(セルマップ番号，セル番号)
+    pub fn cell(&self, part_id: usize, part_name: &str) -> Option<(usize, usize)> {
+        self.part_ids
+            .get(&part_id)
+            .or_else(|| self.part_names.get(part_name))
+            .copied()
+    }
+}
+
+impl Component for CellOverride {
+    type Storage = DenseVecStorage<Self>;
+}
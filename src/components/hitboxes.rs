@@ -0,0 +1,290 @@
+use amethyst::{
+    core::math::Vector2,
+    ecs::{Component, DenseVecStorage},
+};
+
+// パーツの当たり判定のワールド座標での形状
+#[derive(Debug, Clone, PartialEq)]
+pub enum CollisionShape {
+    Quad([Vector2<f32>; 4]), // 回転を含む四角形(外周順)
+    Aabb {
+        min: Vector2<f32>,
+        max: Vector2<f32>,
+    },
+    Circle {
+        center: Vector2<f32>,
+        radius: f32,
+    },
+}
+
+impl CollisionShape {
+    pub fn overlaps(&self, other: &CollisionShape) -> bool {
+        match (self, other) {
+            (
+                CollisionShape::Circle {
+                    center: center1,
+                    radius: radius1,
+                },
+                CollisionShape::Circle {
+                    center: center2,
+                    radius: radius2,
+                },
+            ) => (center1 - center2).norm() <= radius1 + radius2,
+            (CollisionShape::Circle { center, radius }, shape)
+            | (shape, CollisionShape::Circle { center, radius }) => {
+                circle_overlaps_polygon(center, *radius, &shape.polygon())
+            }
+            (shape1, shape2) => polygons_overlap(&shape1.polygon(), &shape2.polygon()),
+        }
+    }
+
+    // 円以外の形状の頂点(外周順)
+    fn polygon(&self) -> [Vector2<f32>; 4] {
+        match self {
+            CollisionShape::Quad(vertices) => *vertices,
+            CollisionShape::Aabb { min, max } => [
+                Vector2::new(min.x, min.y),
+                Vector2::new(max.x, min.y),
+                Vector2::new(max.x, max.y),
+                Vector2::new(min.x, max.y),
+            ],
+            CollisionShape::Circle { center, .. } => [*center; 4],
+        }
+    }
+}
+
+// 分離軸判定．凸多角形なので各辺の法線で投影が重ならなければ離れている
+fn polygons_overlap(polygon1: &[Vector2<f32>; 4], polygon2: &[Vector2<f32>; 4]) -> bool {
+    let project = |polygon: &[Vector2<f32>; 4], axis: &Vector2<f32>| {
+        polygon
+            .iter()
+            .map(|vertex| vertex.dot(axis))
+            .fold((std::f32::MAX, std::f32::MIN), |(min, max), v| {
+                (min.min(v), max.max(v))
+            })
+    };
+    edges(polygon1).chain(edges(polygon2)).all(|(start, end)| {
+        let edge = end - start;
+        let axis = Vector2::new(-edge.y, edge.x);
+        let (min1, max1) = project(polygon1, &axis);
+        let (min2, max2) = project(polygon2, &axis);
+        min1 <= max2 && min2 <= max1
+    })
+}
+
+// 中心が多角形の内側にあるか，いずれかの辺との距離が半径以下なら重なっている
+fn circle_overlaps_polygon(
+    center: &Vector2<f32>,
+    radius: f32,
+    polygon: &[Vector2<f32>; 4],
+) -> bool {
+    // 反転で頂点の並びが逆回りになることがあるので外積の符号がそろっているかで判定する
+    // 辺の上にある場合は辺との距離で判定する
+    let crosses = edges(polygon)
+        .map(|(start, end)| {
+            let edge = end - start;
+            let to_center = center - start;
+            edge.x * to_center.y - edge.y * to_center.x
+        })
+        .collect::<Vec<_>>();
+    if crosses.iter().all(|&c| c > 0.) || crosses.iter().all(|&c| c < 0.) {
+        return true;
+    }
+
+    edges(polygon).any(|(start, end)| {
+        let edge = end - start;
+        let length = edge.norm_squared();
+        let rate = if length == 0. {
+            0.
+        } else {
+            ((center - start).dot(&edge) / length).max(0.).min(1.)
+        };
+        (start + edge * rate - center).norm() <= radius
+    })
+}
+
+fn edges<'a>(
+    polygon: &'a [Vector2<f32>; 4],
+) -> impl Iterator<Item = (Vector2<f32>, Vector2<f32>)> + 'a {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(start, end)| (*start, *end))
+}
+
+// 当たり判定を持つパーツの形状(HitboxSystem が毎フレーム更新する)
+// 非表示のパーツは含まない
+#[derive(Debug, Default)]
+pub struct Hitboxes {
+    shapes: Vec<(usize, CollisionShape)>, // パーツ番号と形状
+}
+
+impl Hitboxes {
+    pub(crate) fn new(shapes: Vec<(usize, CollisionShape)>) -> Self {
+        Hitboxes { shapes }
+    }
+
+    pub fn shapes(&self) -> impl Iterator<Item = &(usize, CollisionShape)> {
+        self.shapes.iter()
+    }
+
+    pub fn part(&self, part_id: usize) -> Option<&CollisionShape> {
+        self.shapes
+            .iter()
+            .find(|(id, _)| *id == part_id)
+            .map(|(_, shape)| shape)
+    }
+
+    // 重なっているパーツ番号の組(自分，相手)
+    pub fn overlapping_parts<'a>(
+        &'a self,
+        other: &'a Hitboxes,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.shapes.iter().flat_map(move |(part_id, shape)| {
+            other
+                .shapes
+                .iter()
+                .filter(move |(_, other_shape)| shape.overlaps(other_shape))
+                .map(move |(other_id, _)| (*part_id, *other_id))
+        })
+    }
+
+    pub fn overlaps(&self, other: &Hitboxes) -> bool {
+        self.overlapping_parts(other).next().is_some()
+    }
+}
+
+impl Component for Hitboxes {
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aabb(min: (f32, f32), max: (f32, f32)) -> CollisionShape {
+        CollisionShape::Aabb {
+            min: Vector2::new(min.0, min.1),
+            max: Vector2::new(max.0, max.1),
+        }
+    }
+
+    fn circle(center: (f32, f32), radius: f32) -> CollisionShape {
+        CollisionShape::Circle {
+            center: Vector2::new(center.0, center.1),
+            radius,
+        }
+    }
+
+    // 原点を中心に 45 度回転した一辺 √2 の四角形
+    fn diamond() -> CollisionShape {
+        CollisionShape::Quad([
+            Vector2::new(1., 0.),
+            Vector2::new(0., 1.),
+            Vector2::new(-1., 0.),
+            Vector2::new(0., -1.),
+        ])
+    }
+
+    fn assert_overlaps(shape1: &CollisionShape, shape2: &CollisionShape, expected: bool) {
+        assert_eq!(
+            shape1.overlaps(shape2),
+            expected,
+            "{:?}, {:?}",
+            shape1,
+            shape2
+        );
+        assert_eq!(
+            shape2.overlaps(shape1),
+            expected,
+            "{:?}, {:?}",
+            shape2,
+            shape1
+        );
+    }
+
+    #[test]
+    fn aabb_overlaps() {
+        let shape = aabb((0., 0.), (2., 2.));
+        assert_overlaps(&shape, &aabb((1., 1.), (3., 3.)), true);
+        // 辺が接しているだけでも重なっている扱い
+        assert_overlaps(&shape, &aabb((2., 0.), (4., 2.)), true);
+        assert_overlaps(&shape, &aabb((2.5, 0.), (4., 2.)), false);
+    }
+
+    #[test]
+    fn rotated_quad_overlaps() {
+        let shape = diamond();
+        assert_overlaps(&shape, &aabb((0.4, 0.4), (1., 1.)), true);
+        assert_overlaps(&shape, &aabb((0.5, 0.5), (1., 1.)), true);
+        // 外接する矩形どうしは重なっているが，回転した辺の法線で離れている
+        assert_overlaps(&shape, &aabb((0.6, 0.6), (1., 1.)), false);
+
+        // 頂点どうしで接している
+        let touching = CollisionShape::Quad([
+            Vector2::new(3., 0.),
+            Vector2::new(2., 1.),
+            Vector2::new(1., 0.),
+            Vector2::new(2., -1.),
+        ]);
+        assert_overlaps(&shape, &touching, true);
+        let separated = CollisionShape::Quad([
+            Vector2::new(2., 1.),
+            Vector2::new(1., 2.),
+            Vector2::new(0.6, 1.6),
+            Vector2::new(1.6, 0.6),
+        ]);
+        assert_overlaps(&shape, &separated, false);
+    }
+
+    #[test]
+    fn circles_overlap() {
+        let shape = circle((0., 0.), 1.);
+        assert_overlaps(&shape, &circle((1.5, 0.), 1.), true);
+        assert_overlaps(&shape, &circle((0., 2.), 1.), true);
+        assert_overlaps(&shape, &circle((2., 2.), 1.), false);
+    }
+
+    #[test]
+    fn circle_overlaps_quad() {
+        let shape = diamond();
+        // 中心が内側
+        assert_overlaps(&shape, &circle((0.1, 0.2), 0.1), true);
+        // 辺の上
+        assert_overlaps(&shape, &circle((0.5, 0.5), 0.), true);
+        // 頂点に接している
+        assert_overlaps(&shape, &circle((2., 0.), 1.), true);
+        // 辺までの距離は √2 / 2
+        assert_overlaps(&shape, &circle((1., 1.), 0.75), true);
+        assert_overlaps(&shape, &circle((1., 1.), 0.5), false);
+        assert_overlaps(&shape, &circle((3., 0.), 1.), false);
+    }
+
+    #[test]
+    fn circle_overlaps_flipped_quad() {
+        // 反転して頂点の並びが逆回りになっても内側と判定する
+        let flipped = CollisionShape::Quad([
+            Vector2::new(1., 0.),
+            Vector2::new(0., -1.),
+            Vector2::new(-1., 0.),
+            Vector2::new(0., 1.),
+        ]);
+        assert_overlaps(&flipped, &circle((0.1, 0.2), 0.1), true);
+        assert_overlaps(&flipped, &circle((1., 1.), 0.5), false);
+    }
+
+    #[test]
+    fn overlapping_parts() {
+        let hitboxes1 = Hitboxes::new(vec![
+            (0, aabb((0., 0.), (1., 1.))),
+            (1, circle((5., 0.), 1.)),
+        ]);
+        let hitboxes2 = Hitboxes::new(vec![(3, circle((5.5, 0.), 0.2))]);
+        assert_eq!(
+            hitboxes1.overlapping_parts(&hitboxes2).collect::<Vec<_>>(),
+            vec![(1, 3)]
+        );
+        assert!(hitboxes1.overlaps(&hitboxes2));
+        assert!(!hitboxes2.overlaps(&Hitboxes::default()));
+    }
+}
@@ -0,0 +1,37 @@
+use amethyst::ecs::{Component, DenseVecStorage};
+use std::collections::BTreeMap;
+
+// 独立動作のエフェクトキーの経過時間(IndependentEffectSystem が毎フレーム更新する)
+// アニメーションのフレームから計算しないので，ループや逆再生で巻き戻らない
+#[derive(Debug, Default)]
+pub struct IndependentEffects {
+    effects: BTreeMap<usize, (usize, f32)>, // パーツ番号ごとのキーがセットされたフレームと経過時間(秒)
+}
+
+impl IndependentEffects {
+    // キーがセットされてからの経過時間(秒)
+    pub fn elapsed(&self, part_id: usize, key_frame: usize) -> Option<f32> {
+        self.effects
+            .get(&part_id)
+            .filter(|(frame, _)| *frame == key_frame)
+            .map(|(_, elapsed)| *elapsed)
+    }
+
+    // 今のフレームで有効な独立動作のキー(パーツ番号，キーがセットされたフレーム)に置き換える
+    // 前回と同じキーなら経過時間を進め，新しいキーなら最初から再生する
+    pub(crate) fn update(&mut self, keys: &[(usize, usize)], delta_sec: f32) {
+        self.effects = keys
+            .iter()
+            .map(|&(part_id, key_frame)| {
+                let elapsed = self
+                    .elapsed(part_id, key_frame)
+                    .map_or(0., |elapsed| elapsed + delta_sec);
+                (part_id, (key_frame, elapsed))
+            })
+            .collect();
+    }
+}
+
+impl Component for IndependentEffects {
+    type Storage = DenseVecStorage<Self>;
+}
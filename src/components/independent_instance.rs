@@ -0,0 +1,74 @@
+use crate::types::InstanceKey;
+use amethyst::ecs::{Component, DenseVecStorage};
+
+// 独立動作のインスタンスパーツから生成されたエンティティ
+// 再生フレームは AnimationTime を直接進めず，経過時間とインスタンスキーから決定する
+#[derive(Debug)]
+pub struct IndependentInstance {
+    key: InstanceKey,
+    elapsed: f32,                 // 生成されてからの経過時間(秒)
+    prev: Option<(usize, usize)>, // 前回の更新での再生回数と再生フレーム
+}
+
+impl IndependentInstance {
+    pub(crate) fn new(key: InstanceKey) -> Self {
+        IndependentInstance {
+            key,
+            elapsed: 0.,
+            prev: None,
+        }
+    }
+
+    pub fn key(&self) -> &InstanceKey {
+        &self.key
+    }
+
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    pub(crate) fn add_time(&mut self, delta: f32) {
+        self.elapsed += delta;
+    }
+
+    // 生成したときの再生時間(再生方向の最初のフレーム)
+    pub(crate) fn start_time(&self, fps: f32, total_frame: usize) -> f32 {
+        let (start_frame, end_frame) = self.key.play_range(total_frame);
+        let frame = if self.key.is_backward(0) {
+            end_frame
+        } else {
+            start_frame
+        };
+        frame_time(frame as f32, fps)
+    }
+
+    // インスタンス上の前回と今回の再生時間(再生回数を超えていれば None)
+    // AnimationTime::passed_frames で通過したフレームを判定できるように，
+    // 新しい再生回に入ったときの前回の時間はその回の最初のフレームの一つ手前にする
+    pub(crate) fn update_play_time(&mut self, fps: f32, total_frame: usize) -> Option<(f32, f32)> {
+        let elapsed_frame = (self.elapsed * fps * self.key.speed_rate()) as usize;
+        let (loop_num, frame) = self.key.play_loop(elapsed_frame, total_frame)?;
+        let prev_frame = match self.prev {
+            Some((prev_loop, prev_frame)) if prev_loop == loop_num => prev_frame as f32,
+            _ => {
+                let (start_frame, end_frame) = self.key.play_range(total_frame);
+                if self.key.is_backward(loop_num) {
+                    end_frame as f32 + 1.
+                } else {
+                    start_frame as f32 - 1.
+                }
+            }
+        };
+        self.prev = Some((loop_num, frame));
+        Some((frame_time(prev_frame, fps), frame_time(frame as f32, fps)))
+    }
+}
+
+// フレームの途中の時間にしておけば前回と今回のフレームの間だけが通過したフレームになる
+fn frame_time(frame: f32, fps: f32) -> f32 {
+    (frame + 0.5) / fps
+}
+
+impl Component for IndependentInstance {
+    type Storage = DenseVecStorage<Self>;
+}
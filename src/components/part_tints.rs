@@ -0,0 +1,169 @@
+use amethyst::ecs::{Component, DenseVecStorage};
+use std::collections::BTreeMap;
+
+// パーツ名ごとの実行時の色と表示の上書き(ダメージ時の点滅，部位破壊での非表示など)
+// フェードの時間は PartTintSystem が進める
+// インスタンスパーツの参照先のアニメーションには適用しない
+#[derive(Debug, Default)]
+pub struct PartTints {
+    parts: BTreeMap<String, PartTintState>,
+}
+
+impl PartTints {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    // パーツの色にかける値
+    pub fn set_color<S: Into<String>>(&mut self, part_name: S, color: [f32; 4]) {
+        self.state_mut(part_name).color = Fade::fixed(color);
+    }
+
+    // 今の色から指定の色まで seconds 秒かけて変化させる
+    pub fn fade_color<S: Into<String>>(&mut self, part_name: S, color: [f32; 4], seconds: f32) {
+        let state = self.state_mut(part_name);
+        state.color = Fade::new(state.color.value(), color, seconds);
+    }
+
+    // テクスチャの色に足す値(アルファは強さ)
+    pub fn set_flash<S: Into<String>>(&mut self, part_name: S, flash: [f32; 4]) {
+        self.state_mut(part_name).flash = Fade::fixed(flash);
+    }
+
+    // 指定の色で光らせてから seconds 秒かけて元に戻す
+    pub fn flash<S: Into<String>>(&mut self, part_name: S, flash: [f32; 4], seconds: f32) {
+        self.state_mut(part_name).flash = Fade::new(flash, [0.; 4], seconds);
+    }
+
+    // Some(false) で強制的に非表示，Some(true) で強制的に表示，None でアニメーションのまま
+    pub fn set_visible<S: Into<String>>(&mut self, part_name: S, visible: Option<bool>) {
+        self.state_mut(part_name).visible = visible;
+    }
+
+    pub fn remove(&mut self, part_name: &str) {
+        self.parts.remove(part_name);
+    }
+
+    pub fn clear(&mut self) {
+        self.parts.clear();
+    }
+
+    // フェード中か
+    pub fn is_fading(&self, part_name: &str) -> bool {
+        self.parts
+            .get(part_name)
+            .map(|state| state.color.is_fading() || state.flash.is_fading())
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn tint(&self, part_name: &str) -> Option<PartTint> {
+        let state = self.parts.get(part_name)?;
+        Some(PartTint {
+            color: state.color.value(),
+            flash: state.flash.value(),
+            visible: state.visible,
+        })
+    }
+
+    pub(crate) fn add_time(&mut self, delta: f32) {
+        for state in self.parts.values_mut() {
+            state.color.add_time(delta);
+            state.flash.add_time(delta);
+        }
+    }
+
+    fn state_mut<S: Into<String>>(&mut self, part_name: S) -> &mut PartTintState {
+        self.parts
+            .entry(part_name.into())
+            .or_insert_with(PartTintState::default)
+    }
+}
+
+impl Component for PartTints {
+    type Storage = DenseVecStorage<Self>;
+}
+
+// ノード作成時に使う今の値
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PartTint {
+    color: [f32; 4],
+    flash: [f32; 4],
+    visible: Option<bool>,
+}
+
+impl PartTint {
+    // 乗算成分と加算成分に上書きの色を合成する
+    pub(crate) fn apply(&self, color: &mut [f32; 4], offset: &mut [f32; 4]) {
+        for (c, t) in color.iter_mut().zip(self.color.iter()) {
+            *c *= t;
+        }
+        for (o, f) in offset.iter_mut().zip(self.flash.iter()).take(3) {
+            *o += f * self.flash[3];
+        }
+    }
+
+    pub(crate) fn visible(&self) -> Option<bool> {
+        self.visible
+    }
+}
+
+#[derive(Debug)]
+struct PartTintState {
+    color: Fade,
+    flash: Fade,
+    visible: Option<bool>,
+}
+
+impl Default for PartTintState {
+    fn default() -> Self {
+        PartTintState {
+            color: Fade::fixed([1.; 4]),
+            flash: Fade::fixed([0.; 4]),
+            visible: None,
+        }
+    }
+}
+
+// 経過時間で線形に変化する色
+#[derive(Debug)]
+struct Fade {
+    from: [f32; 4],
+    to: [f32; 4],
+    duration: f32, // 秒
+    elapsed: f32,  // 秒
+}
+
+impl Fade {
+    fn new(from: [f32; 4], to: [f32; 4], duration: f32) -> Self {
+        Fade {
+            from,
+            to,
+            duration,
+            elapsed: 0.,
+        }
+    }
+
+    fn fixed(value: [f32; 4]) -> Self {
+        Fade::new(value, value, 0.)
+    }
+
+    fn is_fading(&self) -> bool {
+        self.elapsed < self.duration
+    }
+
+    fn add_time(&mut self, delta: f32) {
+        self.elapsed = (self.elapsed + delta).min(self.duration);
+    }
+
+    fn value(&self) -> [f32; 4] {
+        if self.is_fading() == false {
+            return self.to;
+        }
+        let rate = self.elapsed / self.duration;
+        let mut value = self.from;
+        for (v, to) in value.iter_mut().zip(self.to.iter()) {
+            *v += (to - *v) * rate;
+        }
+        value
+    }
+}
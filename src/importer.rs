@@ -0,0 +1,249 @@
+// SpriteStudio のプロジェクトファイル(.sspj/.ssae/.ssce)を直接読み込んで
+// ランタイム用のアニメーションデータとスプライトシートを生成する
+mod animation_pack;
+mod cell_map;
+mod effect;
+mod project;
+pub mod user;
+mod xml;
+
+pub use user::{FromUserValue, UserValue};
+
+use crate::{
+    resource::data::{AnimationData, AnimationDataBuilder},
+    traits::animation_file::AnimationFile,
+};
+use failure::Fail;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+#[derive(Debug, Fail)]
+pub enum Error {
+    #[fail(display = "io error: {}: {}", _0, _1)]
+    Io(String, std::io::Error),
+    #[fail(display = "xml parse error: {}: {}", _0, _1)]
+    Xml(String, String),
+    #[fail(display = "missing element: <{}> in {}", _0, _1)]
+    MissingElement(String, String),
+    #[fail(display = "unknown pack name: {}", _0)]
+    UnknownPackName(String),
+    #[fail(display = "unknown animation name: {}", _0)]
+    UnknownAnimationName(String),
+    #[fail(display = "unknown part name: {}", _0)]
+    UnknownPart(String),
+    #[fail(display = "unknown cell map: {}", _0)]
+    UnknownCellMap(String),
+    #[fail(display = "unknown cell: {}", _0)]
+    UnknownCell(String),
+    #[fail(display = "serialize error: {}", _0)]
+    Serialize(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// 変換後のスプライトシート
+// 画像ファイルのパスとシートの RON 文字列を持つ
+#[derive(Debug)]
+pub struct SpriteSheetData {
+    image_path: PathBuf,
+    sheet: String,
+}
+
+impl SpriteSheetData {
+    pub fn image_path(&self) -> &Path {
+        &self.image_path
+    }
+
+    pub fn sheet(&self) -> &str {
+        &self.sheet
+    }
+}
+
+// 変換後のプロジェクト
+// sprite_sheets の並びがそのままランタイムでのセルマップ番号になる
+pub struct ProjectData<T>
+where
+    T: AnimationFile,
+{
+    name: String,
+    animation_data: AnimationData<T>,
+    sprite_sheets: Vec<SpriteSheetData>,
+}
+
+impl<T> ProjectData<T>
+where
+    T: AnimationFile,
+{
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn animation_data(&self) -> &AnimationData<T> {
+        &self.animation_data
+    }
+
+    pub fn sprite_sheets(&self) -> &[SpriteSheetData] {
+        &self.sprite_sheets
+    }
+}
+
+// プロジェクトファイルを読み込んで変換する
+// パック名，アニメーション名はそれぞれ FromStr でキーに変換する
+pub fn import_project<T, P>(path: P) -> Result<ProjectData<T>>
+where
+    T: AnimationFile,
+    T::PackKey: FromStr,
+    T::AnimationKey: FromStr,
+    T::UserData: FromUserValue,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    log::info!("import project: {:?}", path);
+    let project = project::Project::load(path)?;
+
+    let cell_maps = project
+        .cell_map_paths()
+        .iter()
+        .map(|path| cell_map::CellMap::load(path))
+        .collect::<Result<Vec<_>>>()?;
+
+    let context = animation_pack::PackContext::new(&project, &cell_maps);
+    let mut packs = BTreeMap::new();
+    for pack_path in project.animation_pack_paths() {
+        let (key, pack) = animation_pack::load_pack::<T>(pack_path, &context)?;
+        packs.insert(key, pack);
+    }
+
+    // パーツの refference_effect_index はプロジェクト上のエフェクトの並び順
+    let effects = project
+        .effect_paths()
+        .iter()
+        .map(|path| effect::load_effect(path, &context))
+        .collect::<Result<Vec<_>>>()?;
+
+    let meshes = cell_maps
+        .iter()
+        .enumerate()
+        .flat_map(|(map_id, cell_map)| cell_map.to_meshes(map_id))
+        .collect();
+
+    // テキストキーが参照しているセルマップだけをビットマップフォントにする
+    let fonts = cell_maps
+        .iter()
+        .enumerate()
+        .filter(|(map_id, _)| context.is_font_map(*map_id))
+        .map(|(map_id, cell_map)| cell_map.to_font(map_id))
+        .collect();
+
+    let sprite_sheets = cell_maps
+        .iter()
+        .map(|cell_map| {
+            Ok(SpriteSheetData {
+                image_path: cell_map.image_path().to_path_buf(),
+                sheet: cell_map.to_sprite_sheet()?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(ProjectData {
+        name: project.name().to_string(),
+        animation_data: AnimationDataBuilder::new(packs)
+            .effects(effects)
+            .meshes(meshes)
+            .fonts(fonts)
+            .build(),
+        sprite_sheets,
+    })
+}
+
+// ランタイムが読み込むディレクトリ構成で書き出す
+// <assets_dir>/sprite_studio/<name>/{animation,sheet,image} 以下に出力
+pub fn export_project<T, P>(project: &ProjectData<T>, assets_dir: P, name: &str) -> Result<()>
+where
+    T: AnimationFile,
+    P: AsRef<Path>,
+{
+    let root = assets_dir.as_ref().join("sprite_studio").join(name);
+    let animation_dir = root.join("animation");
+    let sheet_dir = root.join("sheet");
+    let image_dir = root.join("image");
+    for dir in &[&animation_dir, &sheet_dir, &image_dir] {
+        std::fs::create_dir_all(dir).map_err(|e| Error::Io(dir.display().to_string(), e))?;
+    }
+
+    let animation = ron::ser::to_string_pretty(project.animation_data(), Default::default())
+        .map_err(|e| Error::Serialize(e.to_string()))?;
+    write_file(
+        &animation_dir.join("animation.anim.ron"),
+        animation.as_bytes(),
+    )?;
+
+    for (i, sheet) in project.sprite_sheets().iter().enumerate() {
+        write_file(
+            &sheet_dir.join(format!("sprite{:03}.sheet.ron", i)),
+            sheet.sheet().as_bytes(),
+        )?;
+        let image_path = image_dir.join(format!("sprite{:03}.png", i));
+        std::fs::copy(sheet.image_path(), &image_path)
+            .map_err(|e| Error::Io(sheet.image_path().display().to_string(), e))?;
+    }
+    log::info!("export project: {:?}", root);
+    Ok(())
+}
+
+// 読み込みと書き出しをまとめて行う
+// name を省略した場合はプロジェクト名をディレクトリ名にする
+pub fn convert_project<T, P, Q>(path: P, assets_dir: Q, name: Option<&str>) -> Result<()>
+where
+    T: AnimationFile,
+    T::PackKey: FromStr,
+    T::AnimationKey: FromStr,
+    T::UserData: FromUserValue,
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let project = import_project::<T, _>(path)?;
+    let name = name.unwrap_or_else(|| project.name()).to_string();
+    export_project(&project, assets_dir, &name)
+}
+
+// 変換コマンドの main
+// パック名，アニメーション名はそれぞれ T のキーの FromStr で変換するので，
+// ゲーム側で自身の AnimationFile を指定して呼び出す bin を用意するのが変換ツールの使い方になる
+//
+// // src/bin/convert.rs (ゲーム側のクレート)
+// fn main() {
+//     amethyst_sprite_studio::importer::converter_main::<MyAnimationFile>();
+// }
+//
+// usage: <command> <project.sspj> <assets_dir> [name]
+pub fn converter_main<T>()
+where
+    T: AnimationFile,
+    T::PackKey: FromStr,
+    T::AnimationKey: FromStr,
+    T::UserData: FromUserValue,
+{
+    let args = std::env::args().collect::<Vec<_>>();
+    if args.len() < 3 {
+        let command = args.get(0).map(|command| command.as_str());
+        eprintln!(
+            "usage: {} <project.sspj> <assets_dir> [name]",
+            command.unwrap_or("sprite_studio_convert")
+        );
+        std::process::exit(1);
+    }
+
+    let name = args.get(3).map(|name| name.as_str());
+    if let Err(e) = convert_project::<T, _, _>(&args[1], &args[2], name) {
+        eprintln!("convert failed: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn write_file(path: &Path, data: &[u8]) -> Result<()> {
+    std::fs::write(path, data).map_err(|e| Error::Io(path.display().to_string(), e))
+}
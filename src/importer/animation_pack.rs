@@ -0,0 +1,979 @@
+// アニメーションパックファイル(.ssae)
+use super::{
+    cell_map::CellMap, project::Project, user::FromUserValue, xml, Error, Result, UserValue,
+};
+use crate::{
+    resource::{
+        animation::AnimationBuilder,
+        pack::{Pack, PackBuilder},
+        part::{Part, PartBuilder},
+    },
+    traits::animation_file::AnimationFile,
+    types::{
+        cell::{Cell, CellBuilder},
+        interpolate::{Curve, Interpolation, KeyInterpolation},
+        AlphaBlend, BoneWeight, Bounds, ColorBlend, DeformKeyBuilder, EffectKeyBuilder,
+        Inheritance, InheritanceBuilder, InstanceKeyBuilder, LinearColor, PartType, SortMode,
+        TextAlign, TextKeyBuilder, VertexColorKey, VertexColorKeyBuilder, VertexKeyBuilder,
+    },
+};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+    str::FromStr,
+};
+use xmltree::Element;
+
+// パック間で共有するプロジェクト情報
+pub(crate) struct PackContext<'a> {
+    cell_maps: &'a [CellMap],
+    effect_names: Vec<String>,
+    font_map_ids: RefCell<BTreeSet<usize>>, // テキストキーがフォントとして参照したセルマップ番号
+}
+
+impl<'a> PackContext<'a> {
+    pub(crate) fn new(project: &Project, cell_maps: &'a [CellMap]) -> Self {
+        let effect_names = project
+            .effect_paths()
+            .iter()
+            .map(|path| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            })
+            .collect();
+        PackContext {
+            cell_maps,
+            effect_names,
+            font_map_ids: RefCell::new(BTreeSet::new()),
+        }
+    }
+
+    // パック内のセルマップ名からプロジェクト上のセルマップ番号を取得
+    fn cell_map_index(&self, name: &str) -> Result<usize> {
+        let file_name = Path::new(name)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.cell_maps
+            .iter()
+            .position(|cell_map| cell_map.file_name() == file_name)
+            .ok_or_else(|| Error::UnknownCellMap(name.into()))
+    }
+
+    // エフェクトから参照されるセル
+    pub(crate) fn cell(&self, map_name: &str, cell_name: &str) -> Result<Cell> {
+        let map_id = self.cell_map_index(map_name)?;
+        let cell_id = self.cell_maps[map_id].cell_index(cell_name)?;
+        Ok(CellBuilder::new(map_id, cell_id).build())
+    }
+
+    fn effect_index(&self, name: &str) -> Option<usize> {
+        self.effect_names.iter().position(|effect| effect == name)
+    }
+
+    // テキストパーツのフォントとして参照されたセルマップか(全てのパックを読み込んだ後に使う)
+    pub(crate) fn is_font_map(&self, map_id: usize) -> bool {
+        self.font_map_ids.borrow().contains(&map_id)
+    }
+}
+
+type PackData<T> = Pack<
+    <T as AnimationFile>::UserData,
+    <T as AnimationFile>::PackKey,
+    <T as AnimationFile>::AnimationKey,
+>;
+
+pub(crate) fn load_pack<T>(path: &Path, context: &PackContext) -> Result<(T::PackKey, PackData<T>)>
+where
+    T: AnimationFile,
+    T::PackKey: FromStr,
+    T::AnimationKey: FromStr,
+    T::UserData: FromUserValue,
+{
+    log::info!("load animation pack: {:?}", path);
+    parse_pack::<T>(&xml::load(path)?, context)
+}
+
+// 読み込み済みの <SpriteStudioAnimePack> 要素を変換する
+fn parse_pack<T>(root: &Element, context: &PackContext) -> Result<(T::PackKey, PackData<T>)>
+where
+    T: AnimationFile,
+    T::PackKey: FromStr,
+    T::AnimationKey: FromStr,
+    T::UserData: FromUserValue,
+{
+    let pack_name = xml::text(xml::require(root, "name")?);
+    let pack_key = parse_pack_key::<T>(&pack_name)?;
+
+    // パック内のセルマップ番号 -> プロジェクト上のセルマップ番号
+    let map_ids = xml::values(root, "cellmapNames")
+        .map(|value| context.cell_map_index(&xml::text(value)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let model = xml::require(root, "Model")?;
+    let part_elements = xml::values(model, "partList").collect::<Vec<_>>();
+    let part_names = part_elements
+        .iter()
+        .map(|part| xml::child_text(part, "name").unwrap_or_default())
+        .collect::<Vec<_>>();
+
+    // メッシュとボーンの紐付けはメッシュパーツの並び順で保存されている
+    let bone_ids = bone_part_ids(model, &part_names);
+    let mut mesh_binds = xml::values(model, "meshList")
+        .map(|value| parse_mesh_bind(&xml::text(value), &bone_ids))
+        .collect::<Vec<_>>()
+        .into_iter();
+    // 親と同じ継承設定のパーツがあるので親から順に読み込む
+    let mut parts = Vec::with_capacity(part_elements.len());
+    for part in part_elements.iter() {
+        let mesh_bind = match xml::child_text(part, "type").as_ref().map(|s| s.as_str()) {
+            Some("mesh") => mesh_binds.next(),
+            _ => None,
+        };
+        let part = load_part::<T>(part, context, mesh_bind, &parts)?;
+        parts.push(part);
+    }
+
+    let keys = PackKeys {
+        part_names: &part_names,
+        map_ids: &map_ids,
+        cell_maps: context.cell_maps,
+        font_map_ids: &context.font_map_ids,
+    };
+
+    let default_settings = root.get_child("settings");
+    let mut animations = BTreeMap::new();
+    let mut setup = None;
+    for anime in xml::children(xml::require(root, "animeList")?, "anime") {
+        let anime_name = xml::text(xml::require(anime, "name")?);
+        let settings = anime.get_child("settings").or(default_settings);
+        let setting = |name: &str| settings.and_then(|settings| xml::child_parse(settings, name));
+        let fps = setting("fps").unwrap_or(60);
+        let total_frame = setting("frameCount").unwrap_or(1);
+
+        let mut builder = AnimationBuilder::new(parts.len(), total_frame, fps);
+        // 描画順は SpriteStudio の既定と同じく省略時は優先度で並べる
+        let sort_mode = settings.and_then(|settings| xml::child_text(settings, "sortMode"));
+        builder.set_sort_mode(match sort_mode.as_ref().map(|mode| mode.as_str()) {
+            Some("z") => SortMode::Z,
+            _ => SortMode::Priority,
+        });
+        let mut hide_key_at_start = vec![false; parts.len()];
+        if let Some(part_animes) = anime.get_child("partAnimes") {
+            for part_anime in xml::children(part_animes, "partAnime") {
+                let (part_id, hide_key) = keys.add_part_anime::<T>(&mut builder, part_anime)?;
+                hide_key_at_start[part_id] |= hide_key;
+            }
+        }
+        // SpriteStudio ではキーの無いパーツも表示されるが，キーが存在しないフレームは非表示扱いなので
+        // 先頭に非表示キーが無いパーツ(partAnime が無いパーツも含む)には表示キーを打っておく
+        for (part_id, _) in hide_key_at_start
+            .iter()
+            .enumerate()
+            .filter(|(_, hide_key)| **hide_key == false)
+        {
+            builder.add_hide(part_id, 0, Interpolation::Step, false);
+        }
+        let animation = builder.build();
+
+        // セットアップアニメーションは各パーツの初期値として扱う
+        if xml::child_bool(anime, "isSetup").unwrap_or(false) {
+            setup = Some(animation);
+        } else {
+            let anime_key = parse_animation_key::<T>(&anime_name)?;
+            animations.insert(anime_key, animation);
+        }
+    }
+
+    Ok((pack_key, PackBuilder::new(parts, animations, setup).build()))
+}
+
+fn parse_pack_key<T>(name: &str) -> Result<T::PackKey>
+where
+    T: AnimationFile,
+    T::PackKey: FromStr,
+{
+    name.parse()
+        .map_err(|_| Error::UnknownPackName(name.into()))
+}
+
+fn parse_animation_key<T>(name: &str) -> Result<T::AnimationKey>
+where
+    T: AnimationFile,
+    T::AnimationKey: FromStr,
+{
+    name.parse()
+        .map_err(|_| Error::UnknownAnimationName(name.into()))
+}
+
+// <boneList> のボーン番号からパーツ番号への変換表
+fn bone_part_ids(model: &Element, part_names: &[String]) -> Vec<Option<usize>> {
+    let mut bone_ids = vec![];
+    for item in model
+        .get_child("boneList")
+        .into_iter()
+        .flat_map(|list| xml::children(list, "item"))
+    {
+        let bone_index = match xml::text(item).parse::<usize>() {
+            Ok(index) => index,
+            Err(_) => continue,
+        };
+        let part_id = item
+            .attributes
+            .get("key")
+            .and_then(|name| part_names.iter().position(|part| part == name));
+        if bone_ids.len() <= bone_index {
+            bone_ids.resize(bone_index + 1, None);
+        }
+        bone_ids[bone_index] = part_id;
+    }
+    bone_ids
+}
+
+// 頂点ごとに "ボーン数,(ボーン番号,ウェイト,オフセットx,オフセットy,オフセットz)*ボーン数" が並ぶ
+// オフセットはセットアップから計算するので読み飛ばす
+fn parse_mesh_bind(text: &str, bone_ids: &[Option<usize>]) -> Vec<Vec<BoneWeight>> {
+    let values = text
+        .split(',')
+        .filter_map(|v| v.trim().parse::<f32>().ok())
+        .collect::<Vec<_>>();
+    let mut vertices = vec![];
+    let mut rest = values.as_slice();
+    while let Some((&bone_num, tail)) = rest.split_first() {
+        let bone_num = bone_num as usize;
+        let len = (bone_num * 5).min(tail.len());
+        let weights = tail[..len]
+            .chunks(5)
+            .filter_map(|bind| match bind {
+                &[bone_index, weight, ..] => {
+                    let bone_id = (*bone_ids.get(bone_index as usize)?)?;
+                    Some(BoneWeight::new(bone_id, weight))
+                }
+                _ => None,
+            })
+            .collect();
+        vertices.push(weights);
+        rest = &tail[len..];
+    }
+    vertices
+}
+
+fn load_part<T>(
+    element: &Element,
+    context: &PackContext,
+    mesh_bind: Option<Vec<Vec<BoneWeight>>>,
+    loaded_parts: &[Part<T::PackKey, T::AnimationKey>],
+) -> Result<Part<T::PackKey, T::AnimationKey>>
+where
+    T: AnimationFile,
+    T::PackKey: FromStr,
+    T::AnimationKey: FromStr,
+{
+    let name = xml::child_text(element, "name").unwrap_or_default();
+    let part_type = match xml::child_text(element, "type")
+        .as_ref()
+        .map(|s| s.as_str())
+    {
+        Some("null") => PartType::Null,
+        Some("normal") => PartType::Normal,
+        Some("text") => PartType::Text,
+        Some("instance") => PartType::Instance,
+        Some("mesh") => PartType::Mesh,
+        Some("bone") | Some("bonepoint") => PartType::Bone,
+        Some("joint") => PartType::Joint,
+        Some("armature") => PartType::Armature,
+        Some("effect") => PartType::Effect,
+        Some("mask") => PartType::Mask,
+        other => {
+            log::warn!("unsupported part type: {:?} ({})", other, name);
+            PartType::Null
+        }
+    };
+    let bounds = match xml::child_text(element, "boundsType")
+        .as_ref()
+        .map(|s| s.as_str())
+    {
+        Some("quad") => Some(Bounds::Quad),
+        Some("aabb") => Some(Bounds::Aabb),
+        Some("circle") => Some(Bounds::Circle),
+        Some("circle_smin") => Some(Bounds::CircleMin),
+        Some("circle_smax") => Some(Bounds::CircleMax),
+        _ => None,
+    };
+
+    let mut builder = PartBuilder::new(name, part_type).bounds(bounds);
+
+    if let Some(mask_influence) = xml::child_bool(element, "maskInfluence") {
+        builder = builder.mask_influence(mask_influence);
+    }
+
+    if let Some(mesh_bind) = mesh_bind {
+        builder = builder.mesh_weights(mesh_bind);
+    }
+
+    if let Some(blend) = xml::child_text(element, "alphaBlendType") {
+        builder = builder.alpha_blend(parse_alpha_blend(&blend));
+    }
+
+    // ルートパーツの親は -1 で保存されている
+    let parent_id = xml::child_parse::<i32>(element, "parentIndex")
+        .filter(|&parent_id| parent_id >= 0)
+        .map(|parent_id| parent_id as u32);
+    if let Some(parent_id) = parent_id {
+        builder = builder.parent_id(parent_id);
+    }
+
+    // 継承設定が親と同じ場合は読み込み済みの親パーツの設定を使う
+    let inheritance = match xml::child_text(element, "inheritType")
+        .as_ref()
+        .map(|s| s.as_str())
+    {
+        Some("self") => parse_inheritance(element),
+        _ => parent_id
+            .and_then(|parent_id| loaded_parts.get(parent_id as usize))
+            .map(|parent| parent.inheritance())
+            .unwrap_or_default(),
+    };
+    builder = builder.inheritance(inheritance);
+
+    match (
+        xml::child_text(element, "refAnimePack"),
+        xml::child_text(element, "refAnime"),
+    ) {
+        (Some(pack), Some(animation)) if !pack.is_empty() && !animation.is_empty() => {
+            builder = builder.refference_animation_name(
+                parse_pack_key::<T>(&pack)?,
+                parse_animation_key::<T>(&animation)?,
+            );
+        }
+        _ => {}
+    }
+
+    if let Some(effect) = xml::child_text(element, "refEffectName") {
+        if let Some(index) = context.effect_index(&effect) {
+            builder = builder.refference_effect_index(index);
+        }
+    }
+
+    Ok(builder.build())
+}
+
+// 継承する値ごとに 1 なら継承，0 なら継承しない(タグ名 ineheritRates の綴りは ssae のまま)
+// スケールの継承は ssae に設定がないので常に継承する
+fn parse_inheritance(element: &Element) -> Inheritance {
+    let rates = match element.get_child("ineheritRates") {
+        Some(rates) => rates,
+        None => return Inheritance::default(),
+    };
+    // 省略された値は Inheritance::default と同じにする
+    let inherit = |name, default| {
+        xml::child_parse::<f32>(rates, name)
+            .map(|rate| rate != 0.)
+            .unwrap_or(default)
+    };
+    InheritanceBuilder::new()
+        .alpha(inherit("ALPH", false))
+        .flip_h(inherit("FLPH", true))
+        .flip_v(inherit("FLPV", true))
+        .hide(inherit("HIDE", true))
+        .build()
+}
+
+// キーフレームの値変換に必要なパック情報
+struct PackKeys<'a> {
+    part_names: &'a [String],
+    map_ids: &'a [usize],
+    cell_maps: &'a [CellMap],
+    font_map_ids: &'a RefCell<BTreeSet<usize>>,
+}
+
+impl<'a> PackKeys<'a> {
+    // パーツのキーを追加して，パーツ ID と先頭フレームに非表示キーがあったかを返す
+    fn add_part_anime<T>(
+        &self,
+        builder: &mut AnimationBuilder<T::UserData>,
+        part_anime: &Element,
+    ) -> Result<(usize, bool)>
+    where
+        T: AnimationFile,
+        T::UserData: FromUserValue,
+    {
+        let part_name = xml::text(xml::require(part_anime, "partName")?);
+        let part_id = self
+            .part_names
+            .iter()
+            .position(|name| *name == part_name)
+            .ok_or_else(|| Error::UnknownPart(part_name.clone()))?;
+
+        let mut hide_key_at_start = false;
+        let attributes = match part_anime.get_child("attributes") {
+            Some(attributes) => attributes,
+            None => return Ok((part_id, false)),
+        };
+        for attribute in xml::children(attributes, "attribute") {
+            let tag = attribute
+                .attributes
+                .get("tag")
+                .map(|tag| tag.as_str())
+                .unwrap_or("");
+            for key in xml::children(attribute, "key") {
+                let frame = key
+                    .attributes
+                    .get("time")
+                    .and_then(|time| time.parse().ok())
+                    .unwrap_or(0);
+                let value = match key.get_child("value") {
+                    Some(value) => value,
+                    None => continue,
+                };
+                if tag == "HIDE" && frame == 0 {
+                    hide_key_at_start = true;
+                }
+                self.add_key::<T>(builder, part_id, tag, frame, interpolation(key), value)?;
+            }
+        }
+
+        Ok((part_id, hide_key_at_start))
+    }
+
+    fn add_key<T>(
+        &self,
+        builder: &mut AnimationBuilder<T::UserData>,
+        part_id: usize,
+        tag: &str,
+        frame: usize,
+        interpolation: KeyInterpolation,
+        value: &Element,
+    ) -> Result<()>
+    where
+        T: AnimationFile,
+        T::UserData: FromUserValue,
+    {
+        let text = xml::text(value);
+        let float = || text.parse::<f32>().unwrap_or(0.);
+        match tag {
+            "CELL" => {
+                let map_id = xml::child_parse::<usize>(value, "mapId").unwrap_or(0);
+                let map_id = *self
+                    .map_ids
+                    .get(map_id)
+                    .ok_or_else(|| Error::UnknownCellMap(map_id.to_string()))?;
+                let cell_name = xml::child_text(value, "name").unwrap_or_default();
+                let cell_id = self.cell_maps[map_id].cell_index(&cell_name)?;
+                builder.add_cell(
+                    part_id,
+                    frame,
+                    interpolation,
+                    CellBuilder::new(map_id, cell_id).build(),
+                );
+            }
+            "POSX" => builder.add_pos_x(part_id, frame, interpolation, float()),
+            "POSY" => builder.add_pos_y(part_id, frame, interpolation, float()),
+            "POSZ" => builder.add_pos_z(part_id, frame, interpolation, float()),
+            "SCLX" => builder.add_scale_x(part_id, frame, interpolation, float()),
+            "SCLY" => builder.add_scale_y(part_id, frame, interpolation, float()),
+            "ROTZ" => builder.add_rotated(part_id, frame, interpolation, float()),
+            "ALPH" => builder.add_alpha(part_id, frame, interpolation, float()),
+            "LALP" => builder.add_local_alpha(part_id, frame, interpolation, float()),
+            "LSCX" => builder.add_local_scale_x(part_id, frame, interpolation, float()),
+            "LSCY" => builder.add_local_scale_y(part_id, frame, interpolation, float()),
+            "PVTX" => builder.add_pivot_x(part_id, frame, interpolation, float()),
+            "PVTY" => builder.add_pivot_y(part_id, frame, interpolation, float()),
+            "SIZX" => builder.add_size_x(part_id, frame, interpolation, float()),
+            "SIZY" => builder.add_size_y(part_id, frame, interpolation, float()),
+            "UVTX" => builder.add_uv_x(part_id, frame, interpolation, float()),
+            "UVTY" => builder.add_uv_y(part_id, frame, interpolation, float()),
+            "UVRZ" => builder.add_uv_rotated(part_id, frame, interpolation, float()),
+            "UVSX" => builder.add_uv_scale_x(part_id, frame, interpolation, float()),
+            "UVSY" => builder.add_uv_scale_y(part_id, frame, interpolation, float()),
+            "PRIO" => builder.add_priority(part_id, frame, interpolation, float()),
+            "BNDR" => builder.add_bounding_radius(part_id, frame, interpolation, float()),
+            "HIDE" => builder.add_hide(part_id, frame, interpolation, xml::parse_bool(&text)),
+            "FLPH" => builder.add_flip_h(part_id, frame, interpolation, xml::parse_bool(&text)),
+            "FLPV" => builder.add_flip_v(part_id, frame, interpolation, xml::parse_bool(&text)),
+            "VCOL" | "PCOL" => {
+                let blend = match xml::child_text(value, "blendType")
+                    .as_ref()
+                    .map(|b| b.as_str())
+                {
+                    Some("mix") => ColorBlend::Mix,
+                    Some("add") => ColorBlend::Add,
+                    Some("sub") => ColorBlend::Subtract,
+                    _ => ColorBlend::Multiply,
+                };
+                match xml::child_text(value, "target")
+                    .as_ref()
+                    .map(|s| s.as_str())
+                {
+                    Some("vertex") => {
+                        builder.add_vertex_color(
+                            part_id,
+                            frame,
+                            interpolation,
+                            vertex_color(value),
+                        );
+                    }
+                    _ => {
+                        let (color, rate) = value
+                            .get_child("color")
+                            .map(blend_color)
+                            .unwrap_or((LinearColor::default(), 0.));
+                        builder.add_color(part_id, frame, interpolation, color);
+                        builder.add_color_rate(part_id, frame, interpolation, rate);
+                    }
+                }
+                builder.add_color_blend(part_id, frame, interpolation, blend);
+            }
+            "USER" => {
+                if let Some(user) =
+                    <T::UserData as FromUserValue>::from_user_value(&user_value(value))
+                {
+                    builder.add_user(part_id, frame, interpolation, user);
+                }
+            }
+            "IPRM" => {
+                let mut instance = InstanceKeyBuilder::new();
+                if let Some(infinity) = xml::child_bool(value, "infinity") {
+                    instance = instance.infinity(infinity);
+                }
+                if let Some(independent) = xml::child_bool(value, "independent") {
+                    instance = instance.independent(independent);
+                }
+                if let Some(loop_num) = xml::child_parse(value, "loopNum") {
+                    instance = instance.loop_num(loop_num);
+                }
+                if let Some(start_offset) = xml::child_parse(value, "startOffset") {
+                    instance = instance.start_offset(start_offset);
+                }
+                if let Some(end_offset) = xml::child_parse(value, "endOffset") {
+                    instance = instance.end_offset(end_offset);
+                }
+                if let Some(reverse) = xml::child_bool(value, "reverse") {
+                    instance = instance.reverse(reverse);
+                }
+                if let Some(pingpong) = xml::child_bool(value, "pingpong") {
+                    instance = instance.pingpong(pingpong);
+                }
+                if let Some(speed) = xml::child_parse(value, "speed") {
+                    instance = instance.speed_rate(speed);
+                }
+                builder.add_instance(part_id, frame, interpolation, instance.build());
+            }
+            "VERT" => {
+                let corner = |name: &str| {
+                    xml::child_text(value, name)
+                        .and_then(|v| xml::parse_pair(&v))
+                        .unwrap_or((0., 0.))
+                };
+                let vertex = VertexKeyBuilder::new()
+                    .lt(corner("LT"))
+                    .rt(corner("RT"))
+                    .lb(corner("LB"))
+                    .rb(corner("RB"))
+                    .build();
+                builder.add_vertex(part_id, frame, interpolation, vertex);
+            }
+            "EFCT" => {
+                let mut effect = EffectKeyBuilder::new();
+                if let Some(start_time) = xml::child_parse(value, "startTime") {
+                    effect = effect.start_time(start_time);
+                }
+                if let Some(speed) = xml::child_parse(value, "speed") {
+                    effect = effect.speed(speed);
+                }
+                if let Some(independent) = xml::child_bool(value, "independent") {
+                    effect = effect.independent(independent);
+                }
+                builder.add_effect(part_id, frame, interpolation, effect.build());
+            }
+            "DEFORM" => {
+                // 変更のある頂点のみ "頂点番号 x y" で保存されている
+                let vertex_num = xml::child_parse(value, "vsize").unwrap_or(0);
+                let deform = xml::children(value, "vchg")
+                    .filter_map(
+                        |vchg| match xml::parse_floats(&xml::text(vchg)).as_slice() {
+                            &[index, x, y, ..] => Some((index as usize, (x, y))),
+                            _ => None,
+                        },
+                    )
+                    .fold(
+                        DeformKeyBuilder::new(vertex_num),
+                        |deform, (index, offset)| deform.offset(index, offset),
+                    );
+                builder.add_deform(part_id, frame, interpolation, deform.build());
+            }
+            "TEXT" => {
+                // フォントは CELL と同じくパック内のセルマップ番号で指定する
+                let map_id = xml::child_parse::<usize>(value, "mapId").unwrap_or(0);
+                let map_id = *self
+                    .map_ids
+                    .get(map_id)
+                    .ok_or_else(|| Error::UnknownCellMap(map_id.to_string()))?;
+                self.font_map_ids.borrow_mut().insert(map_id);
+                let align = match xml::child_text(value, "align").as_ref().map(|a| a.as_str()) {
+                    Some("center") => TextAlign::Center,
+                    Some("right") => TextAlign::Right,
+                    _ => TextAlign::Left,
+                };
+                let mut text = TextKeyBuilder::new(
+                    xml::child_text(value, "string").unwrap_or_default(),
+                    map_id,
+                )
+                .align(align);
+                if let Some(size) = xml::child_parse(value, "size") {
+                    text = text.size(size);
+                }
+                builder.add_text(part_id, frame, interpolation, text.build());
+            }
+            _ => log::warn!("unsupported attribute: {} (part = {})", tag, part_id),
+        }
+        Ok(())
+    }
+}
+
+fn interpolation(key: &Element) -> KeyInterpolation {
+    let interpolation = match key.attributes.get("ipType").map(|ip| ip.as_str()) {
+        Some("linear") => Interpolation::Linear,
+        Some("hermite") => Interpolation::Hermite,
+        Some("bezier") => Interpolation::Bezier,
+        Some("acceleration") => Interpolation::Acceleration,
+        Some("deceleration") => Interpolation::Deceleration,
+        _ => Interpolation::Step,
+    };
+
+    // カーブは "開始時間 開始値 終了時間 終了値" の順で保存されている
+    let curve = xml::child_text(key, "curve").and_then(|curve| {
+        match xml::parse_floats(&curve).as_slice() {
+            &[start_time, start_value, end_time, end_value, ..] => {
+                Some(Curve::new(start_time, start_value, end_time, end_value))
+            }
+            _ => None,
+        }
+    });
+
+    match curve {
+        Some(curve) => KeyInterpolation::with_curve(interpolation, curve),
+        None => interpolation.into(),
+    }
+}
+
+// AARRGGBB 形式の16進数
+pub(crate) fn parse_argb(text: &str) -> LinearColor {
+    let argb = u32::from_str_radix(text, 16).unwrap_or(0xffff_ffff);
+    let channel = |shift: u32| ((argb >> shift) & 0xff) as f32 / 255.;
+    LinearColor(channel(16), channel(8), channel(0), channel(24))
+}
+
+// パーツ，エフェクトの合成方法(大文字小文字は区別しない)
+pub(crate) fn parse_alpha_blend(text: &str) -> AlphaBlend {
+    match text.to_lowercase().as_str() {
+        "mix" => AlphaBlend::Mix,
+        "mul" => AlphaBlend::Multiply,
+        "add" => AlphaBlend::Add,
+        "sub" => AlphaBlend::Subtract,
+        "screen" => AlphaBlend::Screen,
+        "exclusion" => AlphaBlend::Exclusion,
+        other => {
+            log::warn!("unsupported alpha blend: {}", other);
+            AlphaBlend::Mix
+        }
+    }
+}
+
+// <rgba> と <rate> の組
+fn blend_color(element: &Element) -> (LinearColor, f32) {
+    let color = xml::child_text(element, "rgba")
+        .map(|rgba| parse_argb(&rgba))
+        .unwrap_or_default();
+    let rate = xml::child_parse(element, "rate").unwrap_or(1.);
+    (color, rate)
+}
+
+// 頂点ごとの色は四隅それぞれの <rgba> と <rate> の組
+fn vertex_color(value: &Element) -> VertexColorKey {
+    let corner = |name: &str| {
+        value
+            .get_child(name)
+            .map(blend_color)
+            .unwrap_or((LinearColor::default(), 0.))
+    };
+    let (lt, rt, lb, rb) = (corner("LT"), corner("RT"), corner("LB"), corner("RB"));
+    VertexColorKeyBuilder::new()
+        .lt(lt.0, lt.1)
+        .rt(rt.0, rt.1)
+        .lb(lb.0, lb.1)
+        .rb(rb.0, rb.1)
+        .build()
+}
+
+fn user_value(value: &Element) -> UserValue {
+    UserValue {
+        integer: xml::child_parse(value, "integer"),
+        point: xml::child_text(value, "point").and_then(|point| xml::parse_pair(&point)),
+        rect: xml::child_text(value, "rect").and_then(|rect| {
+            match xml::parse_floats(&rect).as_slice() {
+                &[l, t, r, b, ..] => Some((l, t, r, b)),
+                _ => None,
+            }
+        }),
+        string: xml::child_text(value, "string"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TestFile;
+
+    // パック名とアニメーション名は TestFile のキーに合わせて数値にする
+    const PACK: &str = r#"
+        <SpriteStudioAnimePack version="2.00.00">
+            <name>0</name>
+            <Model>
+                <partList>
+                    <value><name>root</name><type>null</type><parentIndex>-1</parentIndex></value>
+                    <value><name>body</name><type>normal</type><parentIndex>0</parentIndex></value>
+                    <value><name>hidden</name><type>normal</type><parentIndex>0</parentIndex></value>
+                    <value>
+                        <name>instance</name><type>instance</type><parentIndex>1</parentIndex>
+                        <refAnimePack>1</refAnimePack><refAnime>2</refAnime>
+                    </value>
+                    <value><name>counted</name><type>instance</type><parentIndex>1</parentIndex></value>
+                </partList>
+            </Model>
+            <animeList>
+                <anime>
+                    <name>Setup</name>
+                    <isSetup>1</isSetup>
+                </anime>
+                <anime>
+                    <name>3</name>
+                    <settings><fps>30</fps><frameCount>10</frameCount></settings>
+                    <partAnimes>
+                        <partAnime>
+                            <partName>body</partName>
+                            <attributes>
+                                <attribute tag="POSX">
+                                    <key time="0" ipType="linear"><value>10</value></key>
+                                    <key time="4" ipType="linear"><value>50</value></key>
+                                </attribute>
+                                <attribute tag="HIDE">
+                                    <key time="5"><value>1</value></key>
+                                </attribute>
+                                <attribute tag="FLPH">
+                                    <key time="0"><value>1</value></key>
+                                </attribute>
+                                <attribute tag="BNDR">
+                                    <key time="0"><value>12</value></key>
+                                </attribute>
+                                <attribute tag="USER">
+                                    <key time="2"><value><integer>7</integer></value></key>
+                                </attribute>
+                            </attributes>
+                        </partAnime>
+                        <partAnime>
+                            <partName>hidden</partName>
+                            <attributes>
+                                <attribute tag="HIDE">
+                                    <key time="0"><value>1</value></key>
+                                </attribute>
+                            </attributes>
+                        </partAnime>
+                        <partAnime>
+                            <partName>instance</partName>
+                            <attributes>
+                                <attribute tag="IPRM">
+                                    <key time="0">
+                                        <value>
+                                            <infinity>1</infinity>
+                                            <reverse>1</reverse>
+                                            <pingpong>1</pingpong>
+                                            <independent>1</independent>
+                                            <loopNum>3</loopNum>
+                                            <startOffset>1</startOffset>
+                                            <endOffset>2</endOffset>
+                                            <speed>2</speed>
+                                        </value>
+                                    </key>
+                                </attribute>
+                            </attributes>
+                        </partAnime>
+                        <partAnime>
+                            <partName>counted</partName>
+                            <attributes>
+                                <attribute tag="IPRM">
+                                    <key time="0">
+                                        <value>
+                                            <infinity>0</infinity>
+                                            <loopNum>3</loopNum>
+                                        </value>
+                                    </key>
+                                </attribute>
+                            </attributes>
+                        </partAnime>
+                    </partAnimes>
+                </anime>
+            </animeList>
+        </SpriteStudioAnimePack>
+    "#;
+
+    fn element(text: &str) -> Element {
+        Element::parse(text.as_bytes()).unwrap()
+    }
+
+    fn context() -> PackContext<'static> {
+        PackContext {
+            cell_maps: &[],
+            effect_names: vec![],
+            font_map_ids: RefCell::new(BTreeSet::new()),
+        }
+    }
+
+    fn pack() -> PackData<TestFile> {
+        let (key, pack) = parse_pack::<TestFile>(&element(PACK), &context()).unwrap();
+        assert_eq!(key, 0);
+        pack
+    }
+
+    #[test]
+    fn parts() {
+        let pack = pack();
+        let parts = pack
+            .parts()
+            .map(|part| (part.name(), part.part_type(), part.parent_id()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parts,
+            vec![
+                ("root", PartType::Null, None),
+                ("body", PartType::Normal, Some(0)),
+                ("hidden", PartType::Normal, Some(0)),
+                ("instance", PartType::Instance, Some(1)),
+                ("counted", PartType::Instance, Some(1)),
+            ]
+        );
+        let reference = pack.part(3).unwrap().refference_animation_name().unwrap();
+        assert_eq!(reference.pack_animation(&0, &3), Some((1, 2)));
+        assert!(pack.part(4).unwrap().refference_animation_name().is_none());
+    }
+
+    #[test]
+    fn keys() {
+        let pack = pack();
+        assert!(pack.setup_info().is_some());
+        let animation = pack.animation(&3).unwrap();
+        assert_eq!(animation.fps(), 30);
+        assert_eq!(animation.total_frame(), 10);
+
+        assert_eq!(animation.local_transform(1, 0).translation().x, 10.);
+        assert_eq!(animation.local_transform(1, 2).translation().x, 30.);
+        assert_eq!(animation.local_transform(1, 9).translation().x, 50.);
+        assert_eq!(animation.local_flip(1, 3), (true, false));
+        assert_eq!(animation.bounding_radius(1, 3), Some(12.));
+        assert_eq!(animation.bounding_radius(2, 3), None);
+        assert_eq!(animation.user(1, 1), None);
+        assert_eq!(
+            animation.user_keys(1, 0..10).collect::<Vec<_>>(),
+            vec![(2, &7)]
+        );
+    }
+
+    // 先頭に非表示キーの無いパーツは表示キーを補う
+    #[test]
+    fn hide_defaults() {
+        let pack = pack();
+        let animation = pack.animation(&3).unwrap();
+        assert!(animation.hide(0, 0) == false);
+        assert!(animation.hide(1, 4) == false);
+        assert!(animation.hide(1, 5));
+        assert!(animation.hide(2, 0));
+        assert!(animation.hide(4, 9) == false);
+    }
+
+    #[test]
+    fn instance_flags() {
+        let pack = pack();
+        let animation = pack.animation(&3).unwrap();
+
+        let (key_frame, instance) = animation.instance(3, 0).unwrap();
+        assert_eq!(key_frame, 0);
+        assert!(instance.independent());
+        assert_eq!(instance.loop_num(), None); // infinity が優先される
+        assert!(instance.reverse());
+        assert!(instance.pingpong());
+        assert_eq!(instance.start_offset(), 1);
+        assert_eq!(instance.end_offset(), 2);
+        assert_eq!(instance.speed_rate(), 2.);
+
+        let (_, instance) = animation.instance(4, 0).unwrap();
+        assert!(instance.independent() == false);
+        assert_eq!(instance.loop_num(), Some(3));
+        assert!(instance.reverse() == false);
+        assert!(instance.pingpong() == false);
+        assert_eq!(instance.speed_rate(), 1.);
+    }
+
+    #[test]
+    fn inheritance() {
+        let text = r#"
+            <SpriteStudioAnimePack>
+                <name>0</name>
+                <Model>
+                    <partList>
+                        <value>
+                            <name>root</name><type>null</type><parentIndex>-1</parentIndex>
+                            <inheritType>parent</inheritType>
+                        </value>
+                        <value>
+                            <name>self</name><type>normal</type><parentIndex>0</parentIndex>
+                            <inheritType>self</inheritType>
+                            <ineheritRates>
+                                <ALPH>1</ALPH><FLPH>0</FLPH><FLPV>1</FLPV><HIDE>0</HIDE>
+                            </ineheritRates>
+                        </value>
+                        <value>
+                            <name>child</name><type>normal</type><parentIndex>1</parentIndex>
+                            <inheritType>parent</inheritType>
+                        </value>
+                        <value>
+                            <name>omitted</name><type>normal</type><parentIndex>0</parentIndex>
+                            <inheritType>self</inheritType>
+                        </value>
+                    </partList>
+                </Model>
+                <animeList />
+            </SpriteStudioAnimePack>
+        "#;
+        let (_, pack) = parse_pack::<TestFile>(&element(text), &context()).unwrap();
+        let inheritance = pack
+            .parts()
+            .map(|part| part.inheritance())
+            .collect::<Vec<_>>();
+
+        let rates = InheritanceBuilder::new()
+            .alpha(true)
+            .flip_h(false)
+            .flip_v(true)
+            .hide(false)
+            .build();
+        assert_eq!(inheritance[0], Inheritance::default());
+        assert_eq!(inheritance[1], rates);
+        assert_eq!(inheritance[2], rates); // 親と同じ設定
+        assert_eq!(inheritance[3], Inheritance::default());
+        assert!(inheritance[3].alpha() == false);
+    }
+
+    #[test]
+    fn unknown_part_name() {
+        let text = PACK.replace(
+            "<partName>hidden</partName>",
+            "<partName>missing</partName>",
+        );
+        match parse_pack::<TestFile>(&element(&text), &context()) {
+            Err(Error::UnknownPart(name)) => assert_eq!(name, "missing"),
+            other => panic!("unexpected result: {:?}", other.map(|(key, _)| key)),
+        }
+    }
+}
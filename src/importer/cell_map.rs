@@ -0,0 +1,297 @@
+// セルマップファイル(.ssce)
+use super::{xml, Error, Result};
+use crate::{
+    resource::{
+        font::{BitmapFont, BitmapFontBuilder},
+        mesh::{Mesh, MeshBuilder},
+    },
+    types::cell::CellBuilder,
+};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+struct CellInfo {
+    name: String,
+    pos: (u32, u32),
+    size: (u32, u32),
+    pivot: (f32, f32), // セル中心を 0.0 とした -0.5 ~ 0.5 の値
+    mesh: Option<CellMesh>,
+}
+
+// メッシュ化されたセルの頂点(セル左上からのピクセル座標)と三角形
+struct CellMesh {
+    points: Vec<(f32, f32)>,
+    triangles: Vec<(u32, u32, u32)>,
+}
+
+pub(crate) struct CellMap {
+    file_name: String,
+    image_path: PathBuf,
+    pixel_size: (u32, u32),
+    cells: Vec<CellInfo>,
+}
+
+impl CellMap {
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        let cell_map = Self::parse(&xml::load(path)?, path)?;
+        log::info!("load cell map: {:?}", path);
+        Ok(cell_map)
+    }
+
+    // 読み込み済みの <SpriteStudioCellMap> 要素を変換する
+    // 画像のパスはセルマップファイルのディレクトリを基準にする
+    fn parse(root: &xmltree::Element, path: &Path) -> Result<Self> {
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let image_path = dir.join(xml::text(xml::require(root, "imagePath")?));
+        let pixel_size = xml::child_text(root, "pixelSize")
+            .and_then(|size| xml::parse_pair(&size))
+            .map(|(w, h)| (w as u32, h as u32))
+            .ok_or_else(|| Error::MissingElement("pixelSize".into(), file_name.clone()))?;
+
+        let cells = xml::require(root, "cells")
+            .map(|cells| xml::children(cells, "cell"))?
+            .map(|cell| {
+                let pair = |name: &str| {
+                    xml::child_text(cell, name)
+                        .and_then(|v| xml::parse_pair(&v))
+                        .unwrap_or((0., 0.))
+                };
+                let (x, y) = pair("pos");
+                let (width, height) = pair("size");
+                let mesh = if xml::child_bool(cell, "ismesh").unwrap_or(false) {
+                    Some(load_mesh(cell))
+                } else {
+                    None
+                };
+                CellInfo {
+                    name: xml::child_text(cell, "name").unwrap_or_default(),
+                    pos: (x as u32, y as u32),
+                    size: (width as u32, height as u32),
+                    pivot: pair("pivot"),
+                    mesh,
+                }
+            })
+            .collect();
+
+        Ok(CellMap {
+            file_name,
+            image_path,
+            pixel_size,
+            cells,
+        })
+    }
+
+    // アニメーションパックからはファイル名で参照される
+    pub(crate) fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    pub(crate) fn image_path(&self) -> &Path {
+        &self.image_path
+    }
+
+    // セル名からスプライト番号を取得
+    pub(crate) fn cell_index(&self, name: &str) -> Result<usize> {
+        self.cells
+            .iter()
+            .position(|cell| cell.name == name)
+            .ok_or_else(|| Error::UnknownCell(format!("{}: {}", self.file_name, name)))
+    }
+
+    // メッシュ化されたセルの形状をセルの原点基準に変換
+    // map_id はプロジェクト上のセルマップ番号
+    pub(crate) fn to_meshes(&self, map_id: usize) -> Vec<Mesh> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(|(cell_id, cell)| {
+                let mesh = cell.mesh.as_ref()?;
+                let (width, height) = (cell.size.0 as f32, cell.size.1 as f32);
+                // スプライトシートのオフセットと同じ原点にする
+                let origin = ((0.5 + cell.pivot.0) * width, (0.5 - cell.pivot.1) * height);
+                let builder = mesh.points.iter().fold(
+                    MeshBuilder::new(CellBuilder::new(map_id, cell_id).build()),
+                    |builder, &(x, y)| {
+                        builder.add_vertex((x - origin.0, origin.1 - y), (x / width, y / height))
+                    },
+                );
+                let builder = mesh
+                    .triangles
+                    .iter()
+                    .fold(builder, |builder, &triangle| builder.add_triangle(triangle));
+                Some(builder.build())
+            })
+            .collect()
+    }
+
+    // テキストパーツから参照されるセルマップをビットマップフォントに変換する
+    // セル名の一文字がそのセルの文字になる
+    pub(crate) fn to_font(&self, map_id: usize) -> BitmapFont {
+        let font = self.cells.iter().enumerate().fold(
+            BitmapFontBuilder::new(map_id),
+            |font, (cell_id, cell)| {
+                let mut chars = cell.name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(character), None) => {
+                        let size = (cell.size.0 as f32, cell.size.1 as f32);
+                        font.add_glyph(character, cell_id, size)
+                    }
+                    _ => {
+                        log::warn!("not a glyph cell: {} ({})", cell.name, self.file_name);
+                        font
+                    }
+                }
+            },
+        );
+        font.build()
+    }
+
+    // amethyst の SpriteSheetFormat で読める RON 文字列に変換
+    pub(crate) fn to_sprite_sheet(&self) -> Result<String> {
+        let sprites = self
+            .cells
+            .iter()
+            .map(|cell| {
+                let (width, height) = cell.size;
+                // SpriteStudio のピボットは上方向が負なので y は反転させる
+                let offsets = [cell.pivot.0 * width as f32, -cell.pivot.1 * height as f32];
+                SheetSprite {
+                    x: cell.pos.0,
+                    y: cell.pos.1,
+                    width,
+                    height,
+                    offsets: Some(offsets),
+                }
+            })
+            .collect();
+
+        let sheet = SheetFormat::List(SheetList {
+            texture_width: self.pixel_size.0,
+            texture_height: self.pixel_size.1,
+            sprites,
+        });
+
+        ron::ser::to_string_pretty(&sheet, Default::default())
+            .map_err(|e| Error::Serialize(e.to_string()))
+    }
+}
+
+fn load_mesh(cell: &xmltree::Element) -> CellMesh {
+    let points = xml::values(cell, "meshPointList")
+        .filter_map(|point| xml::parse_pair(&xml::text(point)))
+        .collect();
+    let triangles = xml::values(cell, "meshTriList")
+        .filter_map(
+            |triangle| match xml::parse_floats(&xml::text(triangle)).as_slice() {
+                &[a, b, c, ..] => Some((a as u32, b as u32, c as u32)),
+                _ => None,
+            },
+        )
+        .collect();
+    CellMesh { points, triangles }
+}
+
+// amethyst::renderer::sprite::Sprites と同じ形式で出力するための定義
+#[derive(Serialize)]
+enum SheetFormat {
+    List(SheetList),
+}
+
+#[derive(Serialize)]
+struct SheetList {
+    texture_width: u32,
+    texture_height: u32,
+    sprites: Vec<SheetSprite>,
+}
+
+#[derive(Serialize)]
+struct SheetSprite {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    offsets: Option<[f32; 2]>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CELL_MAP: &str = r#"
+        <SpriteStudioCellMap version="2.00.00">
+            <name>font</name>
+            <imagePath>font.png</imagePath>
+            <pixelSize>256 128</pixelSize>
+            <cells>
+                <cell>
+                    <name>A</name>
+                    <pos>0 0</pos>
+                    <size>32 16</size>
+                    <pivot>0 0</pivot>
+                </cell>
+                <cell>
+                    <name>mesh</name>
+                    <pos>32 0</pos>
+                    <size>32 16</size>
+                    <pivot>0 0</pivot>
+                    <ismesh>1</ismesh>
+                    <meshPointList>
+                        <value>0 0</value>
+                        <value>32 0</value>
+                        <value>0 16</value>
+                    </meshPointList>
+                    <meshTriList>
+                        <value>0 1 2</value>
+                    </meshTriList>
+                </cell>
+            </cells>
+        </SpriteStudioCellMap>
+    "#;
+
+    fn cell_map() -> CellMap {
+        let root = xmltree::Element::parse(CELL_MAP.as_bytes()).unwrap();
+        CellMap::parse(&root, Path::new("cells/font.ssce")).unwrap()
+    }
+
+    #[test]
+    fn cells() {
+        let cell_map = cell_map();
+        assert_eq!(cell_map.file_name(), "font.ssce");
+        assert_eq!(cell_map.image_path(), Path::new("cells/font.png"));
+        assert_eq!(cell_map.pixel_size, (256, 128));
+        assert_eq!(cell_map.cell_index("mesh").unwrap(), 1);
+        assert!(cell_map.cell_index("missing").is_err());
+    }
+
+    // 頂点はセルの原点(中心)基準で上方向が正
+    #[test]
+    fn meshes() {
+        let meshes = cell_map().to_meshes(3);
+        assert_eq!(meshes.len(), 1);
+        let mesh = &meshes[0];
+        assert_eq!((mesh.cell().map_id(), mesh.cell().cell_id()), (3, 1));
+        assert_eq!(
+            mesh.vertices().as_slice(),
+            &[[-16., 8.], [16., 8.], [-16., -8.]]
+        );
+        assert_eq!(
+            mesh.tex_coords().as_slice(),
+            &[[0., 0.], [1., 0.], [0., 1.]]
+        );
+        assert_eq!(mesh.indices().as_slice(), &[0, 1, 2]);
+    }
+
+    // 一文字の名前のセルだけがグリフになる
+    #[test]
+    fn font() {
+        let font = cell_map().to_font(3);
+        assert_eq!(font.map_id(), 3);
+        assert_eq!(font.glyph('A').map(|glyph| glyph.cell_id()), Some(0));
+        assert!(font.glyph('m').is_none());
+    }
+}
@@ -0,0 +1,209 @@
+// エフェクトファイル(.ssee)
+use super::{
+    animation_pack::{parse_alpha_blend, parse_argb, PackContext},
+    xml, Result,
+};
+use crate::resource::effect::{
+    ColorRange, Effect, EffectBuilder, Emitter, EmitterBuilder, ValueRange,
+};
+use std::path::Path;
+use xmltree::Element;
+
+pub(crate) fn load_effect(path: &Path, context: &PackContext) -> Result<Effect> {
+    log::info!("load effect: {:?}", path);
+    let root = xml::load(path)?;
+    let data = xml::require(&root, "effectData")?;
+
+    let mut builder = EffectBuilder::new();
+    if xml::child_bool(data, "isLockRandSeed").unwrap_or(false) {
+        builder = builder.lock_seed(xml::child_parse(data, "lockRandSeed").unwrap_or(0));
+    }
+
+    let nodes = xml::children(xml::require(data, "nodeList")?, "node").collect::<Vec<_>>();
+    let node_type = |index: i32| {
+        nodes
+            .iter()
+            .find(|node| xml::child_parse(node, "arrayIndex") == Some(index))
+            .and_then(|node| xml::child_text(node, "type"))
+    };
+
+    let mut emitters = vec![];
+    for node in nodes.iter() {
+        // SpriteStudio 上の綴りは Emmiter
+        match xml::child_text(node, "type").as_ref().map(|s| s.as_str()) {
+            Some("Emmiter") | Some("Emitter") => {}
+            _ => continue,
+        }
+        if xml::child_bool(node, "visible") == Some(false) {
+            continue;
+        }
+        let name = xml::child_text(node, "name").unwrap_or_default();
+
+        // パーティクルにぶら下がったエミッターは未対応
+        let parent = xml::child_parse(node, "parentIndex").unwrap_or(-1);
+        if node_type(parent).as_ref().map(|s| s.as_str()) == Some("Particle") {
+            log::warn!("nested emitter is not supported: {:?}: {}", path, name);
+            continue;
+        }
+
+        let behavior = match node.get_child("behavior") {
+            Some(behavior) => behavior,
+            None => continue,
+        };
+        emitters.push(load_emitter(behavior, context, &name)?);
+    }
+
+    // 優先度の低いものから描画する
+    emitters.sort_by_key(|(priority, _)| *priority);
+    for (_, emitter) in emitters {
+        builder = builder.add_emitter(emitter);
+    }
+    Ok(builder.build())
+}
+
+fn load_emitter(behavior: &Element, context: &PackContext, name: &str) -> Result<(i32, Emitter)> {
+    let mut builder = EmitterBuilder::new();
+    let mut priority = 0;
+
+    if let (Some(map_name), Some(cell_name)) = (
+        xml::child_text(behavior, "CellMapName"),
+        xml::child_text(behavior, "CellName"),
+    ) {
+        builder = builder.cell(context.cell(&map_name, &cell_name)?);
+    }
+    if let Some(blend) = xml::child_text(behavior, "BlendType") {
+        builder = builder.alpha_blend(parse_alpha_blend(&blend));
+    }
+
+    for value in xml::values(behavior, "list") {
+        let behavior_name = value
+            .attributes
+            .get("name")
+            .map(|s| s.as_str())
+            .unwrap_or_default();
+        let range = |name: &str| range(value, name);
+        let scalar = |name: &str| scalar(value, name);
+        match behavior_name {
+            "Basic" => {
+                priority = scalar("priority").unwrap_or(0.) as i32;
+                if let Some(max) = scalar("maximumParticle") {
+                    builder = builder.max_particles(max as usize);
+                }
+                if let Some(count) = scalar("attimeCreate") {
+                    builder = builder.create_count(count as usize);
+                }
+                if let Some(interval) = scalar("interval") {
+                    builder = builder.interval(interval as usize);
+                }
+                if let Some(life_time) = scalar("lifetime") {
+                    builder = builder.life_time(life_time as usize);
+                }
+                if let Some(speed) = range("speed") {
+                    builder = builder.speed(speed);
+                }
+                if let Some(life) = range("lifespan") {
+                    builder = builder.particle_life(life);
+                }
+                builder = builder.angle(
+                    scalar("angle").unwrap_or(0.),
+                    scalar("angleVariance").unwrap_or(0.),
+                );
+            }
+            "OverWriteSeed" => {
+                builder = builder.seed_offset(scalar("Seed").unwrap_or(0.) as u32);
+            }
+            "Delay" => {
+                builder = builder.delay(scalar("DelayTime").unwrap_or(0.) as usize);
+            }
+            "Gravity" => {
+                if let Some((x, y)) = attribute(value, "Gravity").and_then(|v| xml::parse_pair(&v))
+                {
+                    builder = builder.gravity(x, y);
+                }
+            }
+            "init_position" => {
+                builder = builder.offset(
+                    range("OffsetX").unwrap_or(0.0.into()),
+                    range("OffsetY").unwrap_or(0.0.into()),
+                );
+            }
+            "init_rotation" => {
+                builder = builder.rotation(
+                    range("Rotation").unwrap_or(0.0.into()),
+                    range("RotationAdd").unwrap_or(0.0.into()),
+                );
+            }
+            "init_size" => {
+                let (x, y) = size(value);
+                builder = builder.start_scale(x, y);
+            }
+            "trans_size" => {
+                let (x, y) = size(value);
+                builder = builder.end_scale(x, y);
+            }
+            "init_vertexcolor" => {
+                if let Some(color) = color(value, "Color") {
+                    builder = builder.start_color(color);
+                }
+            }
+            "trans_vertexcolor" => {
+                if let Some(color) = color(value, "Color") {
+                    builder = builder.end_color(color);
+                }
+            }
+            "alpha_fade" => {
+                if let Some(ValueRange(fade_in, fade_out)) = range("disprange") {
+                    builder = builder.alpha_fade(fade_in / 100., fade_out / 100.);
+                }
+            }
+            "InfiniteEmit" => builder = builder.infinite(true),
+            _ => log::warn!("unsupported effect behavior: {}: {}", name, behavior_name),
+        }
+    }
+    Ok((priority, builder.build()))
+}
+
+// <name value=".." subvalue=".."/> 形式の属性
+fn attribute(element: &Element, name: &str) -> Option<String> {
+    element.get_child(name)?.attributes.get("value").cloned()
+}
+
+// 属性がなければ要素のテキストを値とする
+fn scalar(element: &Element, name: &str) -> Option<f32> {
+    attribute(element, name)
+        .or_else(|| xml::child_text(element, name))?
+        .parse()
+        .ok()
+}
+
+fn range(element: &Element, name: &str) -> Option<ValueRange> {
+    let child = element.get_child(name)?;
+    let value = child.attributes.get("value")?.parse().ok()?;
+    let sub_value = child
+        .attributes
+        .get("subvalue")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(value);
+    Some(ValueRange(value, sub_value))
+}
+
+fn color(element: &Element, name: &str) -> Option<ColorRange> {
+    let child = element.get_child(name)?;
+    let value = parse_argb(child.attributes.get("value")?);
+    let sub_value = child
+        .attributes
+        .get("subvalue")
+        .map(|v| parse_argb(v))
+        .unwrap_or(value);
+    Some(ColorRange(value, sub_value))
+}
+
+// セルサイズに対する倍率と全体の倍率を掛け合わせる
+fn size(element: &Element) -> (ValueRange, ValueRange) {
+    let ValueRange(min_factor, max_factor) = range(element, "ScaleFactor").unwrap_or(1.0.into());
+    let scaled = |name: &str| {
+        let ValueRange(min, max) = range(element, name).unwrap_or(1.0.into());
+        ValueRange(min * min_factor, max * max_factor)
+    };
+    (scaled("SizeX"), scaled("SizeY"))
+}
@@ -0,0 +1,64 @@
+// プロジェクトファイル(.sspj)
+use super::{xml, Result};
+use std::path::{Path, PathBuf};
+
+pub(crate) struct Project {
+    name: String,
+    cell_map_paths: Vec<PathBuf>,
+    animation_pack_paths: Vec<PathBuf>,
+    effect_paths: Vec<PathBuf>,
+}
+
+impl Project {
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        let root = xml::load(path)?;
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+        // 各ファイルの基準ディレクトリは設定されていればそちらを使う
+        let settings = root.get_child("settings");
+        let base_dir = |name: &str| {
+            settings
+                .and_then(|settings| xml::child_text(settings, name))
+                .map(|base| dir.join(base))
+                .unwrap_or_else(|| dir.to_path_buf())
+        };
+        let cell_map_dir = base_dir("cellMapBaseDirectory");
+        let animation_dir = base_dir("animeBaseDirectory");
+        let effect_dir = base_dir("effectBaseDirectory");
+
+        let name = xml::child_text(&root, "name").unwrap_or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+
+        let list = |list_name: &str, dir: &Path| {
+            xml::values(&root, list_name)
+                .map(|value| dir.join(xml::text(value)))
+                .collect::<Vec<_>>()
+        };
+
+        Ok(Project {
+            name,
+            cell_map_paths: list("cellmapNames", &cell_map_dir),
+            animation_pack_paths: list("animepackNames", &animation_dir),
+            effect_paths: list("effectFileNames", &effect_dir),
+        })
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn cell_map_paths(&self) -> &[PathBuf] {
+        &self.cell_map_paths
+    }
+
+    pub(crate) fn animation_pack_paths(&self) -> &[PathBuf] {
+        &self.animation_pack_paths
+    }
+
+    pub(crate) fn effect_paths(&self) -> &[PathBuf] {
+        &self.effect_paths
+    }
+}
@@ -0,0 +1,15 @@
+// SpriteStudio のユーザーデータ
+// 整数，点，矩形，文字列のうち設定されているものだけ値が入る
+#[derive(Debug, Default, Clone)]
+pub struct UserValue {
+    pub integer: Option<i32>,
+    pub point: Option<(f32, f32)>,
+    pub rect: Option<(f32, f32, f32, f32)>,
+    pub string: Option<String>,
+}
+
+// ユーザーデータをゲーム側の型に変換する
+// None を返した場合はキーを打たない
+pub trait FromUserValue: Sized {
+    fn from_user_value(value: &UserValue) -> Option<Self>;
+}
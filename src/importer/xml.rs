@@ -0,0 +1,73 @@
+// xmltree の要素アクセス補助
+use super::{Error, Result};
+use std::{fs::File, io::BufReader, path::Path, str::FromStr};
+use xmltree::{Element, XMLNode};
+
+pub(crate) fn load(path: &Path) -> Result<Element> {
+    let file = File::open(path).map_err(|e| Error::Io(path.display().to_string(), e))?;
+    Element::parse(BufReader::new(file))
+        .map_err(|e| Error::Xml(path.display().to_string(), e.to_string()))
+}
+
+// 指定した名前の子要素を全て列挙
+pub(crate) fn children<'a>(
+    element: &'a Element,
+    name: &'a str,
+) -> impl Iterator<Item = &'a Element> {
+    element.children.iter().filter_map(move |node| match node {
+        XMLNode::Element(child) if child.name == name => Some(child),
+        _ => None,
+    })
+}
+
+// <name><value>..</value><value>..</value></name> 形式のリストを列挙
+pub(crate) fn values<'a>(element: &'a Element, name: &'a str) -> impl Iterator<Item = &'a Element> {
+    element
+        .get_child(name)
+        .into_iter()
+        .flat_map(|list| children(list, "value"))
+}
+
+pub(crate) fn require<'a>(element: &'a Element, name: &str) -> Result<&'a Element> {
+    element
+        .get_child(name)
+        .ok_or_else(|| Error::MissingElement(name.into(), element.name.clone()))
+}
+
+pub(crate) fn text(element: &Element) -> String {
+    element
+        .get_text()
+        .map(|text| text.trim().to_string())
+        .unwrap_or_default()
+}
+
+pub(crate) fn child_text(element: &Element, name: &str) -> Option<String> {
+    element.get_child(name).map(text)
+}
+
+pub(crate) fn child_parse<F: FromStr>(element: &Element, name: &str) -> Option<F> {
+    child_text(element, name)?.parse().ok()
+}
+
+// SpriteStudio の真偽値は 0/1 で保存されている
+pub(crate) fn parse_bool(text: &str) -> bool {
+    text == "1" || text == "true"
+}
+
+pub(crate) fn child_bool(element: &Element, name: &str) -> Option<bool> {
+    child_text(element, name).map(|text| parse_bool(&text))
+}
+
+// 空白区切りの数値列
+pub(crate) fn parse_floats(text: &str) -> Vec<f32> {
+    text.split_whitespace()
+        .filter_map(|v| v.parse().ok())
+        .collect()
+}
+
+pub(crate) fn parse_pair(text: &str) -> Option<(f32, f32)> {
+    match parse_floats(text).as_slice() {
+        &[x, y, ..] => Some((x, y)),
+        _ => None,
+    }
+}
@@ -1,12 +1,16 @@
 pub mod bundle;
 pub mod components;
 pub mod constant;
+#[cfg(feature = "builder")]
+pub mod importer;
 pub mod load;
 pub mod renderer;
 pub mod resource;
 pub(crate) mod shaders;
 pub mod splash;
 pub mod system;
+#[cfg(test)]
+mod test_util;
 pub mod traits;
 pub mod types;
 
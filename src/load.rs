@@ -1,12 +1,13 @@
 use crate::{
-    resource::{data, AnimationStore},
+    resource::{data, state_machine::StateMachine, AnimationStore},
     traits::translate_animation::TranslateAnimation,
 };
 use amethyst::{
-    assets::{AssetStorage, Loader, ProgressCounter, RonFormat},
+    assets::{AssetStorage, Handle, Loader, ProgressCounter, RonFormat},
     ecs::{Read, ReadExpect, World, Write},
     renderer::{
-        formats::texture::ImageFormat, sprite::SpriteSheet, sprite::SpriteSheetFormat,
+        formats::texture::ImageFormat,
+        sprite::{SpriteSheet, SpriteSheetFormat, SpriteSheetHandle},
         types::Texture,
     },
 };
@@ -49,45 +50,92 @@ impl AnimationLoad for &mut World {
         F: Into<String>,
         T: TranslateAnimation<'s>,
     {
-        self.exec(
-            |(mut store, loader, tex_storage, sprite_storage): (
-                Write<AnimationStore<T>>,
-                ReadExpect<Loader>,
-                Read<AssetStorage<Texture>>,
-                Read<AssetStorage<SpriteSheet>>,
-            )| {
-                let dir_path = dir_path.into();
-                let mut sheets = vec![];
-                for i in 0..sprite_sheet_num {
-                    let sprite_path =
-                        format!("sprite_studio/{}/image/sprite{:03}.png", dir_path, i);
-                    let sheet_path =
-                        format!("sprite_studio/{}/sheet/sprite{:03}.sheet.ron", dir_path, i);
-
-                    log::info!("load sprite: {:?}", sprite_path);
-                    log::info!("load sheet: {:?}", sheet_path);
+        let sheets = load_sprite_sheets(self, dir_path.into(), sprite_sheet_num, progress);
+        self.exec(|mut store: Write<AnimationStore<T>>| {
+            store.sprite_sheets.insert(id, sheets);
+        });
+    }
 
-                    let texture = loader.load(
-                        sprite_path,
-                        ImageFormat::default(),
-                        &mut *progress,
-                        &tex_storage,
-                    );
-                    let sheet = loader.load(
-                        sheet_path,
-                        SpriteSheetFormat(texture),
-                        &mut *progress,
-                        &sprite_storage,
-                    );
-                    sheets.push(sheet);
-                }
+    // 差し替え用のスプライトシートのセットをロード
+    fn load_sprite_set_with_path<'s, S, F, T>(
+        &mut self,
+        id: T::FileId,
+        set_name: S,
+        dir_path: F,
+        sprite_sheet_num: usize,
+        progress: &mut ProgressCounter,
+    ) where
+        S: Into<String>,
+        F: Into<String>,
+        T: TranslateAnimation<'s>,
+    {
+        let sheets = load_sprite_sheets(self, dir_path.into(), sprite_sheet_num, progress);
+        self.exec(|mut store: Write<AnimationStore<T>>| {
+            store.insert_sprite_sheet_set(id, set_name, sheets);
+        });
+    }
 
-                store.sprite_sheets.insert(id, sheets);
+    // ステートマシンをロード
+    fn load_state_machine<'s, F, T>(
+        &mut self,
+        name: F,
+        progress: &mut ProgressCounter,
+    ) -> Handle<StateMachine<T>>
+    where
+        F: Into<String>,
+        T: TranslateAnimation<'s>,
+    {
+        self.exec(
+            |(loader, storage): (ReadExpect<Loader>, Read<AssetStorage<StateMachine<T>>>)| {
+                let path = format!("sprite_studio/state_machine/{}.state.ron", name.into());
+                log::info!("load state machine: {:?}", path);
+                loader.load(path, RonFormat, progress, &storage)
             },
-        );
+        )
     }
 }
 
+// ディレクトリ内のスプライトシートをセルマップ番号順にロード
+fn load_sprite_sheets(
+    world: &mut World,
+    dir_path: String,
+    sprite_sheet_num: usize,
+    progress: &mut ProgressCounter,
+) -> Vec<SpriteSheetHandle> {
+    world.exec(
+        |(loader, tex_storage, sprite_storage): (
+            ReadExpect<Loader>,
+            Read<AssetStorage<Texture>>,
+            Read<AssetStorage<SpriteSheet>>,
+        )| {
+            let mut sheets = vec![];
+            for i in 0..sprite_sheet_num {
+                let sprite_path = format!("sprite_studio/{}/image/sprite{:03}.png", dir_path, i);
+                let sheet_path =
+                    format!("sprite_studio/{}/sheet/sprite{:03}.sheet.ron", dir_path, i);
+
+                log::info!("load sprite: {:?}", sprite_path);
+                log::info!("load sheet: {:?}", sheet_path);
+
+                let texture = loader.load(
+                    sprite_path,
+                    ImageFormat::default(),
+                    &mut *progress,
+                    &tex_storage,
+                );
+                let sheet = loader.load(
+                    sheet_path,
+                    SpriteSheetFormat(texture),
+                    &mut *progress,
+                    &sprite_storage,
+                );
+                sheets.push(sheet);
+            }
+            sheets
+        },
+    )
+}
+
 pub trait AnimationLoad {
     // パス名を指定してロード
     fn load_animation_with_path<'s, F, T>(
@@ -110,6 +158,31 @@ pub trait AnimationLoad {
         F: Into<String>,
         T: TranslateAnimation<'s>;
 
+    // 差し替え用のスプライトシートのセットをロード
+    // CellOverride::set_sprite_sheet_set で指定したセット名で使う
+    fn load_sprite_set_with_path<'s, S, F, T>(
+        &mut self,
+        id: T::FileId,
+        set_name: S,
+        dir_path: F,
+        sprite_sheet_num: usize,
+        progress: &mut ProgressCounter,
+    ) where
+        S: Into<String>,
+        F: Into<String>,
+        T: TranslateAnimation<'s>;
+
+    // ステートマシンをロード
+    // AnimationStateMachine::new に渡して使う
+    fn load_state_machine<'s, F, T>(
+        &mut self,
+        name: F, // sprite_studio/state_machine/ 以下のファイル名(拡張子 .state.ron は除く)
+        progress: &mut ProgressCounter,
+    ) -> Handle<StateMachine<T>>
+    where
+        F: Into<String>,
+        T: TranslateAnimation<'s>;
+
     fn load_animation<'s, T>(&mut self, id: T::FileId, progress: &mut ProgressCounter)
     where
         T: TranslateAnimation<'s>,
@@ -1,18 +1,22 @@
+mod mesh_args;
+mod part_batch;
 mod sprite_args;
 
+use mesh_args::MeshArgs;
+use part_batch::{DrawRange, PartArgs, PartBatch};
 use sprite_args::SpriteArgs;
 
 use crate::{
-    components::{AnimationNodes, BuildRequireData, Node},
+    components::{AnimationNodes, MeshNode, Node},
     traits::translate_animation::TranslateAnimation,
+    types::AlphaBlend,
 };
 use amethyst::{
     assets::{AssetStorage, Handle},
-    core::math::{Matrix4, Vector4},
-    ecs::{Join, Read, SystemData, World},
+    core::math::{Matrix4, Vector2, Vector4},
+    ecs::{Join, Read, ReadStorage, SystemData, World},
     error::Error,
     renderer::{
-        batch::{GroupIterator, OneLevelBatch},
         bundle::{RenderOrder, RenderPlan, RenderPlugin, Target},
         pipeline::{PipelineDescBuilder, PipelinesBuilder},
         pod::IntoPod,
@@ -23,22 +27,35 @@ use amethyst::{
                 render::{PrepareResult, RenderGroup, RenderGroupDesc},
                 GraphContext, NodeBuffer, NodeImage,
             },
-            hal::{self, device::Device, pass::Subpass, pso},
+            hal::{
+                self,
+                device::Device,
+                format::{Aspects, Format},
+                pass::Subpass,
+                pso,
+            },
             mesh::AsVertex,
             shader::Shader,
         },
-        sprite::SpriteSheet,
-        submodules::{DynamicVertexBuffer, FlatEnvironmentSub, TextureId, TextureSub},
+        sprite::{Sprite, SpriteSheet, TextureCoordinates},
+        submodules::{
+            DynamicIndexBuffer, DynamicVertexBuffer, FlatEnvironmentSub, TextureId, TextureSub,
+        },
         types::{Backend, Texture},
         util::simple_shader_set,
     },
 };
-use std::marker::PhantomData;
+use std::{cmp::Ordering, marker::PhantomData, sync::Arc};
 
+// マスクパーツはステンシルバッファを使うので，
+// 描画先の深度バッファはステンシル付きのフォーマット(D24UnormS8Uint など)にして
+// with_depth_stencil_format で指定すること
+// 指定がなければマスクは無効になり，マスクパーツを描画しようとした時にエラーを出す
 #[derive(Debug)]
 pub struct RenderSpriteAnimation<T> {
     _translation: PhantomData<T>,
     target: Target,
+    depth_format: Option<Format>,
 }
 
 impl<T> Default for RenderSpriteAnimation<T> {
@@ -46,6 +63,7 @@ impl<T> Default for RenderSpriteAnimation<T> {
         RenderSpriteAnimation {
             _translation: PhantomData,
             target: Default::default(),
+            depth_format: None,
         }
     }
 }
@@ -56,6 +74,12 @@ impl<T> RenderSpriteAnimation<T> {
         self.target = target;
         self
     }
+
+    /// Set depth format of the target. Mask parts need a format with stencil.
+    pub fn with_depth_stencil_format(mut self, format: Format) -> Self {
+        self.depth_format = Some(format);
+        self
+    }
 }
 
 impl<'s, B, T> RenderPlugin<B> for RenderSpriteAnimation<T>
@@ -69,10 +93,21 @@ where
         _factory: &mut Factory<B>,
         _world: &World,
     ) -> Result<(), Error> {
-        plan.extend_target(self.target, |ctx| {
+        // ステンシルのないフォーマットが指定された場合はマスクを描画できないので失敗させる
+        let stencil = match self.depth_format {
+            Some(format) if format.surface_desc().aspects.contains(Aspects::STENCIL) => true,
+            Some(format) => {
+                return Err(Error::from_string(format!(
+                    "depth format of sprite animation target has no stencil: {:?}",
+                    format
+                )));
+            }
+            None => false,
+        };
+        plan.extend_target(self.target, move |ctx| {
             ctx.add(
                 RenderOrder::Transparent,
-                DrawSpriteAnimationDesc::<T>::new().builder(),
+                DrawSpriteAnimationDesc::<T>::new(stencil).builder(),
             )?;
             Ok(())
         });
@@ -83,12 +118,14 @@ where
 #[derive(Debug)]
 pub struct DrawSpriteAnimationDesc<T> {
     _translation: PhantomData<T>,
+    stencil: bool, // 描画先の深度バッファにステンシルがあるか
 }
 
 impl<T> DrawSpriteAnimationDesc<T> {
-    fn new() -> Self {
+    fn new(stencil: bool) -> Self {
         DrawSpriteAnimationDesc {
             _translation: PhantomData,
+            stencil,
         }
     }
 }
@@ -116,36 +153,85 @@ where
         let env = FlatEnvironmentSub::new(factory)?;
         let textures = TextureSub::new(factory)?;
         let vertex = DynamicVertexBuffer::new();
+        let mesh_vertex = DynamicVertexBuffer::new();
+        let mesh_index = DynamicIndexBuffer::new();
 
-        let (pipeline, pipeline_layout) = build_sprite_pipeline(
-            factory,
-            subpass,
-            framebuffer_width,
-            framebuffer_height,
-            true,
-            vec![env.raw_layout(), textures.raw_layout()],
-        )?;
+        let pipeline_layout = unsafe {
+            factory.device().create_pipeline_layout(
+                vec![env.raw_layout(), textures.raw_layout()],
+                None as Option<(_, _)>,
+            )
+        }?;
+
+        // パイプラインは使われた組み合わせだけ描画前に作る
+        let mut pipelines = Vec::new();
+        pipelines.resize_with(PIPELINE_COUNT, || None);
 
         Ok(Box::new(DrawSpriteAnimation::<B, T> {
-            pipeline,
+            pipelines,
             pipeline_layout,
+            framebuffer_size: (framebuffer_width, framebuffer_height),
             env,
             textures,
             vertex,
-            sprites: Default::default(),
+            mesh_vertex,
+            mesh_index,
+            batch: Default::default(),
+            stencil: self.stencil,
+            mask_reported: false,
             _translation: PhantomData,
         }))
     }
 }
 
+// パーツの描画方法
+// マスクを正しくかけるために描画順を保ったまま切り替える
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SpriteDraw {
+    Normal = 0, // ステンシルを使わない
+    Masked,     // ステンシルにマスクが書き込まれている範囲にのみ描画
+    MaskAdd,    // マスクをステンシルに加算(色は書き込まない)
+    MaskRemove, // マスクをステンシルから減算(色は書き込まない)
+}
+
+impl SpriteDraw {
+    const ALL: [SpriteDraw; 4] = [
+        SpriteDraw::Normal,
+        SpriteDraw::Masked,
+        SpriteDraw::MaskAdd,
+        SpriteDraw::MaskRemove,
+    ];
+
+    fn is_mask(self) -> bool {
+        self == SpriteDraw::MaskAdd || self == SpriteDraw::MaskRemove
+    }
+
+    // パイプラインは描画方法ごとに合成方法の数だけ並び，後半はメッシュ用
+    fn pipeline_index(self, blend: AlphaBlend, mesh: bool) -> usize {
+        let index = self as usize * AlphaBlend::ALL.len() + blend as usize;
+        if mesh {
+            index + SpriteDraw::ALL.len() * AlphaBlend::ALL.len()
+        } else {
+            index
+        }
+    }
+}
+
+const PIPELINE_COUNT: usize = SpriteDraw::ALL.len() * AlphaBlend::ALL.len() * 2;
+
 #[derive(Debug)]
 pub struct DrawSpriteAnimation<B: Backend, T> {
-    pipeline: B::GraphicsPipeline,
+    pipelines: Vec<Option<B::GraphicsPipeline>>, // SpriteDraw::pipeline_index の順に並ぶ
     pipeline_layout: B::PipelineLayout,
+    framebuffer_size: (u32, u32),
     env: FlatEnvironmentSub<B>,
     textures: TextureSub<B>,
     vertex: DynamicVertexBuffer<B, SpriteArgs>,
-    sprites: OneLevelBatch<TextureId, SpriteArgs>,
+    mesh_vertex: DynamicVertexBuffer<B, MeshArgs>,
+    mesh_index: DynamicIndexBuffer<B, u32>,
+    batch: PartBatch,
+    stencil: bool,
+    mask_reported: bool, // ステンシルがないのにマスクを描画しようとしたことを報告済みか
     _translation: PhantomData<T>,
 }
 
@@ -159,47 +245,33 @@ where
         factory: &Factory<B>,
         _queue: QueueId,
         index: usize,
-        _subpass: Subpass<B>,
+        subpass: Subpass<B>,
         world: &World,
     ) -> PrepareResult {
-        let (sprite_sheet_storage, tex_storage, (time, key, transforms, tint, storage, store)) =
-            <(
-                Read<AssetStorage<SpriteSheet>>,
-                Read<AssetStorage<Texture>>,
-                BuildRequireData<T>,
-            )>::fetch(world);
+        let (sprite_sheet_storage, tex_storage, animation_nodes) = <(
+            Read<AssetStorage<SpriteSheet>>,
+            Read<AssetStorage<Texture>>,
+            ReadStorage<AnimationNodes<T::UserData>>,
+        )>::fetch(world);
 
         self.env.process(factory, index, world);
 
-        let sprites_ref = &mut self.sprites;
+        let batch_ref = &mut self.batch;
         let textures_ref = &mut self.textures;
 
-        sprites_ref.clear_inner();
-
-        let mut nodes = (&time, &key, &transforms, tint.maybe())
-            .join()
-            .filter_map(|(time, key, transform, tint)| {
-                AnimationNodes::<T::UserData>::make_node::<T>(
-                    time,
-                    tint,
-                    key.play_key(),
-                    transform,
-                    transform.global_matrix(),
-                    &store,
-                    &storage,
-                )
-            })
-            .collect::<Vec<_>>();
+        batch_ref.clear();
+
+        let mut nodes = (&animation_nodes).join().collect::<Vec<_>>();
 
         nodes.sort_by(|nodes1, nodes2| {
             let z1 = nodes1.nodes().nth(0).unwrap().transform.translation().z;
             let z2 = nodes2.nodes().nth(0).unwrap().transform.translation().z;
-            z1.partial_cmp(&z2).unwrap()
+            z1.partial_cmp(&z2).unwrap_or(Ordering::Equal)
         });
 
         for nodes in nodes {
             build_animation::<B, T>(
-                &nodes,
+                nodes,
                 &sprite_sheet_storage,
                 &tex_storage,
                 &factory,
@@ -207,20 +279,69 @@ where
                 textures_ref,
             )
             .map(|commands| {
-                commands.into_iter().for_each_group(|tex_id, batch_data| {
-                    sprites_ref.insert(tex_id, batch_data.drain(..))
-                })
+                for (draw, blend, tex_id, batch_data) in commands {
+                    batch_ref.insert(draw, blend, tex_id, batch_data);
+                }
             });
         }
 
+        // ステンシルがなければマスクは効かないので一度だけエラーを出す
+        if !self.stencil && !self.mask_reported {
+            let masked = self
+                .batch
+                .commands()
+                .any(|(draw, _, _, _)| *draw != SpriteDraw::Normal);
+            if masked {
+                log::error!(
+                    "mask parts need a depth stencil target: use RenderSpriteAnimation::with_depth_stencil_format"
+                );
+                self.mask_reported = true;
+            }
+        }
+
+        // 初めて使う描画方法と合成方法の組み合わせのパイプラインを作る
+        for (draw, blend, _, range) in self.batch.commands() {
+            let mesh = match range {
+                DrawRange::Sprite(_) => false,
+                DrawRange::Mesh(_) => true,
+            };
+            let pipeline = &mut self.pipelines[draw.pipeline_index(*blend, mesh)];
+            if pipeline.is_some() {
+                continue;
+            }
+            match build_sprite_pipeline(
+                factory,
+                subpass,
+                self.framebuffer_size,
+                &self.pipeline_layout,
+                (*draw, *blend, mesh),
+                true,
+                self.stencil,
+            ) {
+                Ok(built) => *pipeline = Some(built),
+                Err(err) => log::error!("failed to build sprite pipeline: {:?}", err),
+            }
+        }
+
         self.textures.maintain(factory, world);
 
-        sprites_ref.prune();
         self.vertex.write(
             factory,
             index,
-            self.sprites.count() as u64,
-            self.sprites.data(),
+            self.batch.sprites().len() as u64,
+            Some(self.batch.sprites()),
+        );
+        self.mesh_vertex.write(
+            factory,
+            index,
+            self.batch.mesh_vertices().len() as u64,
+            Some(self.batch.mesh_vertices()),
+        );
+        self.mesh_index.write(
+            factory,
+            index,
+            self.batch.mesh_indices().len() as u64,
+            Some(self.batch.mesh_indices()),
         );
 
         PrepareResult::DrawRecord
@@ -234,14 +355,44 @@ where
         _world: &World,
     ) {
         let layout = &self.pipeline_layout;
-        encoder.bind_graphics_pipeline(&self.pipeline);
+        let mut current_pipeline = None;
+        let mut current_mesh = false;
         self.env.bind(index, layout, 0, &mut encoder);
         self.vertex.bind(index, 0, 0, &mut encoder);
-        for (&tex, range) in self.sprites.iter() {
-            if self.textures.loaded(tex) {
-                self.textures.bind(layout, 1, tex, &mut encoder);
-                unsafe {
-                    encoder.draw(0..4, range);
+        self.mesh_index.bind(index, 0, &mut encoder);
+        for (draw, blend, tex, range) in self.batch.commands() {
+            if self.textures.loaded(*tex) == false {
+                continue;
+            }
+            // パイプラインレイアウトは共通なのでバインド済みのデスクリプタはそのまま使える
+            // 頂点バッファはスプライトとメッシュで入れ替える
+            let mesh = match range {
+                DrawRange::Sprite(_) => false,
+                DrawRange::Mesh(_) => true,
+            };
+            // パイプラインが作れなかった組み合わせは描画しない
+            let pipeline_index = draw.pipeline_index(*blend, mesh);
+            let pipeline = match self.pipelines[pipeline_index].as_ref() {
+                Some(pipeline) => pipeline,
+                None => continue,
+            };
+            if current_pipeline != Some(pipeline_index) {
+                encoder.bind_graphics_pipeline(pipeline);
+                current_pipeline = Some(pipeline_index);
+            }
+            if mesh != current_mesh {
+                if mesh {
+                    self.mesh_vertex.bind(index, 0, 0, &mut encoder);
+                } else {
+                    self.vertex.bind(index, 0, 0, &mut encoder);
+                }
+                current_mesh = mesh;
+            }
+            self.textures.bind(layout, 1, *tex, &mut encoder);
+            unsafe {
+                match range {
+                    DrawRange::Sprite(range) => encoder.draw(0..4, range.clone()),
+                    DrawRange::Mesh(range) => encoder.draw_indexed(range.clone(), 0, 0..1),
                 }
             }
         }
@@ -249,7 +400,9 @@ where
 
     fn dispose(self: Box<Self>, factory: &mut Factory<B>, _: &World) {
         unsafe {
-            factory.device().destroy_graphics_pipeline(self.pipeline);
+            for pipeline in self.pipelines.into_iter().flatten() {
+                factory.device().destroy_graphics_pipeline(pipeline);
+            }
             factory
                 .device()
                 .destroy_pipeline_layout(self.pipeline_layout);
@@ -257,46 +410,55 @@ where
     }
 }
 
+// 描画方法(ステンシル)，合成方法(ブレンド)とスプライトかメッシュかの組み合わせのパイプラインを作る
 fn build_sprite_pipeline<B: Backend>(
     factory: &Factory<B>,
     subpass: hal::pass::Subpass<'_, B>,
-    framebuffer_width: u32,
-    framebuffer_height: u32,
+    (framebuffer_width, framebuffer_height): (u32, u32),
+    pipeline_layout: &B::PipelineLayout,
+    (draw, blend, mesh): (SpriteDraw, AlphaBlend, bool),
     transparent: bool,
-    layouts: Vec<&B::DescriptorSetLayout>,
-) -> Result<(B::GraphicsPipeline, B::PipelineLayout), failure::Error> {
-    let pipeline_layout = unsafe {
-        factory
-            .device()
-            .create_pipeline_layout(layouts, None as Option<(_, _)>)
-    }?;
-
+    stencil: bool,
+) -> Result<B::GraphicsPipeline, failure::Error> {
     // AmethystのDrawFlat2Dのシェーダーを流用．
-    let shader_vertex = unsafe { crate::shaders::SPRITE_VERTEX.module(factory).unwrap() };
+    // メッシュは頂点ごとのパラメータを受け取る．フラグメントシェーダーは共通
+    let (vertex_desc, primitive, shader_vertex) = if mesh {
+        (
+            (MeshArgs::vertex(), pso::VertexInputRate::Vertex),
+            hal::Primitive::TriangleList,
+            unsafe { crate::shaders::MESH_VERTEX.module(factory).unwrap() },
+        )
+    } else {
+        (
+            (SpriteArgs::vertex(), pso::VertexInputRate::Instance(1)),
+            hal::Primitive::TriangleStrip,
+            unsafe { crate::shaders::SPRITE_VERTEX.module(factory).unwrap() },
+        )
+    };
     let shader_fragment = unsafe { crate::shaders::SPRITE_FRAGMENT.module(factory).unwrap() };
 
-    // パイプライン生成．
+    // ステンシルがない描画先ではステンシルテストを使わない
     let pipes = PipelinesBuilder::new()
         .with_pipeline(
             PipelineDescBuilder::new()
-                .with_vertex_desc(&[(SpriteArgs::vertex(), pso::VertexInputRate::Instance(1))])
-                .with_input_assembler(pso::InputAssemblerDesc::new(hal::Primitive::TriangleStrip))
+                .with_vertex_desc(&[vertex_desc])
+                .with_input_assembler(pso::InputAssemblerDesc::new(primitive))
                 .with_shaders(simple_shader_set(&shader_vertex, Some(&shader_fragment)))
-                .with_layout(&pipeline_layout)
+                .with_layout(pipeline_layout)
                 .with_subpass(subpass)
                 .with_framebuffer_size(framebuffer_width, framebuffer_height)
-                .with_blend_targets(vec![pso::ColorBlendDesc {
-                    mask: pso::ColorMask::ALL,
-                    blend: if transparent {
-                        pso::BlendState::ALPHA
+                .with_blend_targets(vec![sprite_blend_desc(draw, blend, transparent)])
+                .with_depth_stencil(pso::DepthStencilDesc {
+                    depth: Some(pso::DepthTest {
+                        fun: pso::Comparison::Less,
+                        write: !transparent && !draw.is_mask(),
+                    }),
+                    depth_bounds: false,
+                    stencil: if stencil {
+                        sprite_stencil_test(draw)
                     } else {
-                        pso::BlendState::REPLACE
-                    }
-                    .into(),
-                }])
-                .with_depth_test(pso::DepthTest {
-                    fun: pso::Comparison::Less,
-                    write: !transparent,
+                        None
+                    },
                 }),
         )
         .build(factory, None);
@@ -306,27 +468,206 @@ fn build_sprite_pipeline<B: Backend>(
         factory.destroy_shader_module(shader_fragment);
     }
 
-    match pipes {
-        Err(e) => {
-            unsafe {
-                factory.device().destroy_pipeline_layout(pipeline_layout);
-            }
-            Err(e)
+    Ok(pipes?.remove(0))
+}
+
+// マスクはステンシルにのみ書き込むので色は書き込まない
+fn sprite_blend_desc(
+    draw: SpriteDraw,
+    blend: AlphaBlend,
+    transparent: bool,
+) -> pso::ColorBlendDesc {
+    if draw.is_mask() {
+        return pso::ColorBlendDesc {
+            mask: pso::ColorMask::empty(),
+            blend: None,
+        };
+    }
+    pso::ColorBlendDesc {
+        mask: pso::ColorMask::ALL,
+        blend: if transparent {
+            alpha_blend_state(blend)
+        } else {
+            pso::BlendState::REPLACE
+        }
+        .into(),
+    }
+}
+
+// SpriteStudio の合成方法に合わせたブレンド設定
+// フラグメントシェーダーはアルファ乗算済みの色(色 * アルファ)を出力するので，
+// どの合成方法も描画先との間をアルファで補間した結果になる
+// 描画先のアルファは通常の合成と同じく重ねていく
+fn alpha_blend_state(blend: AlphaBlend) -> pso::BlendState {
+    use pso::{BlendOp, Factor};
+    let color = match blend {
+        // 色 * アルファ + 描画先 * (1 - アルファ)
+        AlphaBlend::Mix => BlendOp::Add {
+            src: Factor::One,
+            dst: Factor::OneMinusSrcAlpha,
+        },
+        // 色 * 描画先 * アルファ + 描画先 * (1 - アルファ)
+        AlphaBlend::Multiply => BlendOp::Add {
+            src: Factor::DstColor,
+            dst: Factor::OneMinusSrcAlpha,
+        },
+        AlphaBlend::Add => BlendOp::Add {
+            src: Factor::One,
+            dst: Factor::One,
+        },
+        AlphaBlend::Subtract => BlendOp::RevSub {
+            src: Factor::One,
+            dst: Factor::One,
+        },
+        // 色 * アルファ + 描画先 - 色 * アルファ * 描画先
+        AlphaBlend::Screen => BlendOp::Add {
+            src: Factor::OneMinusDstColor,
+            dst: Factor::One,
+        },
+        // 色 * アルファ + 描画先 - 2 * 色 * アルファ * 描画先
+        AlphaBlend::Exclusion => BlendOp::Add {
+            src: Factor::OneMinusDstColor,
+            dst: Factor::OneMinusSrcColor,
+        },
+    };
+    pso::BlendState {
+        color,
+        alpha: pso::BlendState::ALPHA.alpha,
+    }
+}
+
+// マスクはステンシルに重なった数を書き込み，マスクの影響を受けるパーツは 1 以上の範囲のみ描画する
+// テクスチャのアルファが 0 の部分はフラグメントシェーダーで破棄されるので書き込まれない
+fn sprite_stencil_test(draw: SpriteDraw) -> Option<pso::StencilTest> {
+    let (fun, op_pass, write_mask) = match draw {
+        SpriteDraw::Normal => return None,
+        SpriteDraw::Masked => (pso::Comparison::Less, pso::StencilOp::Keep, 0),
+        SpriteDraw::MaskAdd => (pso::Comparison::Always, pso::StencilOp::IncrementClamp, !0),
+        SpriteDraw::MaskRemove => (pso::Comparison::Always, pso::StencilOp::DecrementClamp, !0),
+    };
+    Some(pso::StencilTest {
+        faces: pso::Sided::new(pso::StencilFace {
+            fun,
+            op_fail: pso::StencilOp::Keep,
+            op_depth_fail: pso::StencilOp::Keep,
+            op_pass,
+        }),
+        read_masks: pso::State::Static(pso::Sided::new(!0)),
+        write_masks: pso::State::Static(pso::Sided::new(write_mask)),
+        // 比較は (参照値 fun ステンシル値) なので 0 < ステンシル値 の時に通る
+        reference_values: pso::State::Static(pso::Sided::new(0)),
+    })
+}
+
+// セルの描画サイズ，原点と UV の変形
+pub(crate) struct CellTransform {
+    size: [Option<f32>; 2],
+    pivot_offset: [f32; 2],
+    local_scale: [f32; 2],
+    uv_offset: [f32; 2],
+    uv_rotation: f32,
+    uv_scale: [f32; 2],
+}
+
+impl CellTransform {
+    pub(crate) fn from_node<T>(node: &Node<T>) -> Self {
+        CellTransform {
+            size: node.size,
+            pivot_offset: node.pivot_offset,
+            local_scale: node.local_scale,
+            uv_offset: node.uv_offset,
+            uv_rotation: node.uv_rotation,
+            uv_scale: node.uv_scale,
         }
-        Ok(mut pipes) => Ok((pipes.remove(0), pipeline_layout)),
+    }
+
+    // セルのサイズに対する描画サイズの倍率
+    fn size_rate(&self, sprite: &Sprite) -> Vector2<f32> {
+        let rate = |size: Option<f32>, cell_size: f32| match size {
+            Some(size) if cell_size != 0. => size / cell_size,
+            _ => 1.,
+        };
+        Vector2::new(
+            rate(self.size[0], sprite.width),
+            rate(self.size[1], sprite.height),
+        )
+    }
+
+    // パーツの原点から見たセル中心の位置
+    fn center(&self, sprite: &Sprite) -> Vector2<f32> {
+        let rate = self.size_rate(sprite);
+        Vector2::new(
+            (-sprite.offsets[0] - self.pivot_offset[0] * sprite.width) * rate.x,
+            (-sprite.offsets[1] - self.pivot_offset[1] * sprite.height) * rate.y,
+        )
+    }
+
+    // セル中心からの位置をサイズ変更後の位置にしてローカルスケールをかける
+    fn vertex(&self, sprite: &Sprite, local: Vector2<f32>, deform: &[f32; 2]) -> Vector2<f32> {
+        let rate = self.size_rate(sprite);
+        let center = self.center(sprite);
+        Vector2::new(
+            (center.x + local.x * rate.x + deform[0]) * self.local_scale[0],
+            (center.y + local.y * rate.y + deform[1]) * self.local_scale[1],
+        )
+    }
+
+    // セルの左上を 0.0, 右下を 1.0 とした位置のテクスチャ座標
+    // スケールと回転はセル中心を基準にピクセル単位の空間で行う
+    fn tex_coord(&self, sprite: &Sprite, tex_coord: &[f32; 2]) -> [f32; 2] {
+        let TextureCoordinates {
+            left,
+            right,
+            top,
+            bottom,
+        } = sprite.tex_coords;
+        // スプライトの上辺は bottom 側のテクスチャ座標になる
+        let x = (tex_coord[0] - 0.5) * sprite.width * self.uv_scale[0];
+        let y = (tex_coord[1] - 0.5) * sprite.height * self.uv_scale[1];
+        let (sin, cos) = self.uv_rotation.to_radians().sin_cos();
+        let (x, y) = (x * cos - y * sin, x * sin + y * cos);
+
+        let u = (left + right) * 0.5 + x / sprite.width * (right - left);
+        let v = (top + bottom) * 0.5 + y / sprite.height * (top - bottom);
+        [u + self.uv_offset[0], v + self.uv_offset[1]]
     }
 }
 
+// セルの四隅の座標(右上，左上，右下，左下の順)
+// 反転はパーツの原点を中心に頂点座標の符号を反転させる
+pub(crate) fn quad_positions(
+    sprite: &Sprite,
+    global_matrix: &Matrix4<f32>,
+    deform_offsets: &[[f32; 2]; 4],
+    (flip_h, flip_v): (bool, bool),
+    cell_transform: &CellTransform,
+) -> [Vector4<f32>; 4] {
+    let sign_x = if flip_h { -1.0 } else { 1.0 };
+    let sign_y = if flip_v { -1.0 } else { 1.0 };
+    let mut positions = [Vector4::zeros(); 4];
+    for ((i, deform), position) in deform_offsets.iter().enumerate().zip(positions.iter_mut()) {
+        let left = if i % 2 == 0 { 0.5 } else { -0.5 };
+        let top = if i / 2 == 0 { 0.5 } else { -0.5 };
+        let local = Vector2::new(left * sprite.width, top * sprite.height);
+        let vertex = cell_transform.vertex(sprite, local, deform);
+        *position = global_matrix * Vector4::new(vertex.x * sign_x, vertex.y * sign_y, 0.0, 1.0);
+    }
+    positions
+}
+
 // シェーダーにわたすパラメータ生成
+// UV が回転している場合はスプライトの描画では表現できないので四角形のメッシュにする
 fn from_global_matrix_data<'a>(
     tex_storage: &AssetStorage<Texture>,
     sprite_storage: &'a AssetStorage<SpriteSheet>,
     sprite_sheet: &Handle<SpriteSheet>,
     sprite_no: usize,
     global_matrix: &Matrix4<f32>,
-    tint: [f32; 4],
+    (tints, tint_adds): (&[[f32; 4]; 4], &[[f32; 4]; 4]),
     deform_offsets: &[[f32; 2]; 4],
-) -> Option<(SpriteArgs, &'a Handle<Texture>)> {
+    (flip_h, flip_v): (bool, bool),
+    cell_transform: &CellTransform,
+) -> Option<(PartArgs, &'a Handle<Texture>)> {
     let sprite_sheet = sprite_storage.get(&sprite_sheet)?;
     if !tex_storage.contains(&sprite_sheet.texture) {
         return None;
@@ -335,45 +676,203 @@ fn from_global_matrix_data<'a>(
     let sprite = &sprite_sheet.sprites[sprite_no];
 
     let transform = global_matrix;
-    let pos = transform * Vector4::new(-sprite.offsets[0], -sprite.offsets[1], 0.0, 1.0);
-
-    let mut deforms = [
-        [0.0; 2].into(), // right bottom
-        [0.0; 2].into(), // left bottom
-        [0.0; 2].into(), //
-        [0.0; 2].into(),
-    ];
-    for (i, deform) in deform_offsets.iter().enumerate() {
-        let left = if i % 2 == 0 { 0.5 } else { -0.5 };
-        let top = if i / 2 == 0 { 0.5 } else { -0.5 };
-        deforms[i] = (transform
-            * Vector4::new(
-                -sprite.offsets[0] + left * sprite.width + deform[0],
-                -sprite.offsets[1] + top * sprite.height + deform[1],
-                0.0,
-                1.0,
-            ))
-        .xy()
-        .into_pod();
-    }
+    let center = cell_transform.center(sprite);
+    let pos = transform * Vector4::new(center.x, center.y, 0.0, 1.0);
+    let positions = quad_positions(
+        sprite,
+        transform,
+        deform_offsets,
+        (flip_h, flip_v),
+        cell_transform,
+    );
 
     log::debug!("\tmatrix: {:?}", transform);
-    log::debug!("\t\tcolor: {:?}", tint);
+    log::debug!("\t\tcolor: {:?}", tints);
     log::debug!("\t\tdeform offset: {:?}", deform_offsets);
-    log::debug!("\t\tdeforms: {:?}", deforms);
+    log::debug!("\t\tpositions: {:?}", positions);
+
+    if cell_transform.uv_rotation != 0. {
+        let corners = [[1., 0.], [0., 0.], [1., 1.], [0., 1.]];
+        let vertices = positions
+            .iter()
+            .zip(corners.iter())
+            .zip(tints.iter().zip(tint_adds.iter()))
+            .map(|((position, corner), (tint, tint_add))| MeshArgs {
+                position: position.xyz().into_pod(),
+                tex_coord: cell_transform.tex_coord(sprite, corner).into(),
+                tint: (*tint).into(),
+                tint_add: (*tint_add).into(),
+            })
+            .collect();
+        return Some((
+            PartArgs::Mesh(vertices, vec![0, 1, 2, 2, 1, 3]),
+            &sprite_sheet.texture,
+        ));
+    }
+
+    // 回転がなければ u は横位置，v は縦位置だけで決まる
+    let [left, bottom] = cell_transform.tex_coord(sprite, &[0., 0.]);
+    let [right, top] = cell_transform.tex_coord(sprite, &[1., 1.]);
+    let [rt, lt, rb, lb] = positions;
 
     Some((
-        SpriteArgs {
-            u_offset: [sprite.tex_coords.left, sprite.tex_coords.right].into(),
-            v_offset: [sprite.tex_coords.top, sprite.tex_coords.bottom].into(),
+        PartArgs::Sprite(SpriteArgs {
+            u_offset: [left, right].into(),
+            v_offset: [top, bottom].into(),
             depth: pos.z,
-            tint: tint.into(),
-            deforms,
-        },
+            tints: [
+                tints[0].into(),
+                tints[1].into(),
+                tints[2].into(),
+                tints[3].into(),
+            ],
+            deforms: [
+                rt.xy().into_pod(),
+                lt.xy().into_pod(),
+                rb.xy().into_pod(),
+                lb.xy().into_pod(),
+            ],
+            tint_adds: [
+                tint_adds[0].into(),
+                tint_adds[1].into(),
+                tint_adds[2].into(),
+                tint_adds[3].into(),
+            ],
+        }),
+        &sprite_sheet.texture,
+    ))
+}
+
+// メッシュパーツのシェーダーにわたすパラメータ生成
+// 頂点はスプライトと同じくパーツの原点を中心に反転させる
+// 頂点ごとの色はセル上の位置で四隅の色を補間する
+// 描画サイズ，原点と UV の変形はスプライトと同じく適用する
+fn from_mesh_data<'a>(
+    tex_storage: &AssetStorage<Texture>,
+    sprite_storage: &'a AssetStorage<SpriteSheet>,
+    sprite_sheet: &Handle<SpriteSheet>,
+    sprite_no: usize,
+    global_matrix: &Matrix4<f32>,
+    (tints, tint_adds): (&[[f32; 4]; 4], &[[f32; 4]; 4]),
+    mesh: &MeshNode,
+    (flip_h, flip_v): (bool, bool),
+    cell_transform: &CellTransform,
+) -> Option<(PartArgs, &'a Handle<Texture>)> {
+    let sprite_sheet = sprite_storage.get(&sprite_sheet)?;
+    if !tex_storage.contains(&sprite_sheet.texture) {
+        return None;
+    }
+
+    let sprite = &sprite_sheet.sprites[sprite_no];
+    let sign_x = if flip_h { -1.0 } else { 1.0 };
+    let sign_y = if flip_v { -1.0 } else { 1.0 };
+    let vertices = mesh
+        .vertices
+        .iter()
+        .zip(mesh.tex_coords.iter())
+        .map(|(vertex, tex_coord)| {
+            // 頂点はパーツの原点基準なのでセル中心基準にしてから変形する
+            let local = Vector2::new(vertex[0] + sprite.offsets[0], vertex[1] + sprite.offsets[1]);
+            let vertex = cell_transform.vertex(sprite, local, &[0.; 2]);
+            let pos = global_matrix * Vector4::new(vertex.x * sign_x, vertex.y * sign_y, 0., 1.);
+            MeshArgs {
+                position: pos.xyz().into_pod(),
+                tex_coord: cell_transform.tex_coord(sprite, tex_coord).into(),
+                tint: bilinear(tints, tex_coord).into(),
+                tint_add: bilinear(tint_adds, tex_coord).into(),
+            }
+        })
+        .collect();
+
+    log::debug!("\tmatrix: {:?}", global_matrix);
+    log::debug!("\t\tcolor: {:?}", tints);
+    log::debug!("\t\tmesh vertices: {:?}", mesh.vertices);
+
+    Some((
+        PartArgs::Mesh(vertices, Arc::clone(&mesh.indices)),
         &sprite_sheet.texture,
     ))
 }
 
+// 右上，左上，右下，左下の順の値をセル左上を 0.0 とした位置で補間する
+fn bilinear(corners: &[[f32; 4]; 4], tex_coord: &[f32; 2]) -> [f32; 4] {
+    let [rt, lt, rb, lb] = corners;
+    let (u, v) = (tex_coord[0], tex_coord[1]);
+    let mut value = [0.; 4];
+    for (i, value) in value.iter_mut().enumerate() {
+        let top = lt[i] + (rt[i] - lt[i]) * u;
+        let bottom = lb[i] + (rb[i] - lb[i]) * u;
+        *value = top + (bottom - top) * v;
+    }
+    value
+}
+
+fn build_node<B, T>(
+    node: &Node<T>,
+    sprite_sheet_storage: &Read<AssetStorage<SpriteSheet>>,
+    tex_storage: &Read<AssetStorage<Texture>>,
+    factory: &Factory<B>,
+    world: &World,
+    textures_ref: &mut TextureSub<B>,
+) -> Option<(TextureId, PartArgs)>
+where
+    B: Backend,
+{
+    let Node {
+        global_matrix,
+        hide,
+        vertex_colors,
+        vertex_color_offsets,
+        sprite_sheet,
+        sprite_no,
+        deform_offsets,
+        flip_h,
+        flip_v,
+        mesh,
+        ..
+    } = node;
+    let cell_transform = CellTransform::from_node(node);
+    if *hide == true {
+        return None;
+    }
+
+    let sprite_sheet = sprite_sheet.as_ref()?;
+    let sprite_no = (*sprite_no)?;
+    match mesh {
+        Some(mesh) => from_mesh_data(
+            tex_storage,
+            sprite_sheet_storage,
+            sprite_sheet,
+            sprite_no,
+            global_matrix,
+            (vertex_colors, vertex_color_offsets),
+            mesh,
+            (*flip_h, *flip_v),
+            &cell_transform,
+        ),
+        None => from_global_matrix_data(
+            tex_storage,
+            sprite_sheet_storage,
+            sprite_sheet,
+            sprite_no,
+            global_matrix,
+            (vertex_colors, vertex_color_offsets),
+            deform_offsets,
+            (*flip_h, *flip_v),
+            &cell_transform,
+        ),
+    }
+    .and_then(|(batch_data, texture)| {
+        let (tex_id, _) = textures_ref.insert(
+            factory,
+            world,
+            texture,
+            hal::image::Layout::ShaderReadOnlyOptimal,
+        )?;
+        Some((tex_id, batch_data))
+    })
+}
+
 fn build_animation<'s, B, T>(
     nodes: &AnimationNodes<T::UserData>,
     sprite_sheet_storage: &Read<AssetStorage<SpriteSheet>>,
@@ -381,47 +880,51 @@ fn build_animation<'s, B, T>(
     factory: &Factory<B>,
     world: &World,
     textures_ref: &mut TextureSub<B>,
-) -> Option<Vec<(TextureId, SpriteArgs)>>
+) -> Option<Vec<(SpriteDraw, AlphaBlend, TextureId, PartArgs)>>
 where
     B: Backend,
     T: TranslateAnimation<'s>,
 {
-    let node_groups = nodes.nodes().filter_map(
-        |Node {
-             global_matrix,
-             hide,
-             color,
-             sprite_sheet,
-             sprite_no,
-             deform_offsets,
-             ..
-         }| {
-            if *hide == true {
-                return None;
-            }
-
-            let sprite_sheet = sprite_sheet.as_ref()?;
-            let sprite_no = (*sprite_no)?;
-            from_global_matrix_data(
-                tex_storage,
+    // マスクは自分より優先度の低い(先に描画される)パーツにかかるので，
+    // 最初に全てのマスクをステンシルに書き込み，描画順でマスクパーツに来たら取り除く
+    // 書き込んだ分は必ず取り除くので，ほかのアニメーションにマスクが残ることはない
+    let mask_groups = nodes
+        .nodes()
+        .filter(|node| node.mask)
+        .filter_map(|node| {
+            let (tex_id, batch_data) = build_node(
+                node,
                 sprite_sheet_storage,
-                sprite_sheet,
-                sprite_no,
-                global_matrix,
-                *color,
-                deform_offsets,
-            )
-            .and_then(|(batch_data, texture)| {
-                let (tex_id, _) = textures_ref.insert(
-                    factory,
-                    world,
-                    texture,
-                    hal::image::Layout::ShaderReadOnlyOptimal,
-                )?;
-                Some((tex_id, batch_data))
-            })
-        },
-    );
+                tex_storage,
+                factory,
+                world,
+                textures_ref,
+            )?;
+            Some((SpriteDraw::MaskAdd, node.alpha_blend, tex_id, batch_data))
+        })
+        .collect::<Vec<_>>();
+
+    // 取り除かれていないマスクがなければステンシルを使わずに描画する
+    let mut remain_masks = mask_groups.len();
+    let node_groups = nodes.nodes().filter_map(|node| {
+        let (tex_id, batch_data) = build_node(
+            node,
+            sprite_sheet_storage,
+            tex_storage,
+            factory,
+            world,
+            textures_ref,
+        )?;
+        let draw = if node.mask {
+            remain_masks -= 1;
+            SpriteDraw::MaskRemove
+        } else if node.mask_influence && remain_masks > 0 {
+            SpriteDraw::Masked
+        } else {
+            SpriteDraw::Normal
+        };
+        Some((draw, node.alpha_blend, tex_id, batch_data))
+    });
 
     let mut n_group = node_groups.collect::<Vec<_>>();
 
@@ -443,6 +946,8 @@ where
         .flatten();
 
     let mut i_group = instance_group.collect::<Vec<_>>();
-    n_group.append(&mut i_group);
-    Some(n_group)
+    let mut groups = mask_groups;
+    groups.append(&mut n_group);
+    groups.append(&mut i_group);
+    Some(groups)
 }
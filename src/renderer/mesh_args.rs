@@ -0,0 +1,30 @@
+use amethyst::renderer::rendy::{
+    hal::format::Format,
+    util::types::vertex::{AsVertex, VertexFormat},
+};
+use glsl_layout::{vec2, vec3, vec4, AsStd140};
+
+// メッシュパーツの頂点ごとのパラメータ
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, AsStd140)]
+#[repr(C, align(4))]
+pub(crate) struct MeshArgs {
+    /// Transformed position and depth of this vertex
+    pub position: vec3,
+    /// Texture coordinate of this vertex in the spritesheet
+    pub tex_coord: vec2,
+    /// Tint for this vertex
+    pub tint: vec4,
+    /// Color added to the tinted texel (color blend)
+    pub tint_add: vec4,
+}
+
+impl AsVertex for MeshArgs {
+    fn vertex() -> VertexFormat {
+        VertexFormat::new((
+            (Format::Rgb32Sfloat, "position"),
+            (Format::Rg32Sfloat, "tex_coord"),
+            (Format::Rgba32Sfloat, "tint"),
+            (Format::Rgba32Sfloat, "tint_add"),
+        ))
+    }
+}
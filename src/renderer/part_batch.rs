@@ -0,0 +1,94 @@
+use super::{mesh_args::MeshArgs, sprite_args::SpriteArgs, SpriteDraw};
+use crate::types::AlphaBlend;
+use amethyst::renderer::submodules::TextureId;
+use std::{ops::Range, sync::Arc};
+
+// パーツ一個分の描画データ
+pub(crate) enum PartArgs {
+    Sprite(SpriteArgs),
+    Mesh(Vec<MeshArgs>, Arc<Vec<u32>>), // 頂点と三角形リストの頂点番号(メッシュのデータと共有)
+}
+
+// 描画範囲
+// スプライトはインスタンス，メッシュはインデックスの範囲
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum DrawRange {
+    Sprite(Range<u32>),
+    Mesh(Range<u32>),
+}
+
+// スプライトとメッシュを描画順を保ったまま記録する
+// 描画方法，合成方法とテクスチャが同じものが続く場合はまとめて描画する
+#[derive(Debug, Default)]
+pub(crate) struct PartBatch {
+    sprites: Vec<SpriteArgs>,
+    mesh_vertices: Vec<MeshArgs>,
+    mesh_indices: Vec<u32>,
+    commands: Vec<(SpriteDraw, AlphaBlend, TextureId, DrawRange)>,
+}
+
+impl PartBatch {
+    pub(crate) fn clear(&mut self) {
+        self.sprites.clear();
+        self.mesh_vertices.clear();
+        self.mesh_indices.clear();
+        self.commands.clear();
+    }
+
+    pub(crate) fn insert(
+        &mut self,
+        draw: SpriteDraw,
+        blend: AlphaBlend,
+        tex_id: TextureId,
+        args: PartArgs,
+    ) {
+        let range = match args {
+            PartArgs::Sprite(sprite) => {
+                let start = self.sprites.len() as u32;
+                self.sprites.push(sprite);
+                DrawRange::Sprite(start..start + 1)
+            }
+            PartArgs::Mesh(vertices, indices) => {
+                // 頂点番号はバッファ全体での番号にしておく
+                let base = self.mesh_vertices.len() as u32;
+                let start = self.mesh_indices.len() as u32;
+                self.mesh_vertices.extend(vertices);
+                self.mesh_indices
+                    .extend(indices.iter().map(|index| index + base));
+                DrawRange::Mesh(start..self.mesh_indices.len() as u32)
+            }
+        };
+
+        if let Some((last_draw, last_blend, last_tex, last_range)) = self.commands.last_mut() {
+            if *last_draw == draw && *last_blend == blend && *last_tex == tex_id {
+                match (last_range, &range) {
+                    (DrawRange::Sprite(last), DrawRange::Sprite(range))
+                    | (DrawRange::Mesh(last), DrawRange::Mesh(range)) => {
+                        last.end = range.end;
+                        return;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        self.commands.push((draw, blend, tex_id, range));
+    }
+
+    pub(crate) fn sprites(&self) -> &Vec<SpriteArgs> {
+        &self.sprites
+    }
+
+    pub(crate) fn mesh_vertices(&self) -> &Vec<MeshArgs> {
+        &self.mesh_vertices
+    }
+
+    pub(crate) fn mesh_indices(&self) -> &Vec<u32> {
+        &self.mesh_indices
+    }
+
+    pub(crate) fn commands(
+        &self,
+    ) -> impl Iterator<Item = &(SpriteDraw, AlphaBlend, TextureId, DrawRange)> {
+        self.commands.iter()
+    }
+}
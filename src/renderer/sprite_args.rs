@@ -13,9 +13,11 @@ pub(crate) struct SpriteArgs {
     pub v_offset: vec2,
     /// Depth value of this sprite
     pub depth: float,
-    /// Tint for this this sprite
-    pub tint: vec4,
+    /// Tint for each vertex of this sprite
+    pub tints: [vec4; 4],
     pub deforms: [vec2; 4],
+    /// Color added to the tinted texel for each vertex (color blend)
+    pub tint_adds: [vec4; 4],
 }
 
 impl AsVertex for SpriteArgs {
@@ -24,11 +26,18 @@ impl AsVertex for SpriteArgs {
             (Format::Rg32Sfloat, "u_offset"),
             (Format::Rg32Sfloat, "v_offset"),
             (Format::R32Sfloat, "depth"),
-            (Format::Rgba32Sfloat, "tint"),
+            (Format::Rgba32Sfloat, "tint_rt"),
+            (Format::Rgba32Sfloat, "tint_lt"),
+            (Format::Rgba32Sfloat, "tint_rb"),
+            (Format::Rgba32Sfloat, "tint_lb"),
             (Format::Rg32Sfloat, "deform_lt"),
             (Format::Rg32Sfloat, "deform_lb"),
             (Format::Rg32Sfloat, "deform_rt"),
             (Format::Rg32Sfloat, "deform_rb"),
+            (Format::Rgba32Sfloat, "tint_add_rt"),
+            (Format::Rgba32Sfloat, "tint_add_lt"),
+            (Format::Rgba32Sfloat, "tint_add_rb"),
+            (Format::Rgba32Sfloat, "tint_add_lb"),
         ))
     }
 }
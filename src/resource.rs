@@ -1,9 +1,13 @@
 pub mod animation;
 pub mod data;
+pub mod effect;
+pub mod font;
+pub mod mesh;
 pub mod name;
 pub mod pack;
 pub mod part;
 mod part_timeline;
+pub mod state_machine;
 pub mod timeline;
 
 use crate::traits::animation_file::AnimationFile;
@@ -17,6 +21,8 @@ where
 {
     pub(crate) animations: BTreeMap<T::FileId, AnimationHandle<T>>,
     pub(crate) sprite_sheets: BTreeMap<T::FileId, Vec<SpriteSheetHandle>>,
+    // 差し替え用のスプライトシートのセット(セット名ごとにセルマップ番号順)
+    pub(crate) sprite_sheet_sets: BTreeMap<T::FileId, BTreeMap<String, Vec<SpriteSheetHandle>>>,
 }
 
 impl<T> Default for AnimationStore<T>
@@ -27,6 +33,7 @@ where
         AnimationStore {
             animations: BTreeMap::new(),
             sprite_sheets: BTreeMap::new(),
+            sprite_sheet_sets: BTreeMap::new(),
         }
     }
 }
@@ -59,6 +66,40 @@ where
             .and_then(|sprite_sheets| sprite_sheets.get(map_id))
     }
 
+    // CellOverride で使うスプライトシートのセットを登録する
+    // セルの配置は元のスプライトシートと同じであること
+    pub fn insert_sprite_sheet_set<S: Into<String>>(
+        &mut self,
+        id: T::FileId,
+        name: S,
+        sprite_sheets: Vec<SpriteSheetHandle>,
+    ) {
+        self.sprite_sheet_sets
+            .entry(id)
+            .or_insert_with(BTreeMap::new)
+            .insert(name.into(), sprite_sheets);
+    }
+
+    pub fn remove_sprite_sheet_set(
+        &mut self,
+        id: &T::FileId,
+        name: &str,
+    ) -> Option<Vec<SpriteSheetHandle>> {
+        self.sprite_sheet_sets.get_mut(id)?.remove(name)
+    }
+
+    pub fn get_sprite_set_handle(
+        &self,
+        id: &T::FileId,
+        name: &str,
+        map_id: usize,
+    ) -> Option<&SpriteSheetHandle> {
+        self.sprite_sheet_sets
+            .get(id)
+            .and_then(|sets| sets.get(name))
+            .and_then(|sprite_sheets| sprite_sheets.get(map_id))
+    }
+
     // ステートの終わりなどで開放したい場合はここで
     // 別でハンドルを参照しているエンティティがあれば破棄はできない
     pub fn unload_file(
@@ -67,6 +108,7 @@ where
     ) -> Option<(AnimationHandle<T>, Vec<SpriteSheetHandle>)> {
         let removed_animations = self.animations.remove(id)?;
         let removed_sheets = self.sprite_sheets.remove(id)?;
+        self.sprite_sheet_sets.remove(id);
         log::info!(
             "unload animation: {:?}: {:?}, {:?}",
             id,
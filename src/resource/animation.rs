@@ -1,16 +1,22 @@
 use super::part_timeline::PartTimeline;
 #[cfg(feature = "builder")]
 use super::part_timeline::PartTimelineBuilder;
-use crate::types::{cell::Cell, EffectKey, InstanceKey, VertexKey};
+use crate::types::{
+    cell::Cell, DeformKey, EffectKey, InstanceKey, LinearColor, SortMode, TextKey, VertexColorKey,
+    VertexKey,
+};
 #[cfg(feature = "builder")]
-use crate::types::{interpolate::Interpolation, LinearColor};
+use crate::types::{interpolate::KeyInterpolation, ColorBlend};
 use amethyst::{core::Transform, renderer::resources::Tint};
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Animation<U> {
     fps: usize,
     total_frame: usize,
+    #[serde(default)]
+    sort_mode: SortMode,
     parts_timelines: Vec<PartTimeline<U>>,
 }
 
@@ -23,6 +29,11 @@ impl<U> Animation<U> {
         self.total_frame
     }
 
+    // パーツの描画順の決め方
+    pub fn sort_mode(&self) -> SortMode {
+        self.sort_mode
+    }
+
     pub fn hide(&self, part_id: usize, frame: usize) -> bool {
         log::trace!("[hide] id: {}, frame: {}", part_id, frame);
         self.parts_timelines[part_id].hide(frame)
@@ -37,34 +48,114 @@ impl<U> Animation<U> {
         self.parts_timelines[part_id].local_transform(frame)
     }
 
+    pub fn local_flip(&self, part_id: usize, frame: usize) -> (bool, bool) {
+        log::trace!("[local_flip] id: {}, frame: {}", part_id, frame);
+        self.parts_timelines[part_id].flip(frame)
+    }
+
     pub fn local_color(&self, part_id: usize, frame: usize) -> Tint {
         log::trace!("[local_color] id: {}, frame: {}", part_id, frame);
         self.parts_timelines[part_id].color(frame)
     }
 
+    pub fn local_color_offset(&self, part_id: usize, frame: usize) -> LinearColor {
+        log::trace!("[local_color_offset] id: {}, frame: {}", part_id, frame);
+        self.parts_timelines[part_id].color_offset(frame)
+    }
+
+    // 頂点ごとの色(左上，右上，左下，右下の順に乗算成分と加算成分)
+    pub fn local_vertex_colors(
+        &self,
+        part_id: usize,
+        frame: usize,
+    ) -> Option<[(LinearColor, LinearColor); 4]> {
+        log::trace!("[local_vertex_colors] id: {}, frame: {}", part_id, frame);
+        self.parts_timelines[part_id].vertex_colors(frame)
+    }
+
     pub fn user(&self, part_id: usize, frame: usize) -> Option<&U> {
         log::trace!("[user] id: {}, frame: {}", part_id, frame);
         self.parts_timelines[part_id].user(frame)
     }
 
+    // 範囲内のフレームにあるユーザーデータのキー(キーのフレームと値)
+    pub fn user_keys(
+        &self,
+        part_id: usize,
+        frames: Range<usize>,
+    ) -> impl Iterator<Item = (usize, &U)> {
+        self.parts_timelines[part_id].user_keys(frames)
+    }
+
     pub fn instance(&self, part_id: usize, frame: usize) -> Option<(usize, &InstanceKey)> {
         log::trace!("[instance] id: {}, frame: {}", part_id, frame);
         self.parts_timelines[part_id].instance(frame)
     }
 
+    // 範囲内のフレームにあるインスタンスのキー(キーのフレームと値)
+    pub fn instance_keys(
+        &self,
+        part_id: usize,
+        frames: Range<usize>,
+    ) -> impl Iterator<Item = (usize, &InstanceKey)> {
+        self.parts_timelines[part_id].instance_keys(frames)
+    }
+
     pub fn vertex(&self, part_id: usize, frame: usize) -> Option<VertexKey> {
         self.parts_timelines[part_id].vertex(frame)
     }
 
-    pub fn effect(&self, part_id: usize, frame: usize) -> Option<&EffectKey> {
+    pub fn effect(&self, part_id: usize, frame: usize) -> Option<(usize, &EffectKey)> {
         self.parts_timelines[part_id].effect(frame)
     }
+
+    pub fn deform(&self, part_id: usize, frame: usize) -> Option<DeformKey> {
+        self.parts_timelines[part_id].deform(frame)
+    }
+
+    pub fn text(&self, part_id: usize, frame: usize) -> Option<&TextKey> {
+        self.parts_timelines[part_id].text(frame)
+    }
+
+    pub fn local_pivot_offset(&self, part_id: usize, frame: usize) -> (f32, f32) {
+        self.parts_timelines[part_id].pivot_offset(frame)
+    }
+
+    pub fn local_size(&self, part_id: usize, frame: usize) -> (Option<f32>, Option<f32>) {
+        self.parts_timelines[part_id].size(frame)
+    }
+
+    pub fn local_uv_transform(
+        &self,
+        part_id: usize,
+        frame: usize,
+    ) -> ((f32, f32), f32, (f32, f32)) {
+        self.parts_timelines[part_id].uv_transform(frame)
+    }
+
+    pub fn local_scale(&self, part_id: usize, frame: usize) -> (f32, f32) {
+        self.parts_timelines[part_id].local_scale(frame)
+    }
+
+    pub fn local_alpha(&self, part_id: usize, frame: usize) -> f32 {
+        self.parts_timelines[part_id].local_alpha(frame)
+    }
+
+    pub fn priority(&self, part_id: usize, frame: usize) -> f32 {
+        self.parts_timelines[part_id].priority(frame)
+    }
+
+    // 当たり判定の円の半径(キーがなければ None)
+    pub fn bounding_radius(&self, part_id: usize, frame: usize) -> Option<f32> {
+        self.parts_timelines[part_id].bounding_radius(frame)
+    }
 }
 
 #[cfg(feature = "builder")]
 pub struct AnimationBuilder<U> {
     fps: usize,
     total_frame: usize,
+    sort_mode: SortMode,
     parts_timelines: Vec<PartTimelineBuilder<U>>,
 }
 
@@ -74,14 +165,19 @@ impl<U> AnimationBuilder<U> {
         AnimationBuilder {
             fps,
             total_frame,
+            sort_mode: SortMode::default(),
             parts_timelines: (0..part_num).map(|_| PartTimelineBuilder::new()).collect(),
         }
     }
+    pub fn set_sort_mode(&mut self, sort_mode: SortMode) {
+        self.sort_mode = sort_mode;
+    }
+
     pub fn add_hide(
         &mut self,
         part_id: usize,
         frame: usize,
-        interpolation: Interpolation,
+        interpolation: impl Into<KeyInterpolation>,
         visible: bool,
     ) {
         self.parts_timelines[part_id].add_hide(frame, interpolation, visible);
@@ -91,7 +187,7 @@ impl<U> AnimationBuilder<U> {
         &mut self,
         part_id: usize,
         frame: usize,
-        interpolation: Interpolation,
+        interpolation: impl Into<KeyInterpolation>,
         cell: Cell,
     ) {
         self.parts_timelines[part_id].add_cell(frame, interpolation, cell);
@@ -101,7 +197,7 @@ impl<U> AnimationBuilder<U> {
         &mut self,
         part_id: usize,
         frame: usize,
-        interpolation: Interpolation,
+        interpolation: impl Into<KeyInterpolation>,
         pos_x: f32,
     ) {
         self.parts_timelines[part_id].add_pos_x(frame, interpolation, pos_x);
@@ -111,7 +207,7 @@ impl<U> AnimationBuilder<U> {
         &mut self,
         part_id: usize,
         frame: usize,
-        interpolation: Interpolation,
+        interpolation: impl Into<KeyInterpolation>,
         pos_y: f32,
     ) {
         self.parts_timelines[part_id].add_pos_y(frame, interpolation, pos_y);
@@ -121,7 +217,7 @@ impl<U> AnimationBuilder<U> {
         &mut self,
         part_id: usize,
         frame: usize,
-        interpolation: Interpolation,
+        interpolation: impl Into<KeyInterpolation>,
         pos_z: f32,
     ) {
         self.parts_timelines[part_id].add_pos_z(frame, interpolation, pos_z);
@@ -131,7 +227,7 @@ impl<U> AnimationBuilder<U> {
         &mut self,
         part_id: usize,
         frame: usize,
-        interpolation: Interpolation,
+        interpolation: impl Into<KeyInterpolation>,
         scale_x: f32,
     ) {
         self.parts_timelines[part_id].add_scale_x(frame, interpolation, scale_x);
@@ -140,7 +236,7 @@ impl<U> AnimationBuilder<U> {
         &mut self,
         part_id: usize,
         frame: usize,
-        interpolation: Interpolation,
+        interpolation: impl Into<KeyInterpolation>,
         scale_y: f32,
     ) {
         self.parts_timelines[part_id].add_scale_y(frame, interpolation, scale_y);
@@ -149,7 +245,7 @@ impl<U> AnimationBuilder<U> {
         &mut self,
         part_id: usize,
         frame: usize,
-        interpolation: Interpolation,
+        interpolation: impl Into<KeyInterpolation>,
         rotated: f32,
     ) {
         self.parts_timelines[part_id].add_rotated(frame, interpolation, rotated);
@@ -159,7 +255,7 @@ impl<U> AnimationBuilder<U> {
         &mut self,
         part_id: usize,
         frame: usize,
-        interpolation: Interpolation,
+        interpolation: impl Into<KeyInterpolation>,
         flip_v: bool,
     ) {
         self.parts_timelines[part_id].add_flip_v(frame, interpolation, flip_v);
@@ -168,7 +264,7 @@ impl<U> AnimationBuilder<U> {
         &mut self,
         part_id: usize,
         frame: usize,
-        interpolation: Interpolation,
+        interpolation: impl Into<KeyInterpolation>,
         flip_h: bool,
     ) {
         self.parts_timelines[part_id].add_flip_h(frame, interpolation, flip_h);
@@ -178,7 +274,7 @@ impl<U> AnimationBuilder<U> {
         &mut self,
         part_id: usize,
         frame: usize,
-        interpolation: Interpolation,
+        interpolation: impl Into<KeyInterpolation>,
         alpha: f32,
     ) {
         self.parts_timelines[part_id].add_alpha(frame, interpolation, alpha);
@@ -187,18 +283,48 @@ impl<U> AnimationBuilder<U> {
         &mut self,
         part_id: usize,
         frame: usize,
-        interpolation: Interpolation,
+        interpolation: impl Into<KeyInterpolation>,
         color: LinearColor,
     ) {
         self.parts_timelines[part_id].add_color(frame, interpolation, color);
     }
 
+    pub fn add_color_rate(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        rate: f32,
+    ) {
+        self.parts_timelines[part_id].add_color_rate(frame, interpolation, rate);
+    }
+
+    pub fn add_color_blend(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        blend: ColorBlend,
+    ) {
+        self.parts_timelines[part_id].add_color_blend(frame, interpolation, blend);
+    }
+
+    pub fn add_vertex_color(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        vertex_color: VertexColorKey,
+    ) {
+        self.parts_timelines[part_id].add_vertex_color(frame, interpolation, vertex_color);
+    }
+
     // カスタムデータ
     pub fn add_user(
         &mut self,
         part_id: usize,
         frame: usize,
-        interpolation: Interpolation,
+        interpolation: impl Into<KeyInterpolation>,
         user: U,
     ) {
         self.parts_timelines[part_id].add_user(frame, interpolation, user);
@@ -209,7 +335,7 @@ impl<U> AnimationBuilder<U> {
         &mut self,
         part_id: usize,
         frame: usize,
-        interpolation: Interpolation,
+        interpolation: impl Into<KeyInterpolation>,
         instance: InstanceKey,
     ) {
         self.parts_timelines[part_id].add_instance(frame, interpolation, instance);
@@ -219,7 +345,7 @@ impl<U> AnimationBuilder<U> {
         &mut self,
         part_id: usize,
         frame: usize,
-        interpolation: Interpolation,
+        interpolation: impl Into<KeyInterpolation>,
         vertex: VertexKey,
     ) {
         self.parts_timelines[part_id].add_vertex(frame, interpolation, vertex);
@@ -229,16 +355,177 @@ impl<U> AnimationBuilder<U> {
         &mut self,
         part_id: usize,
         frame: usize,
-        interpolation: Interpolation,
+        interpolation: impl Into<KeyInterpolation>,
         effect: EffectKey,
     ) {
         self.parts_timelines[part_id].add_effect(frame, interpolation, effect);
     }
 
+    pub fn add_deform(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        deform: DeformKey,
+    ) {
+        self.parts_timelines[part_id].add_deform(frame, interpolation, deform);
+    }
+
+    pub fn add_text(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        text: TextKey,
+    ) {
+        self.parts_timelines[part_id].add_text(frame, interpolation, text);
+    }
+
+    pub fn add_pivot_x(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        pivot_x: f32,
+    ) {
+        self.parts_timelines[part_id].add_pivot_x(frame, interpolation, pivot_x);
+    }
+
+    pub fn add_pivot_y(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        pivot_y: f32,
+    ) {
+        self.parts_timelines[part_id].add_pivot_y(frame, interpolation, pivot_y);
+    }
+
+    pub fn add_size_x(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        size_x: f32,
+    ) {
+        self.parts_timelines[part_id].add_size_x(frame, interpolation, size_x);
+    }
+
+    pub fn add_size_y(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        size_y: f32,
+    ) {
+        self.parts_timelines[part_id].add_size_y(frame, interpolation, size_y);
+    }
+
+    pub fn add_uv_x(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        uv_x: f32,
+    ) {
+        self.parts_timelines[part_id].add_uv_x(frame, interpolation, uv_x);
+    }
+
+    pub fn add_uv_y(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        uv_y: f32,
+    ) {
+        self.parts_timelines[part_id].add_uv_y(frame, interpolation, uv_y);
+    }
+
+    pub fn add_uv_rotated(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        uv_rotated: f32,
+    ) {
+        self.parts_timelines[part_id].add_uv_rotated(frame, interpolation, uv_rotated);
+    }
+
+    pub fn add_uv_scale_x(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        uv_scale_x: f32,
+    ) {
+        self.parts_timelines[part_id].add_uv_scale_x(frame, interpolation, uv_scale_x);
+    }
+
+    pub fn add_uv_scale_y(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        uv_scale_y: f32,
+    ) {
+        self.parts_timelines[part_id].add_uv_scale_y(frame, interpolation, uv_scale_y);
+    }
+
+    pub fn add_local_scale_x(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        local_scale_x: f32,
+    ) {
+        self.parts_timelines[part_id].add_local_scale_x(frame, interpolation, local_scale_x);
+    }
+
+    pub fn add_local_scale_y(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        local_scale_y: f32,
+    ) {
+        self.parts_timelines[part_id].add_local_scale_y(frame, interpolation, local_scale_y);
+    }
+
+    pub fn add_local_alpha(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        local_alpha: f32,
+    ) {
+        self.parts_timelines[part_id].add_local_alpha(frame, interpolation, local_alpha);
+    }
+
+    pub fn add_priority(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        priority: f32,
+    ) {
+        self.parts_timelines[part_id].add_priority(frame, interpolation, priority);
+    }
+
+    pub fn add_bounding_radius(
+        &mut self,
+        part_id: usize,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        radius: f32,
+    ) {
+        self.parts_timelines[part_id].add_bounding_radius(frame, interpolation, radius);
+    }
+
     pub fn build(self) -> Animation<U> {
         Animation {
             fps: self.fps,
             total_frame: self.total_frame,
+            sort_mode: self.sort_mode,
             parts_timelines: self
                 .parts_timelines
                 .into_iter()
@@ -1,9 +1,12 @@
-use crate::{resource::pack::Pack, traits::animation_file::AnimationFile};
+use crate::{
+    resource::{effect::Effect, font::BitmapFont, mesh::Mesh, name::AnimationName, pack::Pack},
+    traits::animation_file::AnimationFile,
+};
 use amethyst::{
     assets::{Asset, Handle},
     ecs::DenseVecStorage,
 };
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::BTreeMap;
 
 // アニメーションデータ
@@ -17,6 +20,22 @@ where
         deserialize = "BTreeMap<T::PackKey, Pack<T::UserData,T::PackKey, T::AnimationKey>>: Deserialize<'de>"
     ))]
     packs: BTreeMap<T::PackKey, Pack<T::UserData, T::PackKey, T::AnimationKey>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    effects: Vec<Effect>, // パーツからは refference_effect_index で参照される
+    // メッシュパーツからはセルで参照されるので，セルマップ番号とセル番号で引けるようにする
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    meshes: BTreeMap<(usize, usize), Mesh>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    fonts: Vec<BitmapFont>, // テキストパーツからはセルマップ番号で参照される
+    // パーツからは AnimationName::File の番号で参照される
+    // ファイル名で保存して読み込み時にファイルIDに解決する
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_files::<T, _>",
+        deserialize_with = "deserialize_files::<T, _>"
+    )]
+    files: Vec<T::FileId>,
 }
 
 impl<T> AnimationData<T>
@@ -29,6 +48,66 @@ where
     ) -> Option<&Pack<T::UserData, T::PackKey, T::AnimationKey>> {
         self.packs.get(pack)
     }
+
+    pub fn effect(&self, index: usize) -> Option<&Effect> {
+        self.effects.get(index)
+    }
+
+    pub fn mesh(&self, map_id: usize, cell_id: usize) -> Option<&Mesh> {
+        self.meshes.get(&(map_id, cell_id))
+    }
+
+    pub fn font(&self, map_id: usize) -> Option<&BitmapFont> {
+        self.fonts.iter().find(|font| font.map_id() == map_id)
+    }
+
+    // 別ファイルを参照するときのファイルID
+    pub fn file(&self, index: usize) -> Option<&T::FileId> {
+        self.files.get(index)
+    }
+
+    // 参照名から再生するファイル，パック，アニメーションのキーを解決する
+    // 参照名で省略されているものは現在再生中のキーを使う
+    pub fn resolve_animation_name(
+        &self,
+        name: &AnimationName<T::PackKey, T::AnimationKey>,
+        (id, pack, animation): (&T::FileId, &T::PackKey, &T::AnimationKey),
+    ) -> Option<(T::FileId, T::PackKey, T::AnimationKey)> {
+        let file_id = match name.file_index() {
+            Some(index) => *self.file(index)?,
+            None => *id,
+        };
+        let (pack, animation) = match name.pack_animation(pack, animation) {
+            Some(key) => key,
+            None => {
+                log::warn!("unsupported animation name: {:?}", name);
+                return None;
+            }
+        };
+        Some((file_id, pack, animation))
+    }
+}
+
+fn serialize_files<T, S>(files: &[T::FileId], serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: AnimationFile,
+    S: Serializer,
+{
+    serializer.collect_seq(files.iter().map(|file_id| T::to_file_name(file_id)))
+}
+
+fn deserialize_files<'de, T, D>(deserializer: D) -> Result<Vec<T::FileId>, D::Error>
+where
+    T: AnimationFile,
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|file_name| {
+            T::from_file_name(file_name)
+                .ok_or_else(|| D::Error::custom(format!("unknown file name: {}", file_name)))
+        })
+        .collect()
 }
 
 impl<T> Asset for AnimationData<T>
@@ -62,6 +141,10 @@ where
     T: AnimationFile,
 {
     packs: BTreeMap<T::PackKey, Pack<T::UserData, T::PackKey, T::AnimationKey>>,
+    effects: Vec<Effect>,
+    meshes: Vec<Mesh>,
+    fonts: Vec<BitmapFont>,
+    files: Vec<T::FileId>,
 }
 
 #[cfg(feature = "builder")]
@@ -72,10 +155,47 @@ where
     pub fn new(
         packs: BTreeMap<T::PackKey, Pack<T::UserData, T::PackKey, T::AnimationKey>>,
     ) -> Self {
-        AnimationDataBuilder { packs }
+        AnimationDataBuilder {
+            packs,
+            effects: vec![],
+            meshes: vec![],
+            fonts: vec![],
+            files: vec![],
+        }
+    }
+
+    pub fn effects(mut self, effects: Vec<Effect>) -> Self {
+        self.effects = effects;
+        self
+    }
+
+    pub fn meshes(mut self, meshes: Vec<Mesh>) -> Self {
+        self.meshes = meshes;
+        self
+    }
+
+    pub fn fonts(mut self, fonts: Vec<BitmapFont>) -> Self {
+        self.fonts = fonts;
+        self
+    }
+
+    // AnimationName::File で参照するファイル
+    pub fn files(mut self, files: Vec<T::FileId>) -> Self {
+        self.files = files;
+        self
     }
 
     pub fn build(self) -> AnimationData<T> {
-        AnimationData { packs: self.packs }
+        AnimationData {
+            packs: self.packs,
+            effects: self.effects,
+            meshes: self
+                .meshes
+                .into_iter()
+                .map(|mesh| ((mesh.cell().map_id(), mesh.cell().cell_id()), mesh))
+                .collect(),
+            fonts: self.fonts,
+            files: self.files,
+        }
     }
 }
@@ -0,0 +1,403 @@
+use crate::types::{cell::Cell, AlphaBlend, LinearColor};
+use serde::{Deserialize, Serialize};
+
+//----------------------------------------------------
+// エフェクトデータ
+// SpriteStudio のエフェクトファイル(.ssee)一個に相当する
+// パーティクルは状態を持たず，経過フレームと乱数シードから毎回計算する
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Effect {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lock_seed: Option<u32>, // 乱数シード固定
+    emitters: Vec<Emitter>, // 描画優先度順
+}
+
+impl Effect {
+    pub fn emitters(&self) -> impl Iterator<Item = &Emitter> {
+        self.emitters.iter()
+    }
+
+    pub fn emitter(&self, emitter_id: usize) -> Option<&Emitter> {
+        self.emitters.get(emitter_id)
+    }
+
+    // 指定フレームで生存しているパーティクルを列挙
+    // シードが固定されていなければ呼び出し側のシードを使う
+    pub fn particles(&self, frame: f32, seed: u32) -> Vec<Particle> {
+        let seed = self.lock_seed.unwrap_or(seed);
+        self.emitters
+            .iter()
+            .enumerate()
+            .flat_map(|(emitter_id, emitter)| emitter.particles(emitter_id, frame, seed))
+            .collect()
+    }
+}
+
+//----------------------------------------------------
+// 最小値と最大値の間で乱数で決まる値
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ValueRange(pub f32, pub f32);
+
+impl ValueRange {
+    fn sample(&self, random: &mut Random) -> f32 {
+        self.0 + (self.1 - self.0) * random.next_f32()
+    }
+}
+
+impl From<f32> for ValueRange {
+    fn from(value: f32) -> Self {
+        ValueRange(value, value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ColorRange(pub LinearColor, pub LinearColor);
+
+impl ColorRange {
+    fn sample(&self, random: &mut Random) -> LinearColor {
+        (self.1 - self.0) * random.next_f32() + self.0
+    }
+}
+
+//----------------------------------------------------
+// エミッター
+// 生成間隔ごとに生成数分のパーティクルを放出する
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Emitter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cell: Option<Cell>,
+    max_particles: usize,
+    create_count: usize, // 一度に生成する数
+    interval: usize,     // 生成間隔フレーム
+    delay: usize,        // 生成開始までのフレーム
+    life_time: usize,    // エミッターの寿命フレーム
+    infinite: bool,      // 寿命を無視して生成し続ける
+    seed_offset: u32,
+
+    particle_life: ValueRange,
+    speed: ValueRange,
+    angle: f32, // 上方向を 0 とした度数
+    angle_variance: f32,
+    gravity: (f32, f32),
+    offset: (ValueRange, ValueRange),
+    rotation: ValueRange,
+    rotation_add: ValueRange, // 1 フレームあたりの回転量
+    start_scale: (ValueRange, ValueRange),
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end_scale: Option<(ValueRange, ValueRange)>,
+    start_color: ColorRange,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end_color: Option<ColorRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    alpha_fade: Option<(f32, f32)>, // 寿命に対するフェードイン終了，フェードアウト開始の割合
+    #[serde(default)]
+    alpha_blend: AlphaBlend, // パーティクルと描画先との合成方法
+}
+
+impl Emitter {
+    pub fn cell(&self) -> Option<&Cell> {
+        self.cell.as_ref()
+    }
+
+    pub fn max_particles(&self) -> usize {
+        self.max_particles
+    }
+
+    pub fn alpha_blend(&self) -> AlphaBlend {
+        self.alpha_blend
+    }
+
+    fn particles(&self, emitter_id: usize, frame: f32, seed: u32) -> Vec<Particle> {
+        let interval = self.interval.max(1);
+        let max_life = self.particle_life.0.max(self.particle_life.1);
+        let local_frame = frame - self.delay as f32;
+        if local_frame < 0. {
+            return vec![];
+        }
+
+        // 生きている可能性のある生成回だけを調べる
+        let first = ((local_frame - max_life).max(0.) / interval as f32) as usize;
+        let mut last = (local_frame / interval as f32) as usize;
+        if self.infinite == false {
+            if self.life_time == 0 {
+                return vec![];
+            }
+            last = last.min((self.life_time - 1) / interval);
+        }
+
+        let mut particles = Vec::with_capacity(self.max_particles);
+        for emit in (first..=last).rev() {
+            let birth = (emit * interval) as f32;
+            for i in 0..self.create_count {
+                if particles.len() >= self.max_particles {
+                    return particles;
+                }
+                let index = (emit * self.create_count + i) as u32;
+                let mut random = Random::new(seed ^ self.seed_offset, emitter_id as u32, index);
+                if let Some(particle) = self.particle(&mut random, local_frame - birth, emitter_id)
+                {
+                    particles.push(particle);
+                }
+            }
+        }
+        particles
+    }
+
+    // 乱数の取り出し順は固定なのでパラメータ追加時は末尾に足すこと
+    fn particle(&self, random: &mut Random, age: f32, emitter_id: usize) -> Option<Particle> {
+        let life = self.particle_life.sample(random);
+        if age >= life {
+            return None;
+        }
+        let rate = if life > 0. { age / life } else { 1. };
+
+        let speed = self.speed.sample(random);
+        let angle = (self.angle + self.angle_variance * (random.next_f32() - 0.5)).to_radians();
+        let (offset_x, offset_y) = (self.offset.0.sample(random), self.offset.1.sample(random));
+        let rotation = self.rotation.sample(random);
+        let rotation_add = self.rotation_add.sample(random);
+        let start_scale = (
+            self.start_scale.0.sample(random),
+            self.start_scale.1.sample(random),
+        );
+        let end_scale = self
+            .end_scale
+            .map(|(x, y)| (x.sample(random), y.sample(random)))
+            .unwrap_or(start_scale);
+        let start_color = self.start_color.sample(random);
+        let end_color = self
+            .end_color
+            .map(|color| color.sample(random))
+            .unwrap_or(start_color);
+
+        let position = (
+            offset_x - angle.sin() * speed * age + 0.5 * self.gravity.0 * age * age,
+            offset_y + angle.cos() * speed * age + 0.5 * self.gravity.1 * age * age,
+        );
+        let scale = (
+            start_scale.0 + (end_scale.0 - start_scale.0) * rate,
+            start_scale.1 + (end_scale.1 - start_scale.1) * rate,
+        );
+        let LinearColor(r, g, b, a) = (end_color - start_color) * rate + start_color;
+        let fade = match self.alpha_fade {
+            Some((fade_in, _)) if rate < fade_in => rate / fade_in,
+            Some((_, fade_out)) if rate > fade_out && fade_out < 1. => {
+                (1. - rate) / (1. - fade_out)
+            }
+            _ => 1.,
+        };
+
+        Some(Particle {
+            emitter_id,
+            position,
+            rotation: (rotation + rotation_add * age).to_radians(),
+            scale,
+            color: [r, g, b, a * fade],
+        })
+    }
+}
+
+//----------------------------------------------------
+// 計算済みのパーティクル
+// 座標はエフェクトパーツのローカル座標
+#[derive(Debug, Clone)]
+pub struct Particle {
+    pub emitter_id: usize,
+    pub position: (f32, f32),
+    pub rotation: f32, // ラジアン
+    pub scale: (f32, f32),
+    pub color: [f32; 4],
+}
+
+//----------------------------------------------------
+// 同じシードと番号なら必ず同じ値を返す xorshift32
+struct Random(u32);
+
+impl Random {
+    fn new(seed: u32, emitter_id: u32, index: u32) -> Self {
+        let mut state =
+            seed ^ emitter_id.wrapping_mul(0x9e37_79b9) ^ index.wrapping_mul(0x85eb_ca6b);
+        if state == 0 {
+            state = 0x6c07_8965;
+        }
+        let mut random = Random(state);
+        // 近いシード同士の偏りを減らすため最初の数回は捨てる
+        for _ in 0..4 {
+            random.next_u32();
+        }
+        random
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x
+    }
+
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+}
+
+//----------------------------------------------------
+// データ参照のみのためビルダーパターン
+#[cfg(feature = "builder")]
+pub struct EffectBuilder {
+    lock_seed: Option<u32>,
+    emitters: Vec<Emitter>,
+}
+
+#[cfg(feature = "builder")]
+impl EffectBuilder {
+    pub fn new() -> Self {
+        EffectBuilder {
+            lock_seed: None,
+            emitters: vec![],
+        }
+    }
+
+    pub fn lock_seed(mut self, seed: u32) -> Self {
+        self.lock_seed = Some(seed);
+        self
+    }
+
+    pub fn add_emitter(mut self, emitter: Emitter) -> Self {
+        self.emitters.push(emitter);
+        self
+    }
+
+    pub fn build(self) -> Effect {
+        Effect {
+            lock_seed: self.lock_seed,
+            emitters: self.emitters,
+        }
+    }
+}
+
+#[cfg(feature = "builder")]
+pub struct EmitterBuilder {
+    emitter: Emitter,
+}
+
+#[cfg(feature = "builder")]
+impl EmitterBuilder {
+    pub fn new() -> Self {
+        EmitterBuilder {
+            emitter: Emitter {
+                cell: None,
+                max_particles: 50,
+                create_count: 1,
+                interval: 1,
+                delay: 0,
+                life_time: 30,
+                infinite: false,
+                seed_offset: 0,
+                particle_life: 30.0.into(),
+                speed: 5.0.into(),
+                angle: 0.,
+                angle_variance: 45.,
+                gravity: (0., 0.),
+                offset: (0.0.into(), 0.0.into()),
+                rotation: 0.0.into(),
+                rotation_add: 0.0.into(),
+                start_scale: (1.0.into(), 1.0.into()),
+                end_scale: None,
+                start_color: ColorRange(LinearColor::default(), LinearColor::default()),
+                end_color: None,
+                alpha_fade: None,
+                alpha_blend: AlphaBlend::Mix,
+            },
+        }
+    }
+
+    pub fn cell(mut self, cell: Cell) -> Self {
+        self.emitter.cell = Some(cell);
+        self
+    }
+    pub fn max_particles(mut self, max: usize) -> Self {
+        self.emitter.max_particles = max;
+        self
+    }
+    pub fn create_count(mut self, count: usize) -> Self {
+        self.emitter.create_count = count;
+        self
+    }
+    pub fn interval(mut self, interval: usize) -> Self {
+        self.emitter.interval = interval;
+        self
+    }
+    pub fn delay(mut self, delay: usize) -> Self {
+        self.emitter.delay = delay;
+        self
+    }
+    pub fn life_time(mut self, life_time: usize) -> Self {
+        self.emitter.life_time = life_time;
+        self
+    }
+    pub fn infinite(mut self, infinite: bool) -> Self {
+        self.emitter.infinite = infinite;
+        self
+    }
+    pub fn seed_offset(mut self, offset: u32) -> Self {
+        self.emitter.seed_offset = offset;
+        self
+    }
+    pub fn particle_life(mut self, life: ValueRange) -> Self {
+        self.emitter.particle_life = life;
+        self
+    }
+    pub fn speed(mut self, speed: ValueRange) -> Self {
+        self.emitter.speed = speed;
+        self
+    }
+    pub fn angle(mut self, angle: f32, variance: f32) -> Self {
+        self.emitter.angle = angle;
+        self.emitter.angle_variance = variance;
+        self
+    }
+    pub fn gravity(mut self, x: f32, y: f32) -> Self {
+        self.emitter.gravity = (x, y);
+        self
+    }
+    pub fn offset(mut self, x: ValueRange, y: ValueRange) -> Self {
+        self.emitter.offset = (x, y);
+        self
+    }
+    pub fn rotation(mut self, rotation: ValueRange, add: ValueRange) -> Self {
+        self.emitter.rotation = rotation;
+        self.emitter.rotation_add = add;
+        self
+    }
+    pub fn start_scale(mut self, x: ValueRange, y: ValueRange) -> Self {
+        self.emitter.start_scale = (x, y);
+        self
+    }
+    pub fn end_scale(mut self, x: ValueRange, y: ValueRange) -> Self {
+        self.emitter.end_scale = Some((x, y));
+        self
+    }
+    pub fn start_color(mut self, color: ColorRange) -> Self {
+        self.emitter.start_color = color;
+        self
+    }
+    pub fn end_color(mut self, color: ColorRange) -> Self {
+        self.emitter.end_color = Some(color);
+        self
+    }
+    pub fn alpha_fade(mut self, fade_in: f32, fade_out: f32) -> Self {
+        self.emitter.alpha_fade = Some((fade_in, fade_out));
+        self
+    }
+
+    pub fn alpha_blend(mut self, alpha_blend: AlphaBlend) -> Self {
+        self.emitter.alpha_blend = alpha_blend;
+        self
+    }
+
+    pub fn build(self) -> Emitter {
+        self.emitter
+    }
+}
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+//----------------------------------------------------
+// ビットマップフォント
+// 一文字をセル名にしたセルマップをフォントとして扱う
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BitmapFont {
+    map_id: usize,
+    line_height: f32, // 一番高い文字の高さ
+    glyphs: Vec<Glyph>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct Glyph {
+    character: char,
+    cell_id: usize,
+    width: f32,
+}
+
+impl BitmapFont {
+    pub fn map_id(&self) -> usize {
+        self.map_id
+    }
+
+    pub fn line_height(&self) -> f32 {
+        self.line_height
+    }
+
+    pub fn glyph(&self, character: char) -> Option<&Glyph> {
+        self.glyphs
+            .iter()
+            .find(|glyph| glyph.character == character)
+    }
+}
+
+impl Glyph {
+    pub fn character(&self) -> char {
+        self.character
+    }
+
+    pub fn cell_id(&self) -> usize {
+        self.cell_id
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+}
+
+//----------------------------------------------------
+// データ参照のみのためビルダーパターン
+#[cfg(feature = "builder")]
+pub struct BitmapFontBuilder {
+    map_id: usize,
+    line_height: f32,
+    glyphs: Vec<Glyph>,
+}
+
+#[cfg(feature = "builder")]
+impl BitmapFontBuilder {
+    pub fn new(map_id: usize) -> Self {
+        BitmapFontBuilder {
+            map_id,
+            line_height: 0.,
+            glyphs: vec![],
+        }
+    }
+
+    pub fn add_glyph(
+        mut self,
+        character: char,
+        cell_id: usize,
+        (width, height): (f32, f32),
+    ) -> Self {
+        self.line_height = self.line_height.max(height);
+        self.glyphs.push(Glyph {
+            character,
+            cell_id,
+            width,
+        });
+        self
+    }
+
+    pub fn build(self) -> BitmapFont {
+        BitmapFont {
+            map_id: self.map_id,
+            line_height: self.line_height,
+            glyphs: self.glyphs,
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use crate::types::cell::Cell;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+//----------------------------------------------------
+// メッシュ化されたセルの形状
+// SpriteStudio ではメッシュはセルマップ側に定義されている
+// 形状は毎フレーム作るノードと共有するので Arc で持つ
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Mesh {
+    cell: Cell,
+    vertices: Arc<Vec<[f32; 2]>>, // セルの原点を基準とした上方向が正の座標
+    tex_coords: Arc<Vec<[f32; 2]>>, // セルの左上を 0.0, 右下を 1.0 とした位置
+    indices: Arc<Vec<u32>>,       // 三角形リストの頂点番号
+}
+
+impl Mesh {
+    pub fn cell(&self) -> &Cell {
+        &self.cell
+    }
+
+    pub fn vertices(&self) -> &Arc<Vec<[f32; 2]>> {
+        &self.vertices
+    }
+
+    pub fn tex_coords(&self) -> &Arc<Vec<[f32; 2]>> {
+        &self.tex_coords
+    }
+
+    pub fn indices(&self) -> &Arc<Vec<u32>> {
+        &self.indices
+    }
+}
+
+//----------------------------------------------------
+// データ参照のみのためビルダーパターン
+#[cfg(feature = "builder")]
+pub struct MeshBuilder {
+    cell: Cell,
+    vertices: Vec<[f32; 2]>,
+    tex_coords: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+}
+
+#[cfg(feature = "builder")]
+impl MeshBuilder {
+    pub fn new(cell: Cell) -> Self {
+        MeshBuilder {
+            cell,
+            vertices: vec![],
+            tex_coords: vec![],
+            indices: vec![],
+        }
+    }
+
+    pub fn add_vertex(mut self, vertex: (f32, f32), tex_coord: (f32, f32)) -> Self {
+        self.vertices.push([vertex.0, vertex.1]);
+        self.tex_coords.push([tex_coord.0, tex_coord.1]);
+        self
+    }
+
+    pub fn add_triangle(mut self, triangle: (u32, u32, u32)) -> Self {
+        self.indices
+            .extend_from_slice(&[triangle.0, triangle.1, triangle.2]);
+        self
+    }
+
+    pub fn build(self) -> Mesh {
+        Mesh {
+            cell: self.cell,
+            vertices: Arc::new(self.vertices),
+            tex_coords: Arc::new(self.tex_coords),
+            indices: Arc::new(self.indices),
+        }
+    }
+}
@@ -16,9 +16,46 @@ where
     },
     PackName(P),
     AnimName(A),
+    // パーツ番号での参照には対応していないので解決しない
     WithPartId {
         pack: String,
         animation: A,
         part_id: u32,
     },
+    // 別ファイルのアニメーションを参照する
+    // file は AnimationData の参照ファイルの並び(読み込み時にファイルIDに解決済み)
+    File {
+        file: usize,
+        pack: P,
+        animation: A,
+    },
+}
+
+impl<P, A> AnimationName<P, A>
+where
+    P: AnimationKey,
+    A: AnimationKey,
+{
+    // 参照先のパックとアニメーションのキーを取得
+    // 指定されていない方は現在再生中のキーを使う
+    // パーツ番号での参照は解決できないので None
+    pub fn pack_animation(&self, current_pack: &P, current_animation: &A) -> Option<(P, A)> {
+        match self {
+            AnimationName::FullName { pack, animation }
+            | AnimationName::File {
+                pack, animation, ..
+            } => Some((*pack, *animation)),
+            AnimationName::PackName(pack) => Some((*pack, *current_animation)),
+            AnimationName::AnimName(animation) => Some((*current_pack, *animation)),
+            AnimationName::WithPartId { .. } => None,
+        }
+    }
+
+    // 別ファイルを参照している場合は参照ファイルの番号
+    pub fn file_index(&self) -> Option<usize> {
+        match self {
+            AnimationName::File { file, .. } => Some(*file),
+            _ => None,
+        }
+    }
 }
@@ -1,20 +1,57 @@
 use super::{animation::Animation, part::Part};
 use crate::traits::AnimationKey;
+use amethyst::core::math::Matrix4;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
+// バインドポーズは読み込み時にセットアップ情報から計算するので保存しない
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(
+    from = "PackData<U, P, A>",
+    bound(deserialize = "PackData<U, P, A>: Deserialize<'de>")
+)]
 pub struct Pack<U, P, A>
 where
     P: AnimationKey,
     A: AnimationKey,
 {
-    #[serde(bound(deserialize = "Vec<Part<P, A>>: Deserialize<'de>"))]
     parts: Vec<Part<P, A>>,
-    #[serde(bound(deserialize = "BTreeMap<A, Animation<U>>: Deserialize<'de>"))]
     animations: BTreeMap<A, Animation<U>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     setup: Option<Animation<U>>, // 各パーツのセットアップ情報
+    #[serde(skip)]
+    bind_poses: Option<BindPoses>,
+}
+
+// 保存されている Pack の内容
+#[derive(Deserialize)]
+struct PackData<U, P, A>
+where
+    P: AnimationKey,
+    A: AnimationKey,
+{
+    #[serde(bound(deserialize = "Vec<Part<P, A>>: Deserialize<'de>"))]
+    parts: Vec<Part<P, A>>,
+    #[serde(bound(deserialize = "BTreeMap<A, Animation<U>>: Deserialize<'de>"))]
+    animations: BTreeMap<A, Animation<U>>,
+    setup: Option<Animation<U>>,
+}
+
+impl<U, P, A> From<PackData<U, P, A>> for Pack<U, P, A>
+where
+    P: AnimationKey,
+    A: AnimationKey,
+{
+    fn from(data: PackData<U, P, A>) -> Self {
+        Pack::new(data.parts, data.animations, data.setup)
+    }
+}
+
+// スキニングに使うバインドポーズの行列とその逆行列(パーツ番号順)
+#[derive(Debug)]
+struct BindPoses {
+    matrices: Vec<Matrix4<f32>>,
+    inverse_matrices: Vec<Matrix4<f32>>,
 }
 
 impl<U, P, A> Pack<U, P, A>
@@ -22,10 +59,41 @@ where
     P: AnimationKey,
     A: AnimationKey,
 {
+    // ボーンの影響を受けるメッシュがある場合だけバインドポーズを計算しておく
+    fn new(
+        parts: Vec<Part<P, A>>,
+        animations: BTreeMap<A, Animation<U>>,
+        setup: Option<Animation<U>>,
+    ) -> Self {
+        let mut pack = Pack {
+            parts,
+            animations,
+            setup,
+            bind_poses: None,
+        };
+        if pack
+            .parts
+            .iter()
+            .any(|part| !part.mesh_weights().is_empty())
+        {
+            pack.bind_poses = pack.make_bind_poses();
+        }
+        pack
+    }
+
     pub fn parts(&self) -> impl Iterator<Item = &Part<P, A>> {
         self.parts.iter()
     }
 
+    pub fn part(&self, part_id: usize) -> Option<&Part<P, A>> {
+        self.parts.get(part_id)
+    }
+
+    // パーツ名からパーツ番号を取得
+    pub fn part_id(&self, name: &str) -> Option<usize> {
+        self.parts.iter().position(|part| part.name() == name)
+    }
+
     pub fn animation(&self, animation: &A) -> Option<&Animation<U>> {
         self.animations.get(animation)
     }
@@ -33,6 +101,47 @@ where
     pub fn setup_info(&self) -> Option<&Animation<U>> {
         self.setup.as_ref()
     }
+
+    // セットアップの先頭フレームをバインドポーズとした各パーツの行列
+    // ボーンの影響を受けるメッシュがなければ None
+    pub fn bind_matrices(&self) -> Option<&[Matrix4<f32>]> {
+        self.bind_poses
+            .as_ref()
+            .map(|bind_poses| bind_poses.matrices.as_slice())
+    }
+
+    // バインドポーズの逆行列．逆行列がない場合は単位行列
+    pub fn inverse_bind_matrices(&self) -> Option<&[Matrix4<f32>]> {
+        self.bind_poses
+            .as_ref()
+            .map(|bind_poses| bind_poses.inverse_matrices.as_slice())
+    }
+
+    // ルートの移動値は再生時と同じくゼロとする
+    fn make_bind_poses(&self) -> Option<BindPoses> {
+        let setup = self.setup.as_ref()?;
+        let mut matrices: Vec<Matrix4<f32>> = Vec::with_capacity(self.parts.len());
+        for (part_id, part) in self.parts.iter().enumerate() {
+            let parent_matrix = part
+                .parent_id()
+                .and_then(|p| matrices.get(p as usize))
+                .copied()
+                .unwrap_or_else(Matrix4::identity);
+            let mut local_transform = setup.local_transform(part_id, 0);
+            if part_id == crate::constant::ROOT_PART_ID {
+                local_transform.set_translation_xyz(0., 0., 0.);
+            }
+            matrices.push(parent_matrix * local_transform.matrix());
+        }
+        let inverse_matrices = matrices
+            .iter()
+            .map(|matrix| matrix.try_inverse().unwrap_or_else(Matrix4::identity))
+            .collect();
+        Some(BindPoses {
+            matrices,
+            inverse_matrices,
+        })
+    }
 }
 
 #[cfg(feature = "builder")]
@@ -65,11 +174,7 @@ where
     }
 
     pub fn build(self) -> Pack<U, P, A> {
-        Pack {
-            parts: self.parts,
-            animations: self.animations,
-            setup: self.setup,
-        }
+        Pack::new(self.parts, self.animations, self.setup)
     }
 }
 
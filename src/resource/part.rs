@@ -1,7 +1,7 @@
 use super::name::AnimationName;
 use crate::{
     traits::AnimationKey,
-    types::{bound_type::Bounds, part_type::PartType},
+    types::{bound_type::Bounds, part_type::PartType, AlphaBlend, BoneWeight, Inheritance},
 };
 use serde::{Deserialize, Serialize};
 
@@ -26,6 +26,22 @@ where
     part_type: PartType,
     #[serde(skip_serializing_if = "Option::is_none")]
     bounds: Option<Bounds>,
+    // マスクパーツの影響を受けるか(SpriteStudio と同じく省略時は受ける)
+    #[serde(default = "default_mask_influence")]
+    mask_influence: bool,
+    // メッシュの頂点ごとのボーンの影響
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    mesh_weights: Vec<Vec<BoneWeight>>,
+    // 描画先との合成方法
+    #[serde(default)]
+    alpha_blend: AlphaBlend,
+    // 親パーツから引き継ぐ値
+    #[serde(default)]
+    inheritance: Inheritance,
+}
+
+fn default_mask_influence() -> bool {
+    true
 }
 
 impl<P, A> Part<P, A>
@@ -44,6 +60,35 @@ where
     pub fn refference_animation_name(&self) -> Option<&AnimationName<P, A>> {
         self.refference_animation_name.as_ref()
     }
+
+    pub fn refference_effect_index(&self) -> Option<usize> {
+        self.refference_effect_index
+    }
+
+    pub fn part_type(&self) -> PartType {
+        self.part_type
+    }
+
+    // 当たり判定の形状
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    pub fn mask_influence(&self) -> bool {
+        self.mask_influence
+    }
+
+    pub fn mesh_weights(&self) -> &[Vec<BoneWeight>] {
+        &self.mesh_weights
+    }
+
+    pub fn alpha_blend(&self) -> AlphaBlend {
+        self.alpha_blend
+    }
+
+    pub fn inheritance(&self) -> Inheritance {
+        self.inheritance
+    }
 }
 
 //----------------------------------------------------
@@ -60,6 +105,10 @@ where
     refference_effect_index: Option<usize>,
     part_type: PartType,
     bounds: Option<Bounds>,
+    mask_influence: bool,
+    mesh_weights: Vec<Vec<BoneWeight>>,
+    alpha_blend: AlphaBlend,
+    inheritance: Inheritance,
 }
 
 #[cfg(feature = "builder")]
@@ -76,6 +125,10 @@ where
             refference_effect_index: None,
             part_type,
             bounds: None,
+            mask_influence: true,
+            mesh_weights: vec![],
+            alpha_blend: AlphaBlend::Mix,
+            inheritance: Inheritance::default(),
         }
     }
 
@@ -92,6 +145,12 @@ where
         self
     }
 
+    // パック名のみ，アニメーション名のみ，別ファイル参照などを指定する場合
+    pub fn refference_animation(mut self, name: AnimationName<P, A>) -> Self {
+        self.refference_animation_name = Some(name);
+        self
+    }
+
     pub fn refference_effect_index(mut self, index: usize) -> Self {
         self.refference_effect_index = Some(index);
         self
@@ -102,6 +161,27 @@ where
         self
     }
 
+    pub fn mask_influence(mut self, mask_influence: bool) -> Self {
+        self.mask_influence = mask_influence;
+        self
+    }
+
+    // 頂点番号順のボーンの影響
+    pub fn mesh_weights(mut self, mesh_weights: Vec<Vec<BoneWeight>>) -> Self {
+        self.mesh_weights = mesh_weights;
+        self
+    }
+
+    pub fn alpha_blend(mut self, alpha_blend: AlphaBlend) -> Self {
+        self.alpha_blend = alpha_blend;
+        self
+    }
+
+    pub fn inheritance(mut self, inheritance: Inheritance) -> Self {
+        self.inheritance = inheritance;
+        self
+    }
+
     pub fn build(self) -> Part<P, A> {
         Part {
             name: self.name,
@@ -110,6 +190,10 @@ where
             refference_effect_index: self.refference_effect_index,
             part_type: self.part_type,
             bounds: self.bounds,
+            mask_influence: self.mask_influence,
+            mesh_weights: self.mesh_weights,
+            alpha_blend: self.alpha_blend,
+            inheritance: self.inheritance,
         }
     }
 }
@@ -2,8 +2,11 @@ use super::timeline::TimeLine;
 #[cfg(feature = "builder")]
 use super::timeline::TimeLineBuilder;
 #[cfg(feature = "builder")]
-use crate::types::interpolate::Interpolation;
-use crate::types::{cell::Cell, EffectKey, InstanceKey, LinearColor, VertexKey};
+use crate::types::interpolate::KeyInterpolation;
+use crate::types::{
+    cell::Cell, ColorBlend, DeformKey, EffectKey, InstanceKey, LinearColor, TextKey,
+    VertexColorKey, VertexKey,
+};
 use amethyst::{
     core::{
         math::{Translation3, UnitQuaternion, Vector3},
@@ -12,6 +15,7 @@ use amethyst::{
     renderer::resources::Tint,
 };
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
 
 // パーツごとのタイムライン
 #[derive(Debug, Serialize, Deserialize)]
@@ -83,6 +87,22 @@ pub struct PartTimeline<U> {
         skip_serializing_if = "TimeLine::is_empty"
     )]
     color: TimeLine<LinearColor>,
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    color_rate: TimeLine<f32>,
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    color_blend: TimeLine<ColorBlend>,
+    // 頂点ごとの色情報
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    vertex_color: TimeLine<VertexColorKey>,
 
     // カスタムデータ
     #[serde(
@@ -110,6 +130,98 @@ pub struct PartTimeline<U> {
         skip_serializing_if = "TimeLine::is_empty"
     )]
     effect: TimeLine<EffectKey>,
+
+    // メッシュ頂点変形キー
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    deform: TimeLine<DeformKey>,
+
+    // テキスト表示キー
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    text: TimeLine<TextKey>,
+
+    // 原点オフセット
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    pivot_x: TimeLine<f32>,
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    pivot_y: TimeLine<f32>,
+    // サイズ
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    size_x: TimeLine<f32>,
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    size_y: TimeLine<f32>,
+    // UV 変形
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    uv_x: TimeLine<f32>,
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    uv_y: TimeLine<f32>,
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    uv_rotated: TimeLine<f32>,
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    uv_scale_x: TimeLine<f32>,
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    uv_scale_y: TimeLine<f32>,
+    // ローカルスケール(子パーツには継承しない)
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    local_scale_x: TimeLine<f32>,
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    local_scale_y: TimeLine<f32>,
+    // ローカル不透明度(子パーツには継承しない)
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    local_alpha: TimeLine<f32>,
+    // 描画優先度
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    priority: TimeLine<f32>,
+    // 当たり判定の円の半径
+    #[serde(
+        default = "TimeLine::default",
+        skip_serializing_if = "TimeLine::is_empty"
+    )]
+    bounding_radius: TimeLine<f32>,
 }
 
 impl<U> PartTimeline<U> {
@@ -126,17 +238,17 @@ impl<U> PartTimeline<U> {
     // パーツのローカル座標を取得
     // 座標に関連するフレームは常に存在するはずなので，存在しなければクラッシュ
     pub fn local_transform(&self, frame: usize) -> Transform {
-        let pos_x = self.pos_x.get_interpolation_key(frame).unwrap_or(0.);
+        let pos_x = self.pos_x.get_scalar_key(frame).unwrap_or(0.);
         log::trace!("\tpos_x: {}", pos_x);
-        let pos_y = self.pos_y.get_interpolation_key(frame).unwrap_or(0.);
+        let pos_y = self.pos_y.get_scalar_key(frame).unwrap_or(0.);
         log::trace!("\tpos_y: {}", pos_y);
-        let pos_z = self.pos_z.get_interpolation_key(frame).unwrap_or(0.);
+        let pos_z = self.pos_z.get_scalar_key(frame).unwrap_or(0.);
         log::trace!("\tpos_z: {}", pos_z);
-        let scale_x = self.scale_x.get_interpolation_key(frame).unwrap_or(1.);
+        let scale_x = self.scale_x.get_scalar_key(frame).unwrap_or(1.);
         log::trace!("\tscale_x: {}", scale_x);
-        let scale_y = self.scale_y.get_interpolation_key(frame).unwrap_or(1.);
+        let scale_y = self.scale_y.get_scalar_key(frame).unwrap_or(1.);
         log::trace!("\tscale_y: {}", scale_y);
-        let rotated = self.rotated.get_interpolation_key(frame).unwrap_or(0.);
+        let rotated = self.rotated.get_scalar_key(frame).unwrap_or(0.);
         log::trace!("\trotated: {}", rotated);
 
         let position = Translation3::new(pos_x, pos_y, pos_z);
@@ -146,16 +258,73 @@ impl<U> PartTimeline<U> {
         Transform::new(position, rotation, scale)
     }
 
+    // 反転情報取得(左右，上下)
+    // キーフレームがなければ反転なし
+    pub fn flip(&self, frame: usize) -> (bool, bool) {
+        let flip_h = self.flip_h.get_step_key(frame).map(|v| *v).unwrap_or(false);
+        let flip_v = self.flip_v.get_step_key(frame).map(|v| *v).unwrap_or(false);
+        (flip_h, flip_v)
+    }
+
     // スプライトの色情報取得
-    // キーフレームがなければ色変更なし
+    // テクスチャの色にかける値．キーフレームがなければ色変更なし
     pub fn color(&self, frame: usize) -> Tint {
-        let LinearColor(r, g, b, _) = self
+        let (LinearColor(r, g, b, a), _) = self.blend_color(frame);
+        let alpha = self.alpha.get_scalar_key(frame).unwrap_or(1.);
+
+        LinearColor(r, g, b, a * alpha).into()
+    }
+
+    // テクスチャの色に足す値(アルファは常に 0)
+    // 乗算以外のカラーブレンドで使われる
+    pub fn color_offset(&self, frame: usize) -> LinearColor {
+        let (_, offset) = self.blend_color(frame);
+        offset
+    }
+
+    // 頂点ごとの色情報取得(左上，右上，左下，右下の順に乗算成分と加算成分)
+    // パーツ全体の色のキーの方が新しければ頂点ごとの色は使わない
+    pub fn vertex_colors(&self, frame: usize) -> Option<[(LinearColor, LinearColor); 4]> {
+        let (vertex_frame, _) = self.vertex_color.get_step_key_with_frame(frame)?;
+        if let Some((color_frame, _)) = self.color.get_step_key_with_frame(frame) {
+            if color_frame > vertex_frame {
+                return None;
+            }
+        }
+
+        let key = self.vertex_color.get_interpolation_key(frame)?;
+        let blend = self
+            .color_blend
+            .get_step_key(frame)
+            .map(|v| *v)
+            .unwrap_or_default();
+        let alpha = self.alpha.get_scalar_key(frame).unwrap_or(1.);
+        let split = |(color, rate)| {
+            let (LinearColor(r, g, b, a), offset) = blend.split(color, rate);
+            (LinearColor(r, g, b, a * alpha), offset)
+        };
+        Some([
+            split(key.lt()),
+            split(key.rt()),
+            split(key.lb()),
+            split(key.rb()),
+        ])
+    }
+
+    // パーツカラーを合成方法に合わせて乗算成分と加算成分に分ける
+    // 割合のキーがなければ色をそのまま適用する
+    fn blend_color(&self, frame: usize) -> (LinearColor, LinearColor) {
+        let color = self
             .color
             .get_interpolation_key(frame)
             .unwrap_or(LinearColor(1., 1., 1., 1.));
-        let alpha = self.alpha.get_interpolation_key(frame).unwrap_or(1.);
-
-        LinearColor(r, g, b, alpha).into()
+        let rate = self.color_rate.get_scalar_key(frame).unwrap_or(1.);
+        let blend = self
+            .color_blend
+            .get_step_key(frame)
+            .map(|v| *v)
+            .unwrap_or_default();
+        blend.split(color, rate)
     }
 
     // ユーザーパラメータの取得
@@ -163,16 +332,82 @@ impl<U> PartTimeline<U> {
         self.user.get_step_key(frame)
     }
 
+    // 範囲内のフレームにあるユーザーパラメータのキー
+    pub fn user_keys(&self, frames: Range<usize>) -> impl Iterator<Item = (usize, &U)> {
+        self.user.keys_in_range(frames)
+    }
+
     pub fn instance(&self, frame: usize) -> Option<(usize, &InstanceKey)> {
         self.instance.get_step_key_with_frame(frame)
     }
 
+    // 範囲内のフレームにあるインスタンスのキー
+    pub fn instance_keys(
+        &self,
+        frames: Range<usize>,
+    ) -> impl Iterator<Item = (usize, &InstanceKey)> {
+        self.instance.keys_in_range(frames)
+    }
+
     pub fn vertex(&self, frame: usize) -> Option<VertexKey> {
         self.vertex.get_interpolation_key(frame)
     }
 
-    pub fn effect(&self, frame: usize) -> Option<&EffectKey> {
-        self.effect.get_step_key(frame)
+    pub fn effect(&self, frame: usize) -> Option<(usize, &EffectKey)> {
+        self.effect.get_step_key_with_frame(frame)
+    }
+
+    pub fn deform(&self, frame: usize) -> Option<DeformKey> {
+        self.deform.get_deform_key(frame)
+    }
+
+    pub fn text(&self, frame: usize) -> Option<&TextKey> {
+        self.text.get_step_key(frame)
+    }
+
+    // 原点オフセット(セルのサイズに対する割合．上方向が正)
+    pub fn pivot_offset(&self, frame: usize) -> (f32, f32) {
+        let pivot_x = self.pivot_x.get_scalar_key(frame).unwrap_or(0.);
+        let pivot_y = self.pivot_y.get_scalar_key(frame).unwrap_or(0.);
+        (pivot_x, pivot_y)
+    }
+
+    // 描画サイズ．キーがなければセルのサイズのまま
+    pub fn size(&self, frame: usize) -> (Option<f32>, Option<f32>) {
+        (
+            self.size_x.get_scalar_key(frame),
+            self.size_y.get_scalar_key(frame),
+        )
+    }
+
+    // UV の移動量(テクスチャ座標)，回転(度)，スケール
+    // 回転とスケールはセルの中心が基準
+    pub fn uv_transform(&self, frame: usize) -> ((f32, f32), f32, (f32, f32)) {
+        let uv_x = self.uv_x.get_scalar_key(frame).unwrap_or(0.);
+        let uv_y = self.uv_y.get_scalar_key(frame).unwrap_or(0.);
+        let uv_rotated = self.uv_rotated.get_scalar_key(frame).unwrap_or(0.);
+        let uv_scale_x = self.uv_scale_x.get_scalar_key(frame).unwrap_or(1.);
+        let uv_scale_y = self.uv_scale_y.get_scalar_key(frame).unwrap_or(1.);
+        ((uv_x, uv_y), uv_rotated, (uv_scale_x, uv_scale_y))
+    }
+
+    pub fn local_scale(&self, frame: usize) -> (f32, f32) {
+        let scale_x = self.local_scale_x.get_scalar_key(frame).unwrap_or(1.);
+        let scale_y = self.local_scale_y.get_scalar_key(frame).unwrap_or(1.);
+        (scale_x, scale_y)
+    }
+
+    pub fn local_alpha(&self, frame: usize) -> f32 {
+        self.local_alpha.get_scalar_key(frame).unwrap_or(1.)
+    }
+
+    pub fn priority(&self, frame: usize) -> f32 {
+        self.priority.get_scalar_key(frame).unwrap_or(0.)
+    }
+
+    // キーがなければ None
+    pub fn bounding_radius(&self, frame: usize) -> Option<f32> {
+        self.bounding_radius.get_scalar_key(frame)
     }
 }
 
@@ -197,6 +432,9 @@ pub(crate) struct PartTimelineBuilder<U> {
     // 色情報
     alpha: TimeLineBuilder<f32>,
     color: TimeLineBuilder<LinearColor>,
+    color_rate: TimeLineBuilder<f32>,
+    color_blend: TimeLineBuilder<ColorBlend>,
+    vertex_color: TimeLineBuilder<VertexColorKey>,
 
     // カスタムデータ
     user: TimeLineBuilder<U>,
@@ -209,6 +447,34 @@ pub(crate) struct PartTimelineBuilder<U> {
 
     // エフェクト再生キー
     effect: TimeLineBuilder<EffectKey>,
+
+    // メッシュ頂点変形キー
+    deform: TimeLineBuilder<DeformKey>,
+
+    // テキスト表示キー
+    text: TimeLineBuilder<TextKey>,
+
+    // 原点オフセット
+    pivot_x: TimeLineBuilder<f32>,
+    pivot_y: TimeLineBuilder<f32>,
+    // サイズ
+    size_x: TimeLineBuilder<f32>,
+    size_y: TimeLineBuilder<f32>,
+    // UV 変形
+    uv_x: TimeLineBuilder<f32>,
+    uv_y: TimeLineBuilder<f32>,
+    uv_rotated: TimeLineBuilder<f32>,
+    uv_scale_x: TimeLineBuilder<f32>,
+    uv_scale_y: TimeLineBuilder<f32>,
+    // ローカルスケール(子パーツには継承しない)
+    local_scale_x: TimeLineBuilder<f32>,
+    local_scale_y: TimeLineBuilder<f32>,
+    // ローカル不透明度(子パーツには継承しない)
+    local_alpha: TimeLineBuilder<f32>,
+    // 描画優先度
+    priority: TimeLineBuilder<f32>,
+    // 当たり判定の円の半径
+    bounding_radius: TimeLineBuilder<f32>,
 }
 
 #[cfg(feature = "builder")]
@@ -232,6 +498,9 @@ impl<U> PartTimelineBuilder<U> {
             // 色情報
             alpha: TimeLineBuilder::new(),
             color: TimeLineBuilder::new(),
+            color_rate: TimeLineBuilder::new(),
+            color_blend: TimeLineBuilder::new(),
+            vertex_color: TimeLineBuilder::new(),
             // カスタムデータ
             user: TimeLineBuilder::new(),
             // アニメーションインスタンス
@@ -240,53 +509,163 @@ impl<U> PartTimelineBuilder<U> {
             vertex: TimeLineBuilder::new(),
             // エフェクト再生キー
             effect: TimeLineBuilder::new(),
+            // メッシュ頂点変形キー
+            deform: TimeLineBuilder::new(),
+            // テキスト表示キー
+            text: TimeLineBuilder::new(),
+            // 原点オフセット
+            pivot_x: TimeLineBuilder::new(),
+            pivot_y: TimeLineBuilder::new(),
+            // サイズ
+            size_x: TimeLineBuilder::new(),
+            size_y: TimeLineBuilder::new(),
+            // UV 変形
+            uv_x: TimeLineBuilder::new(),
+            uv_y: TimeLineBuilder::new(),
+            uv_rotated: TimeLineBuilder::new(),
+            uv_scale_x: TimeLineBuilder::new(),
+            uv_scale_y: TimeLineBuilder::new(),
+            // ローカルスケール(子パーツには継承しない)
+            local_scale_x: TimeLineBuilder::new(),
+            local_scale_y: TimeLineBuilder::new(),
+            // ローカル不透明度(子パーツには継承しない)
+            local_alpha: TimeLineBuilder::new(),
+            // 描画優先度
+            priority: TimeLineBuilder::new(),
+            // 当たり判定の円の半径
+            bounding_radius: TimeLineBuilder::new(),
         }
     }
 
-    pub fn add_hide(&mut self, frame: usize, interpolation: Interpolation, hide: bool) {
+    pub fn add_hide(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        hide: bool,
+    ) {
         self.hide.add_key(frame, interpolation, hide);
     }
 
-    pub fn add_cell(&mut self, frame: usize, interpolation: Interpolation, cell: Cell) {
+    pub fn add_cell(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        cell: Cell,
+    ) {
         self.cell.add_key(frame, interpolation, cell);
     }
 
-    pub fn add_pos_x(&mut self, frame: usize, interpolation: Interpolation, pos_x: f32) {
+    pub fn add_pos_x(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        pos_x: f32,
+    ) {
         self.pos_x.add_key(frame, interpolation, pos_x);
     }
-    pub fn add_pos_y(&mut self, frame: usize, interpolation: Interpolation, pos_y: f32) {
+    pub fn add_pos_y(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        pos_y: f32,
+    ) {
         self.pos_y.add_key(frame, interpolation, pos_y);
     }
-    pub fn add_pos_z(&mut self, frame: usize, interpolation: Interpolation, pos_z: f32) {
+    pub fn add_pos_z(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        pos_z: f32,
+    ) {
         self.pos_z.add_key(frame, interpolation, pos_z);
     }
 
-    pub fn add_scale_x(&mut self, frame: usize, interpolation: Interpolation, scale_x: f32) {
+    pub fn add_scale_x(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        scale_x: f32,
+    ) {
         self.scale_x.add_key(frame, interpolation, scale_x);
     }
-    pub fn add_scale_y(&mut self, frame: usize, interpolation: Interpolation, scale_y: f32) {
+    pub fn add_scale_y(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        scale_y: f32,
+    ) {
         self.scale_y.add_key(frame, interpolation, scale_y);
     }
-    pub fn add_rotated(&mut self, frame: usize, interpolation: Interpolation, rotated: f32) {
+    pub fn add_rotated(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        rotated: f32,
+    ) {
         self.rotated.add_key(frame, interpolation, rotated);
     }
     // 反転情報
-    pub fn add_flip_v(&mut self, frame: usize, interpolation: Interpolation, flip_v: bool) {
+    pub fn add_flip_v(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        flip_v: bool,
+    ) {
         self.flip_v.add_key(frame, interpolation, flip_v);
     }
-    pub fn add_flip_h(&mut self, frame: usize, interpolation: Interpolation, flip_h: bool) {
+    pub fn add_flip_h(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        flip_h: bool,
+    ) {
         self.flip_h.add_key(frame, interpolation, flip_h);
     }
     // 色情報
-    pub fn add_alpha(&mut self, frame: usize, interpolation: Interpolation, alpha: f32) {
+    pub fn add_alpha(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        alpha: f32,
+    ) {
         self.alpha.add_key(frame, interpolation, alpha);
     }
-    pub fn add_color(&mut self, frame: usize, interpolation: Interpolation, color: LinearColor) {
+    pub fn add_color(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        color: LinearColor,
+    ) {
         self.color.add_key(frame, interpolation, color);
     }
+    pub fn add_color_rate(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        rate: f32,
+    ) {
+        self.color_rate.add_key(frame, interpolation, rate);
+    }
+    pub fn add_color_blend(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        blend: ColorBlend,
+    ) {
+        self.color_blend.add_key(frame, interpolation, blend);
+    }
+    pub fn add_vertex_color(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        vertex_color: VertexColorKey,
+    ) {
+        self.vertex_color
+            .add_key(frame, interpolation, vertex_color);
+    }
 
     // カスタムデータ
-    pub fn add_user(&mut self, frame: usize, interpolation: Interpolation, user: U) {
+    pub fn add_user(&mut self, frame: usize, interpolation: impl Into<KeyInterpolation>, user: U) {
         self.user.add_key(frame, interpolation, user);
     }
 
@@ -294,22 +673,174 @@ impl<U> PartTimelineBuilder<U> {
     pub fn add_instance(
         &mut self,
         frame: usize,
-        interpolation: Interpolation,
+        interpolation: impl Into<KeyInterpolation>,
         instance: InstanceKey,
     ) {
         self.instance.add_key(frame, interpolation, instance);
     }
 
     // 頂点アニメーションキー
-    pub fn add_vertex(&mut self, frame: usize, interpolation: Interpolation, vertex: VertexKey) {
+    pub fn add_vertex(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        vertex: VertexKey,
+    ) {
         self.vertex.add_key(frame, interpolation, vertex);
     }
 
     // エフェクト再生キー
-    pub fn add_effect(&mut self, frame: usize, interpolation: Interpolation, effect: EffectKey) {
+    pub fn add_effect(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        effect: EffectKey,
+    ) {
         self.effect.add_key(frame, interpolation, effect);
     }
 
+    // メッシュ頂点変形キー
+    pub fn add_deform(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        deform: DeformKey,
+    ) {
+        self.deform.add_key(frame, interpolation, deform);
+    }
+
+    // テキスト表示キー
+    pub fn add_text(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        text: TextKey,
+    ) {
+        self.text.add_key(frame, interpolation, text);
+    }
+
+    // 原点オフセット
+    pub fn add_pivot_x(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        pivot_x: f32,
+    ) {
+        self.pivot_x.add_key(frame, interpolation, pivot_x);
+    }
+    pub fn add_pivot_y(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        pivot_y: f32,
+    ) {
+        self.pivot_y.add_key(frame, interpolation, pivot_y);
+    }
+    // サイズ
+    pub fn add_size_x(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        size_x: f32,
+    ) {
+        self.size_x.add_key(frame, interpolation, size_x);
+    }
+    pub fn add_size_y(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        size_y: f32,
+    ) {
+        self.size_y.add_key(frame, interpolation, size_y);
+    }
+    // UV 変形
+    pub fn add_uv_x(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        uv_x: f32,
+    ) {
+        self.uv_x.add_key(frame, interpolation, uv_x);
+    }
+    pub fn add_uv_y(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        uv_y: f32,
+    ) {
+        self.uv_y.add_key(frame, interpolation, uv_y);
+    }
+    pub fn add_uv_rotated(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        uv_rotated: f32,
+    ) {
+        self.uv_rotated.add_key(frame, interpolation, uv_rotated);
+    }
+    pub fn add_uv_scale_x(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        uv_scale_x: f32,
+    ) {
+        self.uv_scale_x.add_key(frame, interpolation, uv_scale_x);
+    }
+    pub fn add_uv_scale_y(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        uv_scale_y: f32,
+    ) {
+        self.uv_scale_y.add_key(frame, interpolation, uv_scale_y);
+    }
+    // ローカルスケール(子パーツには継承しない)
+    pub fn add_local_scale_x(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        local_scale_x: f32,
+    ) {
+        self.local_scale_x
+            .add_key(frame, interpolation, local_scale_x);
+    }
+    pub fn add_local_scale_y(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        local_scale_y: f32,
+    ) {
+        self.local_scale_y
+            .add_key(frame, interpolation, local_scale_y);
+    }
+    // ローカル不透明度(子パーツには継承しない)
+    pub fn add_local_alpha(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        local_alpha: f32,
+    ) {
+        self.local_alpha.add_key(frame, interpolation, local_alpha);
+    }
+    // 描画優先度
+    pub fn add_priority(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        priority: f32,
+    ) {
+        self.priority.add_key(frame, interpolation, priority);
+    }
+    // 当たり判定の円の半径
+    pub fn add_bounding_radius(
+        &mut self,
+        frame: usize,
+        interpolation: impl Into<KeyInterpolation>,
+        radius: f32,
+    ) {
+        self.bounding_radius.add_key(frame, interpolation, radius);
+    }
+
     pub fn build(self) -> PartTimeline<U> {
         PartTimeline {
             // 表示ON/OFF
@@ -329,6 +860,9 @@ impl<U> PartTimelineBuilder<U> {
             // 色情報
             alpha: self.alpha.build(),
             color: self.color.build(),
+            color_rate: self.color_rate.build(),
+            color_blend: self.color_blend.build(),
+            vertex_color: self.vertex_color.build(),
             // カスタムデータ
             user: self.user.build(),
             // アニメーションインスタンス
@@ -337,6 +871,31 @@ impl<U> PartTimelineBuilder<U> {
             vertex: self.vertex.build(),
             // エフェクト再生キー
             effect: self.effect.build(),
+            // メッシュ頂点変形キー
+            deform: self.deform.build(),
+            // テキスト表示キー
+            text: self.text.build(),
+            // 原点オフセット
+            pivot_x: self.pivot_x.build(),
+            pivot_y: self.pivot_y.build(),
+            // サイズ
+            size_x: self.size_x.build(),
+            size_y: self.size_y.build(),
+            // UV 変形
+            uv_x: self.uv_x.build(),
+            uv_y: self.uv_y.build(),
+            uv_rotated: self.uv_rotated.build(),
+            uv_scale_x: self.uv_scale_x.build(),
+            uv_scale_y: self.uv_scale_y.build(),
+            // ローカルスケール(子パーツには継承しない)
+            local_scale_x: self.local_scale_x.build(),
+            local_scale_y: self.local_scale_y.build(),
+            // ローカル不透明度(子パーツには継承しない)
+            local_alpha: self.local_alpha.build(),
+            // 描画優先度
+            priority: self.priority.build(),
+            // 当たり判定の円の半径
+            bounding_radius: self.bounding_radius.build(),
         }
     }
 }
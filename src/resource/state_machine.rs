@@ -0,0 +1,207 @@
+use crate::traits::{animation_file::AnimationFile, AnimationKey, AnimationUser};
+use amethyst::{
+    assets::{Asset, Handle},
+    ecs::DenseVecStorage,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+//----------------------------------------------------
+// アニメーションのステートマシン
+// RON で記述してロードし，AnimationStateMachine コンポーネントから参照する
+// ステートは名前で参照し，遷移は記述順に評価して最初に条件を満たしたものを使う
+//
+// (
+//     initial: "idle",
+//     states: {
+//         "idle": (pack: Player, animation: Idle, transitions: [
+//             (to: "run", condition: Param("speed", Greater(0.1))),
+//             (to: "attack", condition: Trigger("attack"), blend: 0.1),
+//         ]),
+//         "run": (pack: Player, animation: Run, transitions: [
+//             (to: "idle", condition: Param("speed", LessEqual(0.1))),
+//         ]),
+//         "attack": (pack: Player, animation: Attack, transitions: [
+//             (to: "idle", condition: End),
+//         ]),
+//     },
+// )
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StateMachine<T>
+where
+    T: AnimationFile,
+{
+    initial: String, // 開始ステート名
+    #[serde(bound(
+        deserialize = "BTreeMap<String, State<T::UserData, T::PackKey, T::AnimationKey>>: Deserialize<'de>"
+    ))]
+    states: BTreeMap<String, State<T::UserData, T::PackKey, T::AnimationKey>>,
+}
+
+impl<T> StateMachine<T>
+where
+    T: AnimationFile,
+{
+    pub fn initial(&self) -> &str {
+        &self.initial
+    }
+
+    pub fn state(&self, name: &str) -> Option<&State<T::UserData, T::PackKey, T::AnimationKey>> {
+        self.states.get(name)
+    }
+
+    pub fn states(
+        &self,
+    ) -> impl Iterator<Item = (&String, &State<T::UserData, T::PackKey, T::AnimationKey>)> {
+        self.states.iter()
+    }
+}
+
+impl<T> Asset for StateMachine<T>
+where
+    T: 'static + Send + Sync + AnimationFile,
+{
+    const NAME: &'static str = "SPRITE_ANIMATION_STATE_MACHINE";
+
+    type Data = Self;
+    type HandleStorage = DenseVecStorage<Handle<Self>>;
+}
+
+//----------------------------------------------------
+// ステート．再生するアニメーションと遷移先
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound(deserialize = "Vec<Transition<U>>: Deserialize<'de>"))]
+pub struct State<U, P, A>
+where
+    U: AnimationUser,
+    P: AnimationKey,
+    A: AnimationKey,
+{
+    pack: P,
+    animation: A,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    transitions: Vec<Transition<U>>,
+}
+
+impl<U, P, A> State<U, P, A>
+where
+    U: AnimationUser,
+    P: AnimationKey,
+    A: AnimationKey,
+{
+    pub fn pack(&self) -> &P {
+        &self.pack
+    }
+
+    pub fn animation(&self) -> &A {
+        &self.animation
+    }
+
+    pub fn transitions(&self) -> impl Iterator<Item = &Transition<U>> {
+        self.transitions.iter()
+    }
+}
+
+//----------------------------------------------------
+// 遷移先のステート名と遷移条件
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound(deserialize = "Condition<U>: Deserialize<'de>"))]
+pub struct Transition<U>
+where
+    U: AnimationUser,
+{
+    to: String,
+    condition: Condition<U>,
+    #[serde(default)]
+    start_frame: usize, // 遷移先のアニメーションの開始フレーム
+    #[serde(default)]
+    blend: f32, // クロスフェードの秒数(0 の場合は即座に切り替える)
+}
+
+impl<U> Transition<U>
+where
+    U: AnimationUser,
+{
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+
+    pub fn condition(&self) -> &Condition<U> {
+        &self.condition
+    }
+
+    pub fn start_frame(&self) -> usize {
+        self.start_frame
+    }
+
+    pub fn blend(&self) -> f32 {
+        self.blend
+    }
+}
+
+//----------------------------------------------------
+// 遷移条件
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound(deserialize = "U: Deserialize<'de>"))]
+pub enum Condition<U>
+where
+    U: AnimationUser,
+{
+    End,                    // 再生終了
+    User(U),                // ルートパーツの現在のユーザーデータが一致
+    Param(String, Compare), // パラメータの比較
+    Trigger(String),        // トリガーが立っている(遷移したら消費する)
+    Not(Box<Condition<U>>), // 否定
+    All(Vec<Condition<U>>), // すべて満たす
+    Any(Vec<Condition<U>>), // どれかを満たす
+}
+
+//----------------------------------------------------
+// ゲーム側から設定するパラメータの値
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ParamValue {
+    Bool(bool),
+    Int(i32),
+    Float(f32),
+}
+
+impl ParamValue {
+    // 大小比較用の値
+    pub fn as_f32(&self) -> f32 {
+        match *self {
+            ParamValue::Bool(value) => {
+                if value {
+                    1.
+                } else {
+                    0.
+                }
+            }
+            ParamValue::Int(value) => value as f32,
+            ParamValue::Float(value) => value,
+        }
+    }
+}
+
+// パラメータの比較方法
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Compare {
+    Equal(ParamValue),
+    NotEqual(ParamValue),
+    Greater(f32),
+    GreaterEqual(f32),
+    Less(f32),
+    LessEqual(f32),
+}
+
+impl Compare {
+    pub fn test(&self, value: ParamValue) -> bool {
+        match *self {
+            Compare::Equal(other) => value == other,
+            Compare::NotEqual(other) => value != other,
+            Compare::Greater(other) => value.as_f32() > other,
+            Compare::GreaterEqual(other) => value.as_f32() >= other,
+            Compare::Less(other) => value.as_f32() < other,
+            Compare::LessEqual(other) => value.as_f32() <= other,
+        }
+    }
+}
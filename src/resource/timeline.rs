@@ -1,5 +1,14 @@
-use crate::{traits::interpolate::Interpolate, types::interpolate::Interpolation};
+#[cfg(feature = "builder")]
+use crate::types::interpolate::KeyInterpolation;
+use crate::{
+    traits::interpolate::Interpolate,
+    types::{
+        interpolate::{Curve, Interpolation},
+        DeformKey,
+    },
+};
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
 
 // KeyFrameのリストがタイムライン
 #[derive(Debug, Serialize, Deserialize)]
@@ -31,19 +40,20 @@ impl<T> TimeLine<T> {
             .map(|k| (k.frame, &k.value))
     }
 
+    // 指定範囲のフレームにあるキー(フレームを飛ばしてもイベントを取りこぼさないため)
+    pub fn keys_in_range(&self, frames: Range<usize>) -> impl Iterator<Item = (usize, &T)> {
+        self.key_frames
+            .iter()
+            .filter(move |k| frames.contains(&k.frame))
+            .map(|k| (k.frame, &k.value))
+    }
+
     // デシリアライズ時に存在しなければデフォルト値にするための関数
     // Default trait だと外部から生成できてしまうためcrate内関数
     pub(crate) fn default() -> Self {
         TimeLine { key_frames: vec![] }
     }
-}
 
-// 補完可能なら補完処理に対応
-// 計算処理上コピーが必要なので参照ではなく値を返す
-impl<T> TimeLine<T>
-where
-    T: Interpolate,
-{
     // 補完のためのキーフレーム取得(指定フレームを超えない最後のフレーム)
     fn left_key_frame(&self, frame: usize) -> Option<&KeyFrame<T>> {
         self.key_frames.iter().rev().find(|k| k.frame <= frame)
@@ -54,41 +64,86 @@ where
         self.key_frames.iter().find(|k| k.frame > frame)
     }
 
+    // 左キーの補完関数で start ~ end 間の値を求める
+    fn easing(
+        left_key: &KeyFrame<T>,
+        r_frame: usize,
+        current_frame: usize,
+        start: f32,
+        end: f32,
+    ) -> f32 {
+        if left_key.frame == r_frame {
+            return end;
+        }
+        let left = left_key.frame as f32;
+        let right = r_frame as f32;
+        let current = current_frame as f32;
+
+        let rate = (current - left) / (right - left);
+
+        left_key
+            .interpolation
+            .interpolate(rate, start, end, right - left, left_key.curve.as_ref())
+    }
+}
+
+// 補完可能なら補完処理に対応
+// 計算処理上コピーが必要なので参照ではなく値を返す
+impl<T> TimeLine<T>
+where
+    T: Interpolate,
+{
+    // 色や頂点など複数の値を持つものはカーブを 0.0 ~ 1.0 の割合として適用する
     pub fn get_interpolation_key(&self, frame: usize) -> Option<T> {
         let left_key = self.left_key_frame(frame);
         let right_key = self.right_key_frame(frame);
 
         match (left_key, right_key) {
             (Some(left_key), Some(right_key)) => {
-                let rate = Self::easing_rate(
-                    left_key.frame,
-                    right_key.frame,
-                    frame,
-                    &left_key.interpolation,
-                );
+                let rate = Self::easing(left_key, right_key.frame, frame, 0., 1.);
                 Some((right_key.value - left_key.value) * rate + left_key.value)
             }
             (Some(left_key), None) => Some(left_key.value), // 後ろのキーがなければ補完は関係ない
             _ => None, // キーが存在しないか手前のキーがなければ何もキーがない
         }
     }
+}
 
-    fn easing_rate(
-        l_frame: usize,
-        r_frame: usize,
-        current_frame: usize,
-        function: &Interpolation,
-    ) -> f32 {
-        if l_frame == r_frame {
-            return 1.0f32;
+// スカラー値はカーブの値をキーの値に対して直接適用する
+impl TimeLine<f32> {
+    pub fn get_scalar_key(&self, frame: usize) -> Option<f32> {
+        let left_key = self.left_key_frame(frame);
+        let right_key = self.right_key_frame(frame);
+
+        match (left_key, right_key) {
+            (Some(left_key), Some(right_key)) => Some(Self::easing(
+                left_key,
+                right_key.frame,
+                frame,
+                left_key.value,
+                right_key.value,
+            )),
+            (Some(left_key), None) => Some(left_key.value),
+            _ => None,
         }
-        let left = l_frame as f32;
-        let right = r_frame as f32;
-        let current = current_frame as f32;
+    }
+}
 
-        let rate = (current - left) / (right - left);
+// メッシュの変形は頂点数が可変なのでコピーできない
+// 頂点ごとに割合で補完する
+impl TimeLine<DeformKey> {
+    pub fn get_deform_key(&self, frame: usize) -> Option<DeformKey> {
+        let left_key = self.left_key_frame(frame);
+        let right_key = self.right_key_frame(frame);
 
-        function.calc_rate(rate)
+        match (left_key, right_key) {
+            (Some(left_key), Some(right_key)) => {
+                let rate = Self::easing(left_key, right_key.frame, frame, 0., 1.);
+                Some(left_key.value.lerp(&right_key.value, rate))
+            }
+            (Some(left_key), None) => Some(left_key.value.clone()),
+            _ => None,
+        }
     }
 }
 
@@ -96,6 +151,9 @@ where
 struct KeyFrame<T> {
     frame: usize,
     interpolation: Interpolation,
+    // エルミート，ベジエ補完のときのみ存在する
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    curve: Option<Curve>,
     #[serde(bound(serialize = "T: Serialize", deserialize = "T: Deserialize<'de>"))]
     value: T,
 }
@@ -111,10 +169,15 @@ impl<T> TimeLineBuilder<T> {
         TimeLineBuilder { key_frames: vec![] }
     }
 
-    pub fn add_key(&mut self, frame: usize, interpolation: Interpolation, value: T) {
+    pub fn add_key(&mut self, frame: usize, interpolation: impl Into<KeyInterpolation>, value: T) {
+        let KeyInterpolation {
+            interpolation,
+            curve,
+        } = interpolation.into();
         self.key_frames.push(KeyFrame {
             frame,
             interpolation,
+            curve,
             value,
         })
     }
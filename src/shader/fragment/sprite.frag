@@ -5,13 +5,16 @@ layout(set = 1, binding = 0) uniform sampler2D albedo;
 layout(location = 0) in VertexData {
     vec2 tex_uv;
     vec4 color;
+    vec4 color_add;
 } vertex;
 layout(location = 0) out vec4 out_color;
 
 void main() {
-    vec4 color = texture(albedo, vertex.tex_uv) * vertex.color;
+    // Color blend = texel * multiply term + add term.
+    vec4 color = texture(albedo, vertex.tex_uv) * vertex.color + vertex.color_add;
     if (color.a == 0.0) {
         discard;
     }
-    out_color = color;
+    // Premultiplied alpha so that every blend mode can fade by the source alpha.
+    out_color = vec4(color.rgb * color.a, color.a);
 }
\ No newline at end of file
@@ -0,0 +1,26 @@
+#version 450
+
+layout(std140, set = 0, binding = 0) uniform ViewArgs {
+    uniform mat4 proj;
+    uniform mat4 view;
+    uniform mat4 proj_view;
+};
+
+// Mesh vertex.
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec2 tex_coord;
+layout(location = 2) in vec4 color;
+layout(location = 3) in vec4 color_add;
+
+layout(location = 0) out VertexData {
+    vec2 tex_uv;
+    vec4 color;
+    vec4 color_add;
+} vertex;
+
+void main() {
+    vertex.tex_uv = tex_coord;
+    vertex.color = color;
+    vertex.color_add = color_add;
+    gl_Position = proj_view * vec4(position, 1.0);
+}
\ No newline at end of file
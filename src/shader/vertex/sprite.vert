@@ -10,12 +10,14 @@ layout(std140, set = 0, binding = 0) uniform ViewArgs {
 layout(location = 0) in vec2 u_offset;
 layout(location = 1) in vec2 v_offset;
 layout(location = 2) in float depth;
-layout(location = 3) in vec4 color;
-layout(location = 4) in vec2 sprite_vertex[4];
+layout(location = 3) in vec4 color[4];
+layout(location = 7) in vec2 sprite_vertex[4];
+layout(location = 11) in vec4 color_add[4];
 
 layout(location = 0) out VertexData {
     vec2 tex_uv;
     vec4 color;
+    vec4 color_add;
 } vertex;
 
 const vec2 positions[4] = vec2[](
@@ -35,7 +37,8 @@ void main() {
     float tex_v = positions[gl_VertexIndex][1];
 
     vertex.tex_uv = texture_coords(vec2(tex_u, tex_v), u_offset, v_offset);
-    vertex.color = color;
+    vertex.color = color[gl_VertexIndex];
+    vertex.color_add = color_add[gl_VertexIndex];
     vec4 vertex = vec4(sprite_vertex[gl_VertexIndex], depth, 1.0);
     gl_Position = proj_view * vertex;
 }
\ No newline at end of file
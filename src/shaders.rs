@@ -7,6 +7,12 @@ lazy_static::lazy_static! {
         "main",
     ).unwrap();
 
+    pub static ref MESH_VERTEX: SpirvShader = SpirvShader::from_bytes(
+        include_bytes!("./shader/compiled/vertex/mesh.vert.spv"),
+        ShaderStageFlags::VERTEX,
+        "main",
+    ).unwrap();
+
     pub static ref SPRITE_FRAGMENT: SpirvShader = SpirvShader::from_bytes(
         include_bytes!("./shader/compiled/fragment/sprite.frag.spv"),
         ShaderStageFlags::FRAGMENT,
@@ -9,7 +9,7 @@ use std::collections::BTreeMap;
 #[derive(Debug)]
 pub struct SplashTranslation;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct DummyUser;
 
 impl AnimationFile for SplashTranslation {
@@ -22,6 +22,13 @@ impl AnimationFile for SplashTranslation {
         FILE_LIST[file_id].0
     }
 
+    fn from_file_name(file_name: &str) -> Option<Self::FileId> {
+        FILE_LIST
+            .iter()
+            .find(|(_, (name, _))| *name == file_name)
+            .map(|(file_id, _)| *file_id)
+    }
+
     fn sprite_sheet_num(file_id: &Self::FileId) -> usize {
         FILE_LIST[file_id].1
     }
@@ -41,6 +48,14 @@ impl<'s> TranslateAnimation<'s> for SplashTranslation {
     }
 }
 
+// スプラッシュにユーザーデータは無いのでキーは打たない
+#[cfg(feature = "builder")]
+impl crate::importer::FromUserValue for DummyUser {
+    fn from_user_value(_: &crate::importer::UserValue) -> Option<Self> {
+        None
+    }
+}
+
 lazy_static::lazy_static! {
     static ref FILE_LIST: BTreeMap<FileId, (&'static str, usize)> = {
         let mut list = BTreeMap::new();
@@ -1,7 +1,23 @@
+mod animation_blend;
+mod animation_layer;
+mod animation_nodes;
 mod animation_time_increment;
 mod animation_transition;
+mod attachment;
+mod hitbox;
+mod independent_effect;
+mod independent_instance;
+mod part_tint;
 mod root_translate;
 
+pub(crate) use animation_blend::AnimationBlendSystem;
+pub(crate) use animation_layer::AnimationLayerSystem;
+pub(crate) use animation_nodes::AnimationNodeSystem;
 pub(crate) use animation_time_increment::AnimationTimeIncrementSystem;
 pub(crate) use animation_transition::AnimationTransitionSystem;
+pub(crate) use attachment::AttachmentSystem;
+pub(crate) use hitbox::HitboxSystem;
+pub(crate) use independent_effect::IndependentEffectSystem;
+pub(crate) use independent_instance::IndependentInstanceSystem;
+pub(crate) use part_tint::PartTintSystem;
 pub(crate) use root_translate::RootTranslateSystem;
@@ -0,0 +1,48 @@
+use crate::{components::AnimationBlend, traits::animation_file::AnimationFile};
+use amethyst::{
+    core::timing::Time,
+    ecs::{Entities, Join, Read, System, WriteStorage},
+};
+use std::marker::PhantomData;
+
+// クロスフェードの経過時間を進め，終わったものは外す
+pub struct AnimationBlendSystem<T> {
+    _marker: PhantomData<T>,
+}
+
+impl<T> AnimationBlendSystem<T> {
+    pub fn new() -> Self {
+        AnimationBlendSystem {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'s, T> System<'s> for AnimationBlendSystem<T>
+where
+    T: AnimationFile,
+{
+    type SystemData = (
+        Entities<'s>,
+        Read<'s, Time>,
+        WriteStorage<'s, AnimationBlend<T>>,
+    );
+
+    fn run(&mut self, (entities, time, mut blends): Self::SystemData) {
+        #[cfg(not(feature = "count-frame"))]
+        let delta_sec = time.delta_seconds();
+        #[cfg(feature = "count-frame")]
+        let delta_sec = 1. / 60.;
+
+        let mut ended = vec![];
+        for (e, blend) in (&*entities, &mut blends).join() {
+            blend.add_time(delta_sec);
+            if blend.is_end() {
+                ended.push(e);
+            }
+        }
+        for e in ended {
+            blends.remove(e);
+        }
+    }
+}
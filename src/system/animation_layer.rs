@@ -0,0 +1,73 @@
+use crate::{
+    components::{AnimationLayers, PlayAnimationKey},
+    resource::{data::AnimationData, AnimationStore},
+    traits::animation_file::AnimationFile,
+};
+use amethyst::{
+    assets::AssetStorage,
+    core::timing::Time,
+    ecs::{Join, Read, ReadStorage, System, WriteStorage},
+};
+use std::marker::PhantomData;
+
+// レイヤーの再生時間を進める
+// 終端に達したらループの設定に合わせて巻き戻す．巻き戻さない場合は終端のフレームで停止する
+pub struct AnimationLayerSystem<T> {
+    _marker: PhantomData<T>,
+}
+
+impl<T> AnimationLayerSystem<T> {
+    pub fn new() -> Self {
+        AnimationLayerSystem {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'s, T> System<'s> for AnimationLayerSystem<T>
+where
+    T: AnimationFile,
+{
+    type SystemData = (
+        Read<'s, Time>,
+        ReadStorage<'s, PlayAnimationKey<T>>,
+        WriteStorage<'s, AnimationLayers<T>>,
+        Read<'s, AnimationStore<T>>,
+        Read<'s, AssetStorage<AnimationData<T>>>,
+    );
+
+    fn run(&mut self, (time, play_keys, mut layers, store, storage): Self::SystemData) {
+        #[cfg(not(feature = "count-frame"))]
+        let delta_sec = time.delta_seconds();
+        #[cfg(feature = "count-frame")]
+        let delta_sec = 1. / 60.;
+
+        for (key, layers) in (&play_keys, &mut layers).join() {
+            // レイヤーは再生中のファイルのアニメーションを使う
+            let data = key
+                .play_key()
+                .and_then(|(id, _, _)| store.get_animation_handle(id))
+                .and_then(|handle| storage.get(handle));
+            for layer in layers.layers_mut() {
+                layer.time_mut().add_time(delta_sec);
+
+                let animation = data
+                    .and_then(|data| data.pack(layer.pack()))
+                    .and_then(|pack| pack.animation(layer.animation()));
+                let (fps, total_frame) = match animation {
+                    Some(animation) => (animation.fps() as f32, animation.total_frame()),
+                    None => continue,
+                };
+                let layer_time = layer.time_mut();
+                let is_end = if layer_time.is_reverse() {
+                    layer_time.play_time() < 0.
+                } else {
+                    layer_time.play_frame(fps) >= total_frame
+                };
+                if is_end && layer_time.wrap(fps, total_frame).is_none() {
+                    layer_time.clamp_end(fps, total_frame);
+                }
+            }
+        }
+    }
+}
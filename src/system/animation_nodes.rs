@@ -0,0 +1,104 @@
+use crate::{
+    components::{AnimationNodes, BuildRequireData, NodeOverrideData},
+    traits::animation_file::AnimationFile,
+};
+use amethyst::{
+    core::{math::Matrix4, Parent, Transform},
+    ecs::{
+        storage::{MaskedStorage, Storage},
+        Entities, Entity, Join, ReadStorage, System, WriteStorage,
+    },
+};
+use std::{marker::PhantomData, ops::Deref};
+
+// 描画，当たり判定，追従で使うノードを毎フレーム一度だけ作る
+// Transform::global_matrix は TransformSystem が更新するまで前のフレームの値なので，
+// ワールド座標の行列は Parent をたどってローカルの Transform から求める
+pub struct AnimationNodeSystem<T> {
+    _marker: PhantomData<T>,
+}
+
+impl<T> AnimationNodeSystem<T> {
+    pub fn new() -> Self {
+        AnimationNodeSystem {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'s, T> System<'s> for AnimationNodeSystem<T>
+where
+    T: AnimationFile,
+{
+    type SystemData = (
+        Entities<'s>,
+        BuildRequireData<'s, T>,
+        NodeOverrideData<'s, T>,
+        ReadStorage<'s, Parent>,
+        WriteStorage<'s, AnimationNodes<T::UserData>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, (times, keys, transforms, tints, storage, store), overrides, parents, mut nodes): Self::SystemData,
+    ) {
+        for (e, time, key, transform, tint) in
+            (&*entities, &times, &keys, &transforms, tints.maybe()).join()
+        {
+            let made = AnimationNodes::<T::UserData>::make_node(
+                time,
+                tint,
+                &overrides.get(e),
+                key.play_key(),
+                transform,
+                &world_matrix(e, &transforms, &parents),
+                &store,
+                &storage,
+            );
+            match made {
+                Some(made) => {
+                    if let Err(err) = nodes.insert(e, made) {
+                        log::error!("animation nodes insert failed: {:?}", err);
+                    }
+                }
+                None => {
+                    nodes.remove(e);
+                }
+            }
+        }
+
+        // 再生に必要なコンポーネントが外されたエンティティのノードは消す
+        let stale = (&*entities, &nodes)
+            .join()
+            .filter(|(e, _)| {
+                times.get(*e).is_none() || keys.get(*e).is_none() || transforms.get(*e).is_none()
+            })
+            .map(|(e, _)| e)
+            .collect::<Vec<_>>();
+        for e in stale {
+            nodes.remove(e);
+        }
+    }
+}
+
+// 親の Transform を順にかけたワールド座標の行列
+// TransformSystem の結果を待たずに今のフレームのローカルの Transform から求める
+pub(crate) fn world_matrix<D, P>(
+    entity: Entity,
+    transforms: &Storage<Transform, D>,
+    parents: &Storage<Parent, P>,
+) -> Matrix4<f32>
+where
+    D: Deref<Target = MaskedStorage<Transform>>,
+    P: Deref<Target = MaskedStorage<Parent>>,
+{
+    let mut matrix = Matrix4::identity();
+    let mut current = Some(entity);
+    while let Some(e) = current {
+        if let Some(transform) = transforms.get(e) {
+            matrix = transform.matrix() * matrix;
+        }
+        current = parents.get(e).map(|parent| parent.entity);
+    }
+    matrix
+}
@@ -20,10 +20,10 @@ impl<'s> System<'s> for AnimationTimeIncrementSystem {
     }
 
     fn run(&mut self, (time, mut animation_times): Self::SystemData) {
-        #[cfg(not(feature = "count_frame"))]
+        #[cfg(not(feature = "count-frame"))]
         let delta_sec = time.delta_seconds();
-        #[cfg(feature = "count_frame")]
-        let delta_sec = 60.;
+        #[cfg(feature = "count-frame")]
+        let delta_sec = 1. / 60.;
         for (anim_time,) in (&mut animation_times,).join() {
             anim_time.add_time(delta_sec); // 現実時間で再生
         }
@@ -1,14 +1,17 @@
 use crate::{
-    components::{AnimationTime, PlayAnimationKey},
-    resource::{data::AnimationData, AnimationStore},
-    traits::translate_animation::TranslateAnimation,
+    components::{
+        AnimationBlend, AnimationQueue, AnimationStateMachine, AnimationTime, IndependentInstance,
+        LoopMode, PlayAnimationKey,
+    },
+    resource::{data::AnimationData, state_machine::StateMachine, AnimationStore},
+    traits::{animation_file::AnimationFile, translate_animation::TranslateAnimation},
     types::event::{AnimationEvent, AnimationEventChannel},
 };
 use amethyst::{
     assets::AssetStorage,
-    ecs::{Entities, Join, Read, System, Write, WriteStorage},
+    ecs::{Entities, Entity, Join, Read, ReadStorage, System, Write, WriteStorage},
 };
-use std::marker::PhantomData;
+use std::{marker::PhantomData, ops::Range};
 
 pub struct AnimationTransitionSystem<T> {
     _marker: PhantomData<T>,
@@ -30,7 +33,12 @@ where
         Entities<'s>,
         WriteStorage<'s, AnimationTime>,
         WriteStorage<'s, PlayAnimationKey<T>>,
+        ReadStorage<'s, IndependentInstance>,
+        WriteStorage<'s, AnimationStateMachine<T>>,
+        WriteStorage<'s, AnimationQueue<T>>,
+        WriteStorage<'s, AnimationBlend<T>>,
         Read<'s, AssetStorage<AnimationData<T>>>,
+        Read<'s, AssetStorage<StateMachine<T>>>,
         Read<'s, AnimationStore<T>>,
         Write<'s, AnimationEventChannel<T>>,
         T::OptionalData,
@@ -42,13 +50,48 @@ where
             entities,
             mut animation_times,
             mut play_key,
+            instances,
+            mut state_machines,
+            mut queues,
+            mut blends,
             sprite_animation_storage,
+            state_machine_storage,
             animation_store,
             mut channel,
             optional,
         ): Self::SystemData,
     ) {
-        for (e, time) in (&*entities, &mut animation_times).join() {
+        // 独立動作のインスタンスは IndependentInstanceSystem が再生フレームを管理する
+        for (e, time, _) in (&*entities, &mut animation_times, !&instances).join() {
+            // ステートマシンのステートが決まっていなければ開始ステートのアニメーションから再生する
+            let initial = state_machines.get_mut(e).and_then(|machine| {
+                let state_machine = state_machine_storage.get(machine.state_machine())?;
+                machine.enter_initial(state_machine)
+            });
+            // キューに予約があればそちらを優先して再生を始める
+            // 戻り先は開始ステートに入った場合はそのアニメーション
+            let current_key = initial.or_else(|| {
+                let (_, &pack, &anim) = play_key.get(e)?.play_key()?;
+                Some((pack, anim))
+            });
+            let queued = queues.get_mut(e).and_then(|queue| queue.start(current_key));
+            let mut started = false;
+            if let (Some((pack, anim)), Some(key)) = (queued.or(initial), play_key.get_mut(e)) {
+                key.set_pack(pack);
+                key.set_animation(anim);
+                time.set_play_time(0.);
+                time.reset_loop_count();
+                started = true;
+                if let Some((&file_id, _, _)) = key.play_key() {
+                    channel.single_write(AnimationEvent::ChangeKey {
+                        entity: e,
+                        file_id,
+                        pack,
+                        animation: anim,
+                    });
+                }
+            }
+
             let (id, pack_id, anim_id) = match play_key.get(e).and_then(|key| key.play_key()) {
                 Some((&id, &pack, &anim)) => (id, pack, anim),
                 None => continue,
@@ -66,31 +109,199 @@ where
                 }
             };
 
-            let frame = time.play_frame(animation.fps() as f32);
+            // 逆再生で再生を始めた場合は終端から再生する
+            if started && time.is_reverse() {
+                time.set_play_time(animation.total_frame() as f32 / animation.fps() as f32);
+            }
+
+            // 前回から通過したフレームのユーザーデータを全パーツ分通知する
+            if let Some(frames) = time.passed_frames(animation.fps() as f32) {
+                let mut events = vec![];
+                collect_user_events(
+                    e,
+                    (&id, &pack_id, &anim_id),
+                    frames,
+                    &animation_store,
+                    &sprite_animation_storage,
+                    &mut events,
+                );
+                channel.iter_write(events);
+            }
+
+            let fps = animation.fps() as f32;
+            let total_frame = animation.total_frame();
+            let frame = time.current_frame(fps, total_frame);
             // ステート変化に関連する情報はルートにのみ入れる
             let root_user = animation.user(crate::constant::ROOT_PART_ID, frame);
 
             // 現在のフレームが総フレーム以上だった場合はアニメーション自体は終了している
-            let rest_frame = if frame >= animation.total_frame() {
-                None
-            } else {
-                Some(animation.total_frame() - frame)
+            // 逆再生では再生時間が 0 未満になったら終了している
+            let rest_frame = match (time.is_reverse(), frame >= total_frame) {
+                (true, _) if time.play_time() < 0. => None,
+                (true, _) => Some(frame + 1),
+                (false, true) => None,
+                (false, false) => Some(total_frame - frame),
             };
 
-            match T::translate_animation(e, rest_frame, (&pack_id, &anim_id), root_user, &optional)
-            {
-                Some((next_pack, next_anim, next_frame)) => {
-                    let fps = animation.fps() as f32;
-                    // 次のアニメーションのフレーム数が来るので実時間に変換
-                    let next_time = 1.0 / fps * (next_frame as f32);
+            // ループの設定があれば終了時に巻き戻し，設定の回数を再生し終えるまで遷移しない
+            if rest_frame.is_none() {
+                if let Some(count) = time.wrap(fps, total_frame) {
+                    channel.single_write(AnimationEvent::Loop {
+                        entity: e,
+                        file_id: id,
+                        pack: pack_id,
+                        animation: anim_id,
+                        count,
+                    });
+                    // 次回の更新は今の時間から通過判定するので，巻き戻した位置から今の時間までのキーはここで通知する
+                    let frames = if time.is_reverse() {
+                        (time.play_time() * fps).floor() as usize..total_frame
+                    } else {
+                        0..(time.play_time() * fps).ceil() as usize
+                    };
+                    let mut events = vec![];
+                    collect_user_events(
+                        e,
+                        (&id, &pack_id, &anim_id),
+                        frames,
+                        &animation_store,
+                        &sprite_animation_storage,
+                        &mut events,
+                    );
+                    channel.iter_write(events);
+                    continue;
+                }
+                if time.loop_mode() == LoopMode::Clamp {
+                    time.clamp_end(fps, total_frame);
+                    channel.single_write(AnimationEvent::End {
+                        entity: e,
+                        file_id: id,
+                        pack: pack_id,
+                        animation: anim_id,
+                    });
+                    continue;
+                }
+            }
+
+            // キューを再生中なら終了するまで遷移しない
+            // キューを消費し終わった場合はステートマシンか TranslateAnimation で遷移する
+            let queue_next = match queues.get_mut(e) {
+                Some(queue) if queue.is_playing() => match rest_frame {
+                    Some(_) => Some(None),
+                    None => queue.next().map(Some),
+                },
+                _ => None,
+            };
+
+            // ステートマシンがある場合は遷移条件で次のアニメーションを決める
+            let next = match queue_next {
+                Some(next) => next.map(|(next_pack, next_anim)| (next_pack, next_anim, 0, None)),
+                None => match state_machines.get_mut(e).and_then(|machine| {
+                    let state_machine = state_machine_storage.get(machine.state_machine())?;
+                    Some((machine, state_machine))
+                }) {
+                    Some((machine, state_machine)) => {
+                        machine.translate(state_machine, rest_frame, root_user)
+                    }
+                    None => T::translate_animation(
+                        e,
+                        rest_frame,
+                        (&pack_id, &anim_id),
+                        root_user,
+                        &optional,
+                    )
+                    .map(|(next_pack, next_anim, next_frame)| {
+                        let blend_time = T::blend_time(
+                            e,
+                            (&pack_id, &anim_id),
+                            (&next_pack, &next_anim),
+                            &optional,
+                        );
+                        (next_pack, next_anim, next_frame, blend_time)
+                    }),
+                },
+            };
+            match next {
+                Some((next_pack, next_anim, next_frame, blend_time)) => {
+                    // クロスフェードする場合は遷移元のキーと再生時間を残しておく
+                    // 指定がなければフェード中でも即座に切り替える
+                    // パーツの構成が違う別パックへの遷移は補間できないので即座に切り替える
+                    let blend_time = blend_time.filter(|&blend_time| blend_time > 0.);
+                    let blend_time = match blend_time {
+                        Some(_) if next_pack != pack_id => {
+                            log::warn!(
+                                "animation blend skipped: {:?} -> {:?} is not in the same pack",
+                                (pack_id, anim_id),
+                                (next_pack, next_anim)
+                            );
+                            None
+                        }
+                        _ => blend_time,
+                    };
+                    let blend = blend_time.map(|blend_time| {
+                        AnimationBlend::new(pack_id, anim_id, time.clone(), blend_time)
+                    });
+                    match blend {
+                        Some(blend) => {
+                            if let Err(err) = blends.insert(e, blend) {
+                                log::error!("animation blend insert failed: {:?}", err);
+                            }
+                        }
+                        None => {
+                            blends.remove(e);
+                        }
+                    }
+
+                    let (next_fps, next_total_frame) = animation_store
+                        .get_animation_handle(&id)
+                        .and_then(|handle| sprite_animation_storage.get(handle))
+                        .and_then(|data| data.pack(&next_pack))
+                        .and_then(|pack| pack.animation(&next_anim))
+                        .map(|animation| (animation.fps() as f32, animation.total_frame()))
+                        .unwrap_or((fps, 0));
+                    // 次のアニメーションのフレーム数が来るので次のアニメーションの fps で実時間に変換
                     // 次アニメーションに遷移する際に現在のフレームから超過した時間は次のアニメーションの開始オフセットになる
-                    let offset_time = time.play_time() - frame as f32 / fps;
+                    // 逆再生は終端から開始フレーム分戻った位置から再生し，超過した時間は負になる
+                    let (next_time, offset_time) = if time.is_reverse() {
+                        let frame_end = if rest_frame.is_none() {
+                            0.
+                        } else {
+                            (frame + 1) as f32 / fps
+                        };
+                        (
+                            next_total_frame.saturating_sub(next_frame) as f32 / next_fps,
+                            time.play_time() - frame_end,
+                        )
+                    } else {
+                        (
+                            1.0 / next_fps * (next_frame as f32),
+                            time.play_time() - frame as f32 / fps,
+                        )
+                    };
                     time.set_play_time(next_time + offset_time);
+                    time.reset_loop_count();
                     if let Some(key) = play_key.get_mut(e) {
                         key.set_pack(next_pack);
                         key.set_animation(next_anim);
                     }
 
+                    // 次回の更新は今の時間から通過判定するので，開始フレームから今の時間までのキーはここで通知する
+                    let frames = if time.is_reverse() {
+                        (time.play_time() * next_fps).floor().max(0.) as usize
+                            ..next_total_frame.saturating_sub(next_frame)
+                    } else {
+                        next_frame..(time.play_time() * next_fps).ceil() as usize
+                    };
+                    let mut user_events = vec![];
+                    collect_user_events(
+                        e,
+                        (&id, &next_pack, &next_anim),
+                        frames,
+                        &animation_store,
+                        &sprite_animation_storage,
+                        &mut user_events,
+                    );
+
                     // 切り替わったので今再生中のアニメーションは終了
                     channel.single_write(AnimationEvent::End {
                         entity: e,
@@ -106,6 +317,7 @@ where
                         pack: next_pack,
                         animation: next_anim,
                     });
+                    channel.iter_write(user_events);
                 }
                 None => {
                     if rest_frame.is_none() {
@@ -122,3 +334,85 @@ where
         }
     }
 }
+
+// 範囲内のフレームにあるユーザーデータのキーを集める
+// 独立動作でないインスタンスパーツは参照先のアニメーションで通過したフレームのキーも集める
+fn collect_user_events<T>(
+    entity: Entity,
+    key: (&T::FileId, &T::PackKey, &T::AnimationKey),
+    frames: Range<usize>,
+    store: &AnimationStore<T>,
+    storage: &AssetStorage<AnimationData<T>>,
+    events: &mut Vec<AnimationEvent<T>>,
+) -> Option<()>
+where
+    T: AnimationFile,
+{
+    let (&id, &pack_id, &anim_id) = key;
+    let data = storage.get(store.get_animation_handle(&id)?)?;
+    let pack = data.pack(&pack_id)?;
+    let animation = pack.animation(&anim_id)?;
+    let frames = frames.start..frames.end.min(animation.total_frame());
+
+    for (part_id, part) in pack.parts().enumerate() {
+        for (frame, user) in animation.user_keys(part_id, frames.clone()) {
+            events.push(AnimationEvent::UserData {
+                entity,
+                file_id: id,
+                pack: pack_id,
+                animation: anim_id,
+                part_id,
+                frame,
+                user: user.clone(),
+            });
+        }
+
+        let (ref_id, ref_pack, ref_anim) = match part
+            .refference_animation_name()
+            .and_then(|name| data.resolve_animation_name(name, key))
+        {
+            Some(ref_key) => ref_key,
+            None => continue,
+        };
+        let ref_total_frame = match store
+            .get_animation_handle(&ref_id)
+            .and_then(|handle| storage.get(handle))
+            .and_then(|data| data.pack(&ref_pack))
+            .and_then(|pack| pack.animation(&ref_anim))
+        {
+            Some(ref_animation) => ref_animation.total_frame(),
+            None => continue,
+        };
+
+        for frame in frames.clone() {
+            let (key_frame, instance_key) = match animation.instance(part_id, frame) {
+                Some((key_frame, instance_key)) if instance_key.independent() == false => {
+                    (key_frame, instance_key)
+                }
+                _ => continue,
+            };
+            // 親の 1 フレームで進むインスタンス上の経過フレームをすべて通過したとする
+            let elapsed =
+                |frame: usize| ((frame - key_frame) as f32 * instance_key.speed_rate()) as usize;
+            let current = elapsed(frame);
+            let start = if frame > key_frame {
+                (elapsed(frame - 1) + 1).min(current + 1)
+            } else {
+                current
+            };
+            for elapsed_frame in start..=current {
+                if let Some(play_frame) = instance_key.play_frame(elapsed_frame, ref_total_frame) {
+                    collect_user_events(
+                        entity,
+                        (&ref_id, &ref_pack, &ref_anim),
+                        play_frame..play_frame + 1,
+                        store,
+                        storage,
+                        events,
+                    );
+                }
+            }
+        }
+    }
+    Some(())
+}
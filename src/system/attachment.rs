@@ -0,0 +1,103 @@
+use crate::{
+    components::{AnimationNodes, Attachment, PlayAnimationKey},
+    resource::{data::AnimationData, AnimationStore},
+    traits::animation_file::AnimationFile,
+};
+use amethyst::{
+    assets::AssetStorage,
+    core::{
+        math::{Matrix4, Vector3},
+        Transform,
+    },
+    ecs::{Entities, Entity, Join, Read, ReadStorage, System, WriteStorage},
+};
+use std::marker::PhantomData;
+
+// 追従先のパーツのワールド座標の行列を Attachment を持つエンティティの Transform にコピーする
+pub struct AttachmentSystem<T> {
+    _marker: PhantomData<T>,
+}
+
+impl<T> AttachmentSystem<T> {
+    pub fn new() -> Self {
+        AttachmentSystem {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'s, T> System<'s> for AttachmentSystem<T>
+where
+    T: AnimationFile,
+{
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Attachment>,
+        ReadStorage<'s, PlayAnimationKey<T>>,
+        ReadStorage<'s, AnimationNodes<T::UserData>>,
+        WriteStorage<'s, Transform>,
+        Read<'s, AssetStorage<AnimationData<T>>>,
+        Read<'s, AnimationStore<T>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, attachments, keys, nodes, mut transforms, storage, store): Self::SystemData,
+    ) {
+        let matrices = (&*entities, &attachments)
+            .join()
+            .filter_map(|(e, attachment)| {
+                let target = attachment.target();
+                let part_id = part_id(target, attachment.part_name(), &keys, &store, &storage)?;
+                let node = nodes.get(target)?.part_node(part_id)?;
+                Some((e, flipped_matrix(&node.global_matrix, node.flip_h, node.flip_v)))
+            })
+            .collect::<Vec<_>>();
+
+        for (e, matrix) in matrices {
+            if let Some(transform) = transforms.get_mut(e) {
+                set_matrix(transform, &matrix);
+            }
+        }
+    }
+}
+
+fn part_id<T>(
+    target: Entity,
+    part_name: &str,
+    keys: &ReadStorage<PlayAnimationKey<T>>,
+    store: &AnimationStore<T>,
+    storage: &AssetStorage<AnimationData<T>>,
+) -> Option<usize>
+where
+    T: AnimationFile,
+{
+    let (id, pack_id, _) = keys.get(target)?.play_key()?;
+    storage
+        .get(store.get_animation_handle(id)?)?
+        .pack(pack_id)?
+        .part_id(part_name)
+}
+
+// 描画と同じくパーツの原点を中心に反転させた行列
+fn flipped_matrix(matrix: &Matrix4<f32>, flip_h: bool, flip_v: bool) -> Matrix4<f32> {
+    let sign_x = if flip_h { -1. } else { 1. };
+    let sign_y = if flip_v { -1. } else { 1. };
+    matrix * Matrix4::new_nonuniform_scaling(&Vector3::new(sign_x, sign_y, 1.))
+}
+
+// 2D の行列を移動，Z 軸回転，スケールに分解して設定する
+// 反転している場合は Y 軸のスケールを負にする
+fn set_matrix(transform: &mut Transform, matrix: &Matrix4<f32>) {
+    let axis_x = Vector3::new(matrix[(0, 0)], matrix[(1, 0)], matrix[(2, 0)]);
+    let axis_y = Vector3::new(matrix[(0, 1)], matrix[(1, 1)], matrix[(2, 1)]);
+    let sign = if axis_x.x * axis_y.y - axis_x.y * axis_y.x < 0. {
+        -1.
+    } else {
+        1.
+    };
+
+    transform.set_translation_xyz(matrix[(0, 3)], matrix[(1, 3)], matrix[(2, 3)]);
+    transform.set_rotation_2d(axis_x.y.atan2(axis_x.x));
+    transform.set_scale(Vector3::new(axis_x.norm(), axis_y.norm() * sign, 1.));
+}
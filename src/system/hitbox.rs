@@ -0,0 +1,139 @@
+use crate::{
+    components::{AnimationNodes, CollisionShape, Hitboxes, Node, PlayAnimationKey},
+    renderer::{quad_positions, CellTransform},
+    resource::{data::AnimationData, AnimationStore},
+    traits::animation_file::AnimationFile,
+    types::Bounds,
+};
+use amethyst::{
+    assets::AssetStorage,
+    core::math::Vector2,
+    ecs::{Entities, Join, Read, ReadStorage, System, WriteStorage},
+    renderer::sprite::SpriteSheet,
+};
+use std::marker::PhantomData;
+
+// 当たり判定を持つパーツの形状をワールド座標で計算する
+// AnimationNodeSystem が作ったノードを使う
+pub struct HitboxSystem<T> {
+    _marker: PhantomData<T>,
+}
+
+impl<T> HitboxSystem<T> {
+    pub fn new() -> Self {
+        HitboxSystem {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'s, T> System<'s> for HitboxSystem<T>
+where
+    T: AnimationFile,
+{
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, PlayAnimationKey<T>>,
+        ReadStorage<'s, AnimationNodes<T::UserData>>,
+        Read<'s, AssetStorage<AnimationData<T>>>,
+        Read<'s, AnimationStore<T>>,
+        Read<'s, AssetStorage<SpriteSheet>>,
+        WriteStorage<'s, Hitboxes>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, keys, nodes, storage, store, sprite_sheets, mut hitboxes): Self::SystemData,
+    ) {
+        for (e, key) in (&*entities, &keys).join() {
+            let shapes = nodes
+                .get(e)
+                .and_then(|nodes| {
+                    let (id, pack_id, _) = key.play_key()?;
+                    let pack = storage
+                        .get(store.get_animation_handle(id)?)?
+                        .pack(pack_id)?;
+                    let shapes = pack
+                        .parts()
+                        .enumerate()
+                        .filter_map(|(part_id, part)| {
+                            let node = nodes.part_node(part_id)?;
+                            let shape = collision_shape(node, part.bounds()?, &sprite_sheets)?;
+                            Some((part_id, shape))
+                        })
+                        .collect::<Vec<_>>();
+                    Some(shapes)
+                })
+                .unwrap_or_default();
+
+            if shapes.is_empty() {
+                hitboxes.remove(e);
+            } else if let Err(err) = hitboxes.insert(e, Hitboxes::new(shapes)) {
+                log::error!("hitbox insert failed: {:?}", err);
+            }
+        }
+    }
+}
+
+// 描画と同じセルの四隅から形状を作る
+// 円の半径はセルの対角線の半分で，スケールの影響を受けるかは形状の種類による
+fn collision_shape<U>(
+    node: &Node<U>,
+    bounds: Bounds,
+    sprite_sheets: &AssetStorage<SpriteSheet>,
+) -> Option<CollisionShape> {
+    if node.hide {
+        return None;
+    }
+    let sprite_sheet = sprite_sheets.get(node.sprite_sheet.as_ref()?)?;
+    let sprite = sprite_sheet.sprites.get(node.sprite_no?)?;
+
+    let [rt, lt, rb, lb] = quad_positions(
+        sprite,
+        &node.global_matrix,
+        &node.deform_offsets,
+        (node.flip_h, node.flip_v),
+        &CellTransform::from_node(node),
+    );
+    let (rt, lt, rb, lb) = (rt.xy(), lt.xy(), rb.xy(), lb.xy());
+
+    let center = (rt + lt + rb + lb) / 4.;
+    // 半径は BNDR のキー．キーがなければセルの対角線の半分
+    let radius = node
+        .bounding_radius
+        .unwrap_or_else(|| Vector2::new(sprite.width, sprite.height).norm() * 0.5);
+    let scale_x = if sprite.width == 0. {
+        1.
+    } else {
+        (rt - lt).norm() / sprite.width
+    };
+    let scale_y = if sprite.height == 0. {
+        1.
+    } else {
+        (rt - rb).norm() / sprite.height
+    };
+
+    let shape = match bounds {
+        Bounds::Quad => CollisionShape::Quad([rt, lt, lb, rb]),
+        Bounds::Aabb => {
+            let vertices = [rt, lt, rb, lb];
+            let min = vertices
+                .iter()
+                .fold(rt, |min, v| Vector2::new(min.x.min(v.x), min.y.min(v.y)));
+            let max = vertices
+                .iter()
+                .fold(rt, |max, v| Vector2::new(max.x.max(v.x), max.y.max(v.y)));
+            CollisionShape::Aabb { min, max }
+        }
+        Bounds::Circle => CollisionShape::Circle { center, radius },
+        Bounds::CircleMin => CollisionShape::Circle {
+            center,
+            radius: radius * scale_x.min(scale_y),
+        },
+        Bounds::CircleMax => CollisionShape::Circle {
+            center,
+            radius: radius * scale_x.max(scale_y),
+        },
+    };
+    Some(shape)
+}
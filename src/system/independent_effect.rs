@@ -0,0 +1,101 @@
+use crate::{
+    components::{AnimationTime, IndependentEffects, PlayAnimationKey},
+    resource::{data::AnimationData, AnimationStore},
+    traits::animation_file::AnimationFile,
+};
+use amethyst::{
+    assets::AssetStorage,
+    core::timing::Time,
+    ecs::{Entities, Join, Read, ReadStorage, System, WriteStorage},
+};
+use std::marker::PhantomData;
+
+// 独立動作のエフェクトキーの経過時間を進める
+pub struct IndependentEffectSystem<T> {
+    _marker: PhantomData<T>,
+}
+
+impl<T> IndependentEffectSystem<T> {
+    pub fn new() -> Self {
+        IndependentEffectSystem {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'s, T> System<'s> for IndependentEffectSystem<T>
+where
+    T: AnimationFile,
+{
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, AnimationTime>,
+        ReadStorage<'s, PlayAnimationKey<T>>,
+        WriteStorage<'s, IndependentEffects>,
+        Read<'s, AssetStorage<AnimationData<T>>>,
+        Read<'s, AnimationStore<T>>,
+        Read<'s, Time>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, times, keys, mut effects, storage, store, time): Self::SystemData,
+    ) {
+        #[cfg(not(feature = "count-frame"))]
+        let delta_sec = time.delta_seconds();
+        #[cfg(feature = "count-frame")]
+        let delta_sec = 1. / 60.;
+
+        for (e, anim_time, key) in (&*entities, &times, &keys).join() {
+            let effect_keys =
+                independent_effect_keys(anim_time, key, &store, &storage).unwrap_or_default();
+            if effect_keys.is_empty() {
+                effects.remove(e);
+                continue;
+            }
+
+            if let Some(part_effects) = effects.get_mut(e) {
+                part_effects.update(&effect_keys, delta_sec);
+                continue;
+            }
+            let mut new_effects = IndependentEffects::default();
+            new_effects.update(&effect_keys, delta_sec);
+            if let Err(err) = effects.insert(e, new_effects) {
+                log::error!("independent effects insert failed: {:?}", err);
+            }
+        }
+    }
+}
+
+// 今のフレームで有効な独立動作のエフェクトキー(パーツ番号，キーがセットされたフレーム)
+fn independent_effect_keys<T>(
+    anim_time: &AnimationTime,
+    key: &PlayAnimationKey<T>,
+    store: &AnimationStore<T>,
+    storage: &AssetStorage<AnimationData<T>>,
+) -> Option<Vec<(usize, usize)>>
+where
+    T: AnimationFile,
+{
+    let (id, pack_id, animation_id) = key.play_key()?;
+    let pack = storage
+        .get(store.get_animation_handle(id)?)?
+        .pack(pack_id)?;
+    let animation = pack.animation(animation_id)?;
+    let current_frame = anim_time.current_frame(animation.fps() as f32, animation.total_frame());
+
+    let keys = pack
+        .parts()
+        .enumerate()
+        .filter(|(_, part)| part.refference_effect_index().is_some())
+        .filter_map(|(part_id, _)| {
+            let (key_frame, effect_key) = animation.effect(part_id, current_frame)?;
+            if effect_key.independent() {
+                Some((part_id, key_frame))
+            } else {
+                None
+            }
+        })
+        .collect();
+    Some(keys)
+}
@@ -0,0 +1,340 @@
+use crate::{
+    components::{
+        AnimationNodes, AnimationTime, Attachment, IndependentInstance, NodeOverrides,
+        PlayAnimationKey,
+    },
+    resource::{data::AnimationData, AnimationStore},
+    system::animation_nodes::world_matrix,
+    traits::animation_file::AnimationFile,
+    types::{InstanceKey, LinearColor},
+};
+use amethyst::{
+    assets::AssetStorage,
+    core::{math::Matrix4, Parent, Time, Transform},
+    ecs::{Entities, Entity, Join, Read, ReadStorage, System, WriteStorage},
+    renderer::resources::Tint,
+};
+use std::marker::PhantomData;
+
+// 独立動作のインスタンスパーツのキーが来たらエンティティを生成する
+// 生成したエンティティは Attachment でインスタンスパーツに追従させ，再生回数を終えたら破棄する
+// 同じパーツのキーが再び来た場合と追従先が破棄された場合も破棄する(無限ループのキーでも増え続けない)
+pub struct IndependentInstanceSystem<T> {
+    _marker: PhantomData<T>,
+}
+
+impl<T> IndependentInstanceSystem<T> {
+    pub fn new() -> Self {
+        IndependentInstanceSystem {
+            _marker: PhantomData,
+        }
+    }
+}
+
+// 生成するエンティティの情報
+struct SpawnRequest<T>
+where
+    T: AnimationFile,
+{
+    owner: Entity,      // インスタンスパーツを持つエンティティ
+    part_name: String,  // 追従するインスタンスパーツ名
+    file_id: T::FileId, // 参照先が別ファイルの場合もある
+    pack: T::PackKey,
+    animation: T::AnimationKey,
+    key: InstanceKey,
+    transform: Transform,
+    color: [f32; 4],
+}
+
+impl<'s, T> System<'s> for IndependentInstanceSystem<T>
+where
+    T: AnimationFile,
+{
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, AnimationTime>,
+        WriteStorage<'s, PlayAnimationKey<T>>,
+        WriteStorage<'s, IndependentInstance>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Tint>,
+        WriteStorage<'s, Attachment>,
+        ReadStorage<'s, Parent>,
+        Read<'s, AssetStorage<AnimationData<T>>>,
+        Read<'s, AnimationStore<T>>,
+        Read<'s, Time>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut animation_times,
+            mut play_keys,
+            mut instances,
+            mut transforms,
+            mut tints,
+            mut attachments,
+            parents,
+            storage,
+            store,
+            time,
+        ): Self::SystemData,
+    ) {
+        #[cfg(not(feature = "count-frame"))]
+        let delta_sec = time.delta_seconds();
+        #[cfg(feature = "count-frame")]
+        let delta_sec = 1. / 60.;
+        // 生成済みのインスタンスの再生フレームを更新
+        for (e, instance, anim_time, key, attachment) in (
+            &*entities,
+            &mut instances,
+            &mut animation_times,
+            &play_keys,
+            attachments.maybe(),
+        )
+            .join()
+        {
+            let owner_alive = attachment
+                .map(|attachment| entities.is_alive(attachment.target()))
+                .unwrap_or(true);
+            instance.add_time(delta_sec);
+            if owner_alive == false
+                || update_instance_time(instance, anim_time, key, &store, &storage).is_none()
+            {
+                log::debug!("end independent instance: {:?}", e);
+                if let Err(err) = entities.delete(e) {
+                    log::error!("instance delete failed: {:?}", err);
+                }
+            }
+        }
+
+        // 新しく独立動作のキーが来たものを収集
+        let requests = (
+            &*entities,
+            &animation_times,
+            &play_keys,
+            &transforms,
+            tints.maybe(),
+        )
+            .join()
+            .filter_map(|(e, anim_time, key, transform, tint)| {
+                let matrix = world_matrix(e, &transforms, &parents);
+                spawn_requests(
+                    e,
+                    anim_time,
+                    key,
+                    (transform, &matrix),
+                    tint,
+                    &store,
+                    &storage,
+                )
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+
+        // 同じパーツから生成済みのインスタンスは新しいものに置き換える
+        // 一度の更新で同じパーツのキーを複数通過した場合は最後のキーだけを生成する
+        let mut spawn_parts = Vec::<(Entity, String)>::new();
+        let requests = requests
+            .into_iter()
+            .rev()
+            .filter(|request| {
+                let part = (request.owner, request.part_name.clone());
+                if spawn_parts.contains(&part) {
+                    false
+                } else {
+                    spawn_parts.push(part);
+                    true
+                }
+            })
+            .collect::<Vec<_>>();
+        for (e, _, attachment) in (&*entities, &instances, &attachments).join() {
+            let replaced = spawn_parts.iter().any(|(owner, part_name)| {
+                attachment.target() == *owner && attachment.part_name() == part_name
+            });
+            if replaced {
+                log::debug!("replace independent instance: {:?}", e);
+                if let Err(err) = entities.delete(e) {
+                    log::error!("instance delete failed: {:?}", err);
+                }
+            }
+        }
+
+        for request in requests {
+            if let Err(err) = spawn_instance(
+                &entities,
+                request,
+                &mut animation_times,
+                &mut play_keys,
+                &mut instances,
+                &mut transforms,
+                &mut tints,
+                &mut attachments,
+                &store,
+                &storage,
+            ) {
+                log::error!("instance spawn failed: {:?}", err);
+            }
+        }
+    }
+}
+
+fn update_instance_time<T>(
+    instance: &mut IndependentInstance,
+    anim_time: &mut AnimationTime,
+    key: &PlayAnimationKey<T>,
+    store: &AnimationStore<T>,
+    storage: &AssetStorage<AnimationData<T>>,
+) -> Option<()>
+where
+    T: AnimationFile,
+{
+    let (id, pack_id, animation_id) = key.play_key()?;
+    let animation = storage
+        .get(store.get_animation_handle(id)?)?
+        .pack(pack_id)?
+        .animation(animation_id)?;
+    let (from, to) = instance.update_play_time(animation.fps() as f32, animation.total_frame())?;
+    anim_time.seek(from, to);
+    Some(())
+}
+
+// 前回から今回の更新までに通過した独立動作のキーを集める
+fn spawn_requests<T>(
+    owner: Entity,
+    anim_time: &AnimationTime,
+    key: &PlayAnimationKey<T>,
+    (transform, matrix): (&Transform, &Matrix4<f32>),
+    tint: Option<&Tint>,
+    store: &AnimationStore<T>,
+    storage: &AssetStorage<AnimationData<T>>,
+) -> Option<Vec<SpawnRequest<T>>>
+where
+    T: AnimationFile,
+{
+    // 停止中は新しいキーは来ない
+    if anim_time.is_play() == false {
+        return None;
+    }
+    let (&id, pack_id, animation_id) = key.play_key()?;
+    let data = storage.get(store.get_animation_handle(&id)?)?;
+    let pack = data.pack(pack_id)?;
+    let animation = pack.animation(animation_id)?;
+
+    // 再生時間を設定し直した直後は前回の時間が無いので次の更新から判定する
+    let frames = anim_time.passed_frames(animation.fps() as f32)?;
+
+    let fired = pack
+        .parts()
+        .enumerate()
+        .flat_map(|(part_id, part)| {
+            animation
+                .instance_keys(part_id, frames.clone())
+                .filter(|(_, instance_key)| instance_key.independent())
+                .filter_map(move |(_, instance_key)| {
+                    let (file_id, pack, animation) = data.resolve_animation_name(
+                        part.refference_animation_name()?,
+                        (&id, pack_id, animation_id),
+                    )?;
+                    let part_name = part.name().to_string();
+                    Some((
+                        part_id,
+                        part_name,
+                        file_id,
+                        pack,
+                        animation,
+                        instance_key.clone(),
+                    ))
+                })
+        })
+        .collect::<Vec<_>>();
+    if fired.is_empty() {
+        return None;
+    }
+
+    // 生成位置を決めるためにパーツのノードを作成
+    let nodes = AnimationNodes::<T::UserData>::make_node(
+        anim_time,
+        tint,
+        // 生成位置だけ必要で，クロスフェードとレイヤーに関係なく再生中のアニメーションで決める
+        &NodeOverrides::default(),
+        key.play_key(),
+        transform,
+        matrix,
+        store,
+        storage,
+    )?;
+
+    Some(
+        fired
+            .into_iter()
+            .filter_map(|(part_id, part_name, file_id, pack, animation, key)| {
+                let node = nodes.part_node(part_id)?;
+                Some(SpawnRequest {
+                    owner,
+                    part_name,
+                    file_id,
+                    pack,
+                    animation,
+                    key,
+                    transform: node.transform.clone(),
+                    color: node.color,
+                })
+            })
+            .collect(),
+    )
+}
+
+fn spawn_instance<T>(
+    entities: &Entities,
+    request: SpawnRequest<T>,
+    animation_times: &mut WriteStorage<AnimationTime>,
+    play_keys: &mut WriteStorage<PlayAnimationKey<T>>,
+    instances: &mut WriteStorage<IndependentInstance>,
+    transforms: &mut WriteStorage<Transform>,
+    tints: &mut WriteStorage<Tint>,
+    attachments: &mut WriteStorage<Attachment>,
+    store: &AnimationStore<T>,
+    storage: &AssetStorage<AnimationData<T>>,
+) -> Result<Entity, amethyst::ecs::error::Error>
+where
+    T: AnimationFile,
+{
+    log::debug!(
+        "spawn independent instance: {:?}",
+        (request.file_id, request.pack, request.animation)
+    );
+    let mut key = PlayAnimationKey::<T>::new(request.file_id);
+    key.set_pack(request.pack);
+    key.set_animation(request.animation);
+
+    // 再生フレームは IndependentInstanceSystem が決めるが，
+    // 入れ子の独立動作のインスタンスのキーを判定するために再生中にしておく
+    let instance = IndependentInstance::new(request.key);
+    let mut anim_time = AnimationTime::new();
+    if let Some(animation) = store
+        .get_animation_handle(&request.file_id)
+        .and_then(|handle| storage.get(handle))
+        .and_then(|data| data.pack(&request.pack))
+        .and_then(|pack| pack.animation(&request.animation))
+    {
+        anim_time
+            .set_play_time(instance.start_time(animation.fps() as f32, animation.total_frame()));
+    }
+    let speed = instance.key().speed_rate();
+    anim_time.play(if instance.key().reverse() {
+        -speed
+    } else {
+        speed
+    });
+
+    let [r, g, b, a] = request.color;
+    let entity = entities.create();
+    animation_times.insert(entity, anim_time)?;
+    play_keys.insert(entity, key)?;
+    instances.insert(entity, instance)?;
+    transforms.insert(entity, request.transform)?;
+    tints.insert(entity, LinearColor(r, g, b, a).into())?;
+    attachments.insert(entity, Attachment::new(request.owner, request.part_name))?;
+    Ok(entity)
+}
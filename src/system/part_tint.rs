@@ -0,0 +1,28 @@
+use crate::components::PartTints;
+use amethyst::{
+    core::timing::Time,
+    ecs::{Join, Read, System, WriteStorage},
+};
+
+// パーツの色の上書きのフェードを進める
+pub struct PartTintSystem;
+
+impl PartTintSystem {
+    pub fn new() -> Self {
+        PartTintSystem
+    }
+}
+
+impl<'s> System<'s> for PartTintSystem {
+    type SystemData = (Read<'s, Time>, WriteStorage<'s, PartTints>);
+
+    fn run(&mut self, (time, mut part_tints): Self::SystemData) {
+        #[cfg(not(feature = "count-frame"))]
+        let delta_sec = time.delta_seconds();
+        #[cfg(feature = "count-frame")]
+        let delta_sec = 1. / 60.;
+        for part_tints in (&mut part_tints).join() {
+            part_tints.add_time(delta_sec);
+        }
+    }
+}
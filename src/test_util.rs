@@ -0,0 +1,29 @@
+// テスト用の共通定義
+use crate::traits::animation_file::AnimationFile;
+
+// キーはすべて数値で扱うテスト用のアニメーションファイル
+// ユーザーデータは整数のみ
+pub(crate) struct TestFile;
+
+impl AnimationFile for TestFile {
+    type FileId = u32;
+    type PackKey = u32;
+    type AnimationKey = u32;
+    type UserData = i32;
+
+    fn to_file_name(_file_id: &u32) -> &'static str {
+        "test"
+    }
+
+    fn sprite_sheet_num(_file_id: &u32) -> usize {
+        1
+    }
+}
+
+// インポーターのテストではユーザーデータの整数をそのまま使う
+#[cfg(feature = "builder")]
+impl crate::importer::FromUserValue for i32 {
+    fn from_user_value(value: &crate::importer::UserValue) -> Option<Self> {
+        value.integer
+    }
+}
@@ -7,5 +7,9 @@ pub trait AnimationFile: 'static + Send + Sync {
     type UserData: AnimationUser;
 
     fn to_file_name(file_id: &Self::FileId) -> &'static str;
+    // 別ファイルのアニメーションを参照するデータを読み込むときに使う
+    fn from_file_name(_file_name: &str) -> Option<Self::FileId> {
+        None
+    }
     fn sprite_sheet_num(file_id: &Self::FileId) -> usize;
 }
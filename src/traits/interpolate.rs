@@ -9,3 +9,8 @@ impl<T> Interpolate for T where
     T: Add<Self, Output = Self> + Sub<Self, Output = Self> + Mul<f32, Output = Self> + Clone + Copy
 {
 }
+
+// start から end へ rate の割合で線形補間する
+pub fn lerp<T: Interpolate>(start: T, end: T, rate: f32) -> T {
+    (end - start) * rate + start
+}
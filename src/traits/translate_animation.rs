@@ -19,4 +19,14 @@ pub trait TranslateAnimation<'s>: 'static + Send + Sync + AnimationFile {
             None
         }
     }
+
+    // 遷移時のクロスフェードの秒数(None の場合は即座に切り替える)
+    fn blend_time(
+        _entity: Entity,
+        _current: (&Self::PackKey, &Self::AnimationKey), // 遷移元のキー
+        _next: (&Self::PackKey, &Self::AnimationKey),    // 遷移先のキー
+        _optional: &Self::OptionalData,
+    ) -> Option<f32> {
+        None
+    }
 }
@@ -1,22 +1,44 @@
 pub(crate) mod animation_instance;
+mod blend;
+mod bone;
 pub(crate) mod bound_type;
 pub mod cell;
+mod deform;
 mod effect;
 pub mod event;
+mod inherit;
 pub mod interpolate;
 pub(crate) mod linear_color;
 pub(crate) mod part_type;
+mod sort_mode;
+mod text;
 mod vertex;
+mod vertex_color;
 
 pub use animation_instance::InstanceKey;
 #[cfg(feature = "builder")]
 pub use animation_instance::InstanceKeyBuilder;
+pub use blend::{AlphaBlend, ColorBlend};
+pub use bone::BoneWeight;
 pub use bound_type::Bounds;
+pub use deform::DeformKey;
+#[cfg(feature = "builder")]
+pub use deform::DeformKeyBuilder;
 pub use effect::EffectKey;
 #[cfg(feature = "builder")]
 pub use effect::EffectKeyBuilder;
+pub use inherit::Inheritance;
+#[cfg(feature = "builder")]
+pub use inherit::InheritanceBuilder;
 pub use linear_color::LinearColor;
 pub use part_type::PartType;
+pub use sort_mode::SortMode;
+#[cfg(feature = "builder")]
+pub use text::TextKeyBuilder;
+pub use text::{TextAlign, TextKey};
 pub use vertex::VertexKey;
 #[cfg(feature = "builder")]
 pub use vertex::VertexKeyBuilder;
+pub use vertex_color::VertexColorKey;
+#[cfg(feature = "builder")]
+pub use vertex_color::VertexColorKeyBuilder;
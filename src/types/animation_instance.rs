@@ -1,7 +1,7 @@
 use serde::*;
 
 // インスタンスパーツの再生情報
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct InstanceKey {
     independent: bool, // 独立動作(true のときは新たにエンティティ生成する)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -41,6 +41,49 @@ impl InstanceKey {
     pub fn speed_rate(&self) -> f32 {
         self.speed_rate
     }
+
+    // 1回の再生の開始フレームと終了フレーム
+    // インスタンスキーには再生開始位置，終了位置(最終フレームからのオフセット)が載っている
+    pub fn play_range(&self, total_frame: usize) -> (usize, usize) {
+        let end_frame = total_frame.saturating_sub(1 + self.end_offset);
+        (self.start_offset, end_frame.max(self.start_offset))
+    }
+
+    // 何回目(0 始まり)の再生が逆方向に再生するか
+    // 逆再生の場合か，pingpong再生の奇数ループ目は再生フレーム値が逆になる
+    // 逆再生中の pingpong は偶数ループ目が順再生になる
+    pub fn is_backward(&self, loop_num: usize) -> bool {
+        self.reverse ^ (self.pingpong && loop_num % 2 == 1)
+    }
+
+    // キーが打たれてからの経過フレーム数(再生速度考慮済み)から何回目(0 始まり)の再生かとインスタンス上の再生フレームを算出
+    // 再生回数が指定回数を超えていれば None
+    pub fn play_loop(&self, elapsed_frame: usize, total_frame: usize) -> Option<(usize, usize)> {
+        // 1回の再生時間算出
+        let (start_frame, end_frame) = self.play_range(total_frame);
+        let once_play_time = end_frame - start_frame + 1;
+        // 再生回数と，その再生フレーム値を算出
+        let played_loop_num = elapsed_frame / once_play_time;
+        let mut current_play_frame = elapsed_frame % once_play_time;
+        if let Some(loop_num) = self.loop_num {
+            if loop_num <= played_loop_num {
+                // 再生回数が指定回数を超えてるので終了
+                return None;
+            }
+        }
+
+        if self.is_backward(played_loop_num) {
+            current_play_frame = once_play_time - 1 - current_play_frame;
+        }
+
+        Some((played_loop_num, current_play_frame + start_frame))
+    }
+
+    // インスタンス上の再生フレーム
+    pub fn play_frame(&self, elapsed_frame: usize, total_frame: usize) -> Option<usize> {
+        self.play_loop(elapsed_frame, total_frame)
+            .map(|(_, frame)| frame)
+    }
 }
 
 // インスタンスパーツ再生情報生成
@@ -114,7 +157,7 @@ impl InstanceKeyBuilder {
             start_offset: self.start_offset.unwrap_or(0),
             end_offset: self.end_offset.unwrap_or(0), // 再生終了位置
             reverse: self.reverse.unwrap_or(false),   // 逆再生
-            pingpong: self.reverse.unwrap_or(false),  // 往復再生
+            pingpong: self.pingpong.unwrap_or(false), // 往復再生
         }
     }
 }
@@ -0,0 +1,78 @@
+use super::LinearColor;
+use serde::{Deserialize, Serialize};
+
+// パーツカラーとテクスチャの色の合成方法
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ColorBlend {
+    Mix,
+    Multiply,
+    Add,
+    Subtract,
+}
+
+// キーがない場合は乗算(パーツカラーの初期値が白なので色は変わらない)
+impl Default for ColorBlend {
+    fn default() -> Self {
+        ColorBlend::Multiply
+    }
+}
+
+impl ColorBlend {
+    // テクスチャの色を texel * 乗算成分 + 加算成分 で合成するための値に分解する
+    // アルファはどの合成方法でも白から rate の割合で乗算する
+    pub(crate) fn split(self, color: LinearColor, rate: f32) -> (LinearColor, LinearColor) {
+        let LinearColor(r, g, b, a) = color;
+        let alpha = 1. - rate + a * rate;
+        match self {
+            ColorBlend::Mix => (
+                LinearColor(1. - rate, 1. - rate, 1. - rate, alpha),
+                LinearColor(r * rate, g * rate, b * rate, 0.),
+            ),
+            ColorBlend::Multiply => (
+                LinearColor(
+                    1. - rate + r * rate,
+                    1. - rate + g * rate,
+                    1. - rate + b * rate,
+                    alpha,
+                ),
+                LinearColor(0., 0., 0., 0.),
+            ),
+            ColorBlend::Add => (
+                LinearColor(1., 1., 1., alpha),
+                LinearColor(r * rate, g * rate, b * rate, 0.),
+            ),
+            ColorBlend::Subtract => (
+                LinearColor(1., 1., 1., alpha),
+                LinearColor(-r * rate, -g * rate, -b * rate, 0.),
+            ),
+        }
+    }
+}
+
+// 描画先との合成方法
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlphaBlend {
+    Mix = 0,
+    Multiply,
+    Add,
+    Subtract,
+    Screen,
+    Exclusion,
+}
+
+impl Default for AlphaBlend {
+    fn default() -> Self {
+        AlphaBlend::Mix
+    }
+}
+
+impl AlphaBlend {
+    pub const ALL: [AlphaBlend; 6] = [
+        AlphaBlend::Mix,
+        AlphaBlend::Multiply,
+        AlphaBlend::Add,
+        AlphaBlend::Subtract,
+        AlphaBlend::Screen,
+        AlphaBlend::Exclusion,
+    ];
+}
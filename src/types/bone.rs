@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+// メッシュの頂点に対するボーンの影響
+// ボーンはパーツ番号で参照する
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct BoneWeight {
+    bone_id: usize,
+    weight: f32,
+}
+
+impl BoneWeight {
+    #[cfg(feature = "builder")]
+    pub fn new(bone_id: usize, weight: f32) -> Self {
+        BoneWeight { bone_id, weight }
+    }
+
+    pub fn bone_id(&self) -> usize {
+        self.bone_id
+    }
+
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
+}
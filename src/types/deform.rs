@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+// メッシュパーツの頂点変形キー
+// 頂点番号順にメッシュの頂点からのオフセットを持つ
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeformKey {
+    offsets: Vec<(f32, f32)>,
+}
+
+impl DeformKey {
+    pub fn offsets(&self) -> &[(f32, f32)] {
+        &self.offsets
+    }
+
+    pub fn offset(&self, index: usize) -> (f32, f32) {
+        self.offsets.get(index).copied().unwrap_or((0., 0.))
+    }
+
+    // 頂点数が異なる場合は多い方に合わせ，足りない頂点は変形なしとして扱う
+    pub(crate) fn lerp(&self, other: &DeformKey, rate: f32) -> DeformKey {
+        let len = self.offsets.len().max(other.offsets.len());
+        let offsets = (0..len)
+            .map(|i| {
+                let (x1, y1) = self.offset(i);
+                let (x2, y2) = other.offset(i);
+                ((x2 - x1) * rate + x1, (y2 - y1) * rate + y1)
+            })
+            .collect();
+        DeformKey { offsets }
+    }
+}
+
+#[cfg(feature = "builder")]
+pub struct DeformKeyBuilder {
+    offsets: Vec<(f32, f32)>,
+}
+
+#[cfg(feature = "builder")]
+impl DeformKeyBuilder {
+    pub fn new(vertex_num: usize) -> Self {
+        DeformKeyBuilder {
+            offsets: vec![(0., 0.); vertex_num],
+        }
+    }
+
+    pub fn offset(mut self, index: usize, offset: (f32, f32)) -> Self {
+        if index >= self.offsets.len() {
+            self.offsets.resize(index + 1, (0., 0.));
+        }
+        self.offsets[index] = offset;
+        self
+    }
+
+    pub fn build(self) -> DeformKey {
+        DeformKey {
+            offsets: self.offsets,
+        }
+    }
+}
@@ -23,4 +23,23 @@ where
         pack: T::PackKey,
         animation: T::AnimationKey,
     },
+    // ループの設定でアニメーションを巻き戻したときに再生し終えた回数を通知
+    Loop {
+        entity: Entity,
+        file_id: T::FileId,
+        pack: T::PackKey,
+        animation: T::AnimationKey,
+        count: usize,
+    },
+    // 再生中に通過したユーザーデータのキーを通知
+    // 独立動作でないインスタンスパーツの参照先のキーは参照先のアニメーションとパーツ番号で通知する
+    UserData {
+        entity: Entity,
+        file_id: T::FileId,
+        pack: T::PackKey,
+        animation: T::AnimationKey,
+        part_id: usize,
+        frame: usize,
+        user: T::UserData,
+    },
 }
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+// 親パーツから引き継ぐ値の設定
+// 指定がなければ不透明度以外を引き継ぐ(不透明度は従来通り親の値をかけない)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Inheritance {
+    alpha: bool,
+    flip_h: bool,
+    flip_v: bool,
+    hide: bool,
+    scale: bool,
+}
+
+impl Default for Inheritance {
+    fn default() -> Self {
+        Inheritance {
+            alpha: false,
+            flip_h: true,
+            flip_v: true,
+            hide: true,
+            scale: true,
+        }
+    }
+}
+
+impl Inheritance {
+    pub fn alpha(&self) -> bool {
+        self.alpha
+    }
+
+    pub fn flip_h(&self) -> bool {
+        self.flip_h
+    }
+
+    pub fn flip_v(&self) -> bool {
+        self.flip_v
+    }
+
+    pub fn hide(&self) -> bool {
+        self.hide
+    }
+
+    pub fn scale(&self) -> bool {
+        self.scale
+    }
+}
+
+#[cfg(feature = "builder")]
+pub struct InheritanceBuilder {
+    inheritance: Inheritance,
+}
+
+#[cfg(feature = "builder")]
+impl InheritanceBuilder {
+    pub fn new() -> Self {
+        InheritanceBuilder {
+            inheritance: Inheritance::default(),
+        }
+    }
+
+    pub fn alpha(mut self, alpha: bool) -> Self {
+        self.inheritance.alpha = alpha;
+        self
+    }
+
+    pub fn flip_h(mut self, flip_h: bool) -> Self {
+        self.inheritance.flip_h = flip_h;
+        self
+    }
+
+    pub fn flip_v(mut self, flip_v: bool) -> Self {
+        self.inheritance.flip_v = flip_v;
+        self
+    }
+
+    pub fn hide(mut self, hide: bool) -> Self {
+        self.inheritance.hide = hide;
+        self
+    }
+
+    pub fn scale(mut self, scale: bool) -> Self {
+        self.inheritance.scale = scale;
+        self
+    }
+
+    pub fn build(self) -> Inheritance {
+        self.inheritance
+    }
+}
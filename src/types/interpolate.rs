@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 // 補完関数
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 pub enum Interpolation {
     Linear,
     Hermite,
@@ -10,22 +10,106 @@ pub enum Interpolation {
     Step,
 }
 
+// エルミート，ベジエ補完のカーブパラメータ
+// 時間は各キーからのフレーム数，値は SpriteStudio の保存形式のまま持つ
+// (ベジエは各キーの値からの差分，エルミートはキーの値との差が接線になる)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+pub struct Curve {
+    start_time: f32,
+    start_value: f32,
+    end_time: f32,
+    end_value: f32,
+}
+
+impl Curve {
+    pub fn new(start_time: f32, start_value: f32, end_time: f32, end_value: f32) -> Self {
+        Curve {
+            start_time,
+            start_value,
+            end_time,
+            end_value,
+        }
+    }
+
+    pub fn start_time(&self) -> f32 {
+        self.start_time
+    }
+
+    pub fn start_value(&self) -> f32 {
+        self.start_value
+    }
+
+    pub fn end_time(&self) -> f32 {
+        self.end_time
+    }
+
+    pub fn end_value(&self) -> f32 {
+        self.end_value
+    }
+}
+
+// キー追加時の補完情報
+// Interpolation からも変換できるのでカーブが不要な場合はそのまま渡せる
+#[derive(Debug, Clone, Copy)]
+pub struct KeyInterpolation {
+    pub interpolation: Interpolation,
+    pub curve: Option<Curve>,
+}
+
+impl KeyInterpolation {
+    pub fn with_curve(interpolation: Interpolation, curve: Curve) -> Self {
+        KeyInterpolation {
+            interpolation,
+            curve: Some(curve),
+        }
+    }
+}
+
+impl From<Interpolation> for KeyInterpolation {
+    fn from(interpolation: Interpolation) -> Self {
+        KeyInterpolation {
+            interpolation,
+            curve: None,
+        }
+    }
+}
+
 // 補完関数に合わせてf32の値を返す
 impl Interpolation {
     // 0.0 ~ 1.0 の値でその時間に合わせた補完割合を返す
+    // カーブ補完はパラメータがないので線形補間として扱う
     pub fn calc_rate(&self, time: f32) -> f32 {
         // もし0.0 ~ 1.0 の外にいたら中になるように補正する
         let time = num::clamp(time, 0.0, 1.0);
 
         match self {
             Interpolation::Linear => lerp(time),
-            Interpolation::Hermite => hermite(time),
-            Interpolation::Bezier => bezier(time),
+            Interpolation::Hermite => lerp(time),
+            Interpolation::Bezier => lerp(time),
             Interpolation::Acceleration => acceleration(time),
             Interpolation::Deceleration => deceleration(time),
             Interpolation::Step => step(time),
         }
     }
+
+    // start ~ end の間で 0.0 ~ 1.0 の時間に合わせた値を返す
+    // frames はキー間のフレーム数(ベジエの時間軸に使う)
+    pub fn interpolate(
+        &self,
+        time: f32,
+        start: f32,
+        end: f32,
+        frames: f32,
+        curve: Option<&Curve>,
+    ) -> f32 {
+        let time = num::clamp(time, 0.0, 1.0);
+
+        match (self, curve) {
+            (Interpolation::Hermite, Some(curve)) => hermite(time, start, end, curve),
+            (Interpolation::Bezier, Some(curve)) => bezier(time, start, end, frames, curve),
+            _ => (end - start) * self.calc_rate(time) + start,
+        }
+    }
 }
 
 // 線形補間なのでtime をそのまま返す
@@ -51,13 +135,127 @@ fn deceleration(time: f32) -> f32 {
 }
 
 // エルミート補完
-fn hermite(_: f32) -> f32 {
-    // エルミート補完がよくわからんので仮実装
-    unimplemented!("unimplement hermite")
+// カーブの値とキーの値の差を始点，終点の接線として使う
+// 接線はキー間を 1 とした時間に対する変化量なので time はキー間の割合のまま使う
+fn hermite(time: f32, start: f32, end: f32, curve: &Curve) -> f32 {
+    let t2 = time * time;
+    let t3 = t2 * time;
+    (2. * t3 - 3. * t2 + 1.) * start
+        + (-2. * t3 + 3. * t2) * end
+        + (t3 - 2. * t2 + time) * (curve.start_value - start)
+        + (t3 - t2) * (curve.end_value - end)
 }
 
 // ベジエ曲線補完
-fn bezier(_: f32) -> f32 {
-    // ベジエの補完がよくわからんので仮実装
-    unimplemented!("unimplement bezier")
+// 制御点の時間軸が等間隔ではないので，現在時間になる媒介変数を二分探索で求めてから値を計算する
+fn bezier(time: f32, start: f32, end: f32, frames: f32, curve: &Curve) -> f32 {
+    let cubic = |t: f32, p0: f32, p1: f32, p2: f32, p3: f32| {
+        let r = 1. - t;
+        r * r * r * p0 + 3. * r * r * t * p1 + 3. * r * t * t * p2 + t * t * t * p3
+    };
+
+    let current = frames * time;
+    let mut t = 0.5;
+    let mut range = 0.5;
+    for _ in 0..8 {
+        let x = cubic(t, 0., curve.start_time, frames + curve.end_time, frames);
+        range /= 2.;
+        if x > current {
+            t -= range;
+        } else {
+            t += range;
+        }
+    }
+
+    cubic(
+        t,
+        start,
+        start + curve.start_value,
+        end + curve.end_value,
+        end,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 期待値は SpriteStudio のランタイムの補完計算(SsInterpolation)で求めた値
+    fn assert_near(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "actual: {}, expected: {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn hermite_end_points() {
+        let curve = Curve::new(0., 40., 0., 0.);
+        let hermite = Interpolation::Hermite;
+        assert_near(hermite.interpolate(0., 10., 30., 8., Some(&curve)), 10.);
+        assert_near(hermite.interpolate(1., 10., 30., 8., Some(&curve)), 30.);
+    }
+
+    #[test]
+    fn hermite_tangent_is_relative_to_key_value() {
+        let hermite = Interpolation::Hermite;
+        // カーブの値がキーの値と同じなら接線は 0
+        let flat = Curve::new(0., 0., 0., 100.);
+        assert_near(hermite.interpolate(0.5, 0., 100., 10., Some(&flat)), 50.);
+        assert_near(
+            hermite.interpolate(0.25, 0., 100., 10., Some(&flat)),
+            15.625,
+        );
+
+        let curve = Curve::new(0., 50., 0., 100.);
+        assert_near(hermite.interpolate(0.5, 0., 100., 10., Some(&curve)), 56.25);
+
+        let curve = Curve::new(0., 40., 0., 0.);
+        assert_near(hermite.interpolate(0.25, 10., 30., 8., Some(&curve)), 18.75);
+        assert_near(hermite.interpolate(0.75, 10., 30., 8., Some(&curve)), 32.5);
+    }
+
+    #[test]
+    fn bezier_end_points() {
+        let curve = Curve::new(5., 0., -5., 0.);
+        let bezier = Interpolation::Bezier;
+        assert_near(bezier.interpolate(0., 0., 100., 10., Some(&curve)), 0.);
+        assert_near(bezier.interpolate(1., 0., 100., 10., Some(&curve)), 100.);
+    }
+
+    #[test]
+    fn bezier_matches_sprite_studio() {
+        let bezier = Interpolation::Bezier;
+        // イーズインアウト
+        let curve = Curve::new(5., 0., -5., 0.);
+        assert_near(
+            bezier.interpolate(0.3, 0., 100., 10., Some(&curve)),
+            15.845297,
+        );
+        assert_near(
+            bezier.interpolate(0.5, 0., 100., 10., Some(&curve)),
+            50.292967,
+        );
+
+        // 値が減少するキーで始点側の制御点が上に出ている
+        let curve = Curve::new(2., 30., -2., 0.);
+        assert_near(
+            bezier.interpolate(0.25, 10., -20., 8., Some(&curve)),
+            17.534990,
+        );
+    }
+
+    #[test]
+    fn curve_without_parameters_is_linear() {
+        assert_near(
+            Interpolation::Hermite.interpolate(0.25, 0., 8., 4., None),
+            2.,
+        );
+        assert_near(
+            Interpolation::Bezier.interpolate(0.75, 0., 8., 4., None),
+            6.,
+        );
+    }
 }
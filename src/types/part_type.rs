@@ -1,17 +1,15 @@
 use serde::*;
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum PartType {
     Null,
     Normal,
     Text,
     Instance,
-
-    // ↓アニメーション的に複雑なので余裕のあるときに対応
     Mesh,
     Bone,
     Joint,
     Armature,
     Effect,
-    Mask,
+    Mask, // シリアライズ時の番号が変わらないように末尾に追加する
 }
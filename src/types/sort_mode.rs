@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+// パーツの描画順の決め方(SpriteStudio のアニメーション設定)
+// 省略時は従来通り Z 座標で並べる
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum SortMode {
+    Priority, // 描画優先度の順．同じ場合はパーツの並び順
+    Z,        // Z 座標の順．同じ場合は描画優先度の順
+}
+
+impl Default for SortMode {
+    fn default() -> Self {
+        SortMode::Z
+    }
+}
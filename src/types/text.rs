@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+// テキストの揃え位置(パーツの原点に対して)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+// テキストパーツの表示内容
+// フォントはビットマップフォントとして使うセルマップ番号
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TextKey {
+    text: String,
+    font: usize,
+    size: f32, // 一行の高さ(ピクセル)
+    align: TextAlign,
+}
+
+impl TextKey {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn font(&self) -> usize {
+        self.font
+    }
+
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    pub fn align(&self) -> TextAlign {
+        self.align
+    }
+}
+
+#[cfg(feature = "builder")]
+pub struct TextKeyBuilder {
+    text: String,
+    font: usize,
+    size: Option<f32>,
+    align: Option<TextAlign>,
+}
+
+#[cfg(feature = "builder")]
+impl TextKeyBuilder {
+    pub fn new<S: Into<String>>(text: S, font: usize) -> Self {
+        TextKeyBuilder {
+            text: text.into(),
+            font,
+            size: None,
+            align: None,
+        }
+    }
+
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn align(mut self, align: TextAlign) -> Self {
+        self.align = Some(align);
+        self
+    }
+
+    pub fn build(self) -> TextKey {
+        TextKey {
+            text: self.text,
+            font: self.font,
+            size: self.size.unwrap_or(0.), // 0 のときはフォントの高さそのまま
+            align: self.align.unwrap_or(TextAlign::Left),
+        }
+    }
+}
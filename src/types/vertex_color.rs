@@ -0,0 +1,115 @@
+use super::LinearColor;
+use serde::{Deserialize, Serialize};
+use std::ops::{Add, Mul, Sub};
+
+// 頂点ごとのパーツカラーのキー
+// 色と合成の割合を四隅それぞれに持つ
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct VertexColorKey {
+    lt: (LinearColor, f32), // 左上
+    rt: (LinearColor, f32), // 右上
+    lb: (LinearColor, f32), // 左下
+    rb: (LinearColor, f32), // 右下
+}
+
+impl VertexColorKey {
+    pub fn lt(&self) -> (LinearColor, f32) {
+        self.lt
+    }
+    pub fn rt(&self) -> (LinearColor, f32) {
+        self.rt
+    }
+    pub fn lb(&self) -> (LinearColor, f32) {
+        self.lb
+    }
+    pub fn rb(&self) -> (LinearColor, f32) {
+        self.rb
+    }
+}
+
+impl Add<VertexColorKey> for VertexColorKey {
+    type Output = Self;
+
+    fn add(self, rhs: VertexColorKey) -> Self::Output {
+        VertexColorKey {
+            lt: (self.lt.0 + rhs.lt.0, self.lt.1 + rhs.lt.1),
+            rt: (self.rt.0 + rhs.rt.0, self.rt.1 + rhs.rt.1),
+            lb: (self.lb.0 + rhs.lb.0, self.lb.1 + rhs.lb.1),
+            rb: (self.rb.0 + rhs.rb.0, self.rb.1 + rhs.rb.1),
+        }
+    }
+}
+
+impl Sub<VertexColorKey> for VertexColorKey {
+    type Output = Self;
+
+    fn sub(self, rhs: VertexColorKey) -> Self::Output {
+        VertexColorKey {
+            lt: (self.lt.0 - rhs.lt.0, self.lt.1 - rhs.lt.1),
+            rt: (self.rt.0 - rhs.rt.0, self.rt.1 - rhs.rt.1),
+            lb: (self.lb.0 - rhs.lb.0, self.lb.1 - rhs.lb.1),
+            rb: (self.rb.0 - rhs.rb.0, self.rb.1 - rhs.rb.1),
+        }
+    }
+}
+
+impl Mul<f32> for VertexColorKey {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        VertexColorKey {
+            lt: (self.lt.0 * rhs, self.lt.1 * rhs),
+            rt: (self.rt.0 * rhs, self.rt.1 * rhs),
+            lb: (self.lb.0 * rhs, self.lb.1 * rhs),
+            rb: (self.rb.0 * rhs, self.rb.1 * rhs),
+        }
+    }
+}
+
+#[cfg(feature = "builder")]
+pub struct VertexColorKeyBuilder {
+    lt: Option<(LinearColor, f32)>, // 左上
+    rt: Option<(LinearColor, f32)>, // 右上
+    lb: Option<(LinearColor, f32)>, // 左下
+    rb: Option<(LinearColor, f32)>, // 右下
+}
+
+#[cfg(feature = "builder")]
+impl VertexColorKeyBuilder {
+    pub fn new() -> Self {
+        VertexColorKeyBuilder {
+            lt: None,
+            rt: None,
+            lb: None,
+            rb: None,
+        }
+    }
+
+    pub fn lt(mut self, color: LinearColor, rate: f32) -> Self {
+        self.lt = (color, rate).into();
+        self
+    }
+    pub fn rt(mut self, color: LinearColor, rate: f32) -> Self {
+        self.rt = (color, rate).into();
+        self
+    }
+    pub fn lb(mut self, color: LinearColor, rate: f32) -> Self {
+        self.lb = (color, rate).into();
+        self
+    }
+    pub fn rb(mut self, color: LinearColor, rate: f32) -> Self {
+        self.rb = (color, rate).into();
+        self
+    }
+
+    // 指定されていない頂点は色を変えない
+    pub fn build(self) -> VertexColorKey {
+        let none = (LinearColor::default(), 0.);
+        VertexColorKey {
+            lt: self.lt.unwrap_or(none),
+            rt: self.rt.unwrap_or(none),
+            lb: self.lb.unwrap_or(none),
+            rb: self.rb.unwrap_or(none),
+        }
+    }
+}